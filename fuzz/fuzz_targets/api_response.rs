@@ -0,0 +1,29 @@
+//! Feeds arbitrary JSON to `ApiResponse::from_json`.
+//!
+//! Run with `cargo fuzz run api_response`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ironshield::client::response::ApiResponse;
+use serde_json::Value;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(value) = serde_json::from_slice::<Value>(data) else {
+        return;
+    };
+
+    let has_status: bool = value.get("status").is_some_and(|status| !status.is_null());
+
+    match ApiResponse::from_json(value) {
+        Ok(response) => {
+            assert!(has_status, "accepted a response without a status");
+
+            // Extraction must fail cleanly rather than panic.
+            let _ = response.extract_challenge();
+            let _ = response.extract_token();
+            let _ = response.extract_tokens();
+        },
+        Err(error) => assert!(error.to_string().starts_with("Processing failed: Invalid API response")),
+    }
+});
@@ -0,0 +1,353 @@
+//! # Caches for solved challenges and issued tokens.
+
+use ironshield_types::{
+    IronShieldChallenge,
+    IronShieldChallengeResponse,
+    IronShieldToken
+};
+
+use crate::client::clock::Clock;
+use crate::client::token::token_is_expired_at;
+
+use std::collections::{
+    BTreeMap,
+    HashMap
+};
+use std::sync::{
+    Mutex,
+    PoisonError
+};
+
+/// Bounded least-recently-used cache of solved challenges.
+///
+/// Attach to a `ClientConfig` with `set_solve_cache` to have
+/// `solve_challenge` return the cached response when the same
+/// challenge is presented again. Challenges are normally
+/// unique, so this only helps when a challenge is re-issued
+/// verbatim, e.g. on a retry.
+///
+/// Entries are keyed by the challenge's full serialized
+/// content, so distinct challenges never collide.
+#[derive(Debug)]
+pub struct SolveCache {
+    max_size: usize,
+    entries:  Mutex<CacheEntries>,
+}
+
+/// Cached responses with the tick of their last use, and
+/// their keys ordered by that tick, least recently used
+/// first.
+#[derive(Debug, Default)]
+struct CacheEntries {
+    responses: HashMap<String, (IronShieldChallengeResponse, u64)>,
+    order:     BTreeMap<u64, String>,
+    next_tick: u64,
+}
+
+impl CacheEntries {
+    /// # Returns
+    /// * `u64`: A tick later than every tick handed out so far.
+    fn tick(&mut self) -> u64 {
+        self.next_tick += 1;
+        self.next_tick
+    }
+
+    /// Marks `key` as the most recently used entry.
+    fn touch(&mut self, key: &str) {
+        let tick: u64 = self.tick();
+
+        if let Some((_, last_used)) = self.responses.get_mut(key) {
+            if let Some(entry) = self.order.remove(last_used) {
+                self.order.insert(tick, entry);
+            }
+            *last_used = tick;
+        }
+    }
+}
+
+impl SolveCache {
+    /// # Arguments
+    /// * `max_size`: Maximum number of responses to keep.
+    ///
+    /// # Returns
+    /// * `Self`: An empty cache.
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            entries: Mutex::new(CacheEntries::default()),
+        }
+    }
+
+    /// # Arguments
+    /// * `challenge`: The challenge to look up.
+    ///
+    /// # Returns
+    /// * `Option<IronShieldChallengeResponse>`: The cached
+    ///                                          response, if
+    ///                                          present.
+    pub fn get(&self, challenge: &IronShieldChallenge) -> Option<IronShieldChallengeResponse> {
+        let key: String = Self::key(challenge)?;
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let response: IronShieldChallengeResponse = entries.responses.get(&key)?.0.clone();
+        entries.touch(&key);
+
+        Some(response)
+    }
+
+    /// Stores a solved challenge, evicting the least recently
+    /// used entry if the cache is full.
+    ///
+    /// # Arguments
+    /// * `challenge`: The challenge that was solved.
+    /// * `response`:  Its solution.
+    pub fn insert(
+        &self,
+        challenge: &IronShieldChallenge,
+        response:  IronShieldChallengeResponse,
+    ) {
+        if self.max_size == 0 {
+            return;
+        }
+
+        let Some(key) = Self::key(challenge) else {
+            return;
+        };
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some((cached, _)) = entries.responses.get_mut(&key) {
+            *cached = response;
+            entries.touch(&key);
+            return;
+        }
+
+        let tick: u64 = entries.tick();
+        entries.responses.insert(key.clone(), (response, tick));
+        entries.order.insert(tick, key);
+
+        while entries.order.len() > self.max_size {
+            if let Some((_, evicted)) = entries.order.pop_first() {
+                entries.responses.remove(&evicted);
+            }
+        }
+    }
+
+    /// # Returns
+    /// * `usize`: Number of cached responses.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner).responses.len()
+    }
+
+    /// # Returns
+    /// * `bool`: `true` if nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// # Arguments
+    /// * `challenge`: The challenge to derive a key for.
+    ///
+    /// # Returns
+    /// * `Option<String>`: The challenge's serialized content.
+    fn key(challenge: &IronShieldChallenge) -> Option<String> {
+        serde_json::to_string(challenge).ok()
+    }
+}
+
+/// Tokens issued per endpoint, attached to a `ClientConfig`
+/// with `set_token_cache`.
+///
+/// `validate_challenge` returns a cached token for the
+/// endpoint without fetching or solving anything while the
+/// token is outside `ClientConfig::token_refresh_margin` of
+/// its expiry, and stores each token it obtains.
+#[derive(Default)]
+pub struct TokenCache {
+    tokens: Mutex<HashMap<String, CachedToken>>,
+}
+
+impl std::fmt::Debug for TokenCache {
+    /// Formats the cache without its tokens so they never
+    /// end up in logs.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let tokens = self.tokens.lock().unwrap_or_else(PoisonError::into_inner);
+
+        f.debug_struct("TokenCache")
+            .field("endpoints", &tokens.len())
+            .finish()
+    }
+}
+
+/// A cached token and when it was stored.
+#[derive(Debug, Clone)]
+struct CachedToken {
+    token:     IronShieldToken,
+    stored_at: i64,
+}
+
+impl TokenCache {
+    /// # Returns
+    /// * `Self`: An empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// # Arguments
+    /// * `endpoint`:       The endpoint the token was issued for.
+    /// * `clock`:          Source of the current time.
+    /// * `refresh_margin`: Fraction of the token's lifetime,
+    ///                     counted back from expiry, during
+    ///                     which it is no longer reused.
+    ///
+    /// # Returns
+    /// * `Option<IronShieldToken>`: The cached token, if
+    ///                              present and not within
+    ///                              `refresh_margin` of expiry.
+    pub fn get(
+        &self,
+        endpoint:       &str,
+        clock:          &dyn Clock,
+        refresh_margin: f64,
+    ) -> Option<IronShieldToken> {
+        let tokens = self.tokens.lock().unwrap_or_else(PoisonError::into_inner);
+        let cached: &CachedToken = tokens.get(endpoint)?;
+
+        let lifetime: i64 = cached.token.valid_for.saturating_sub(cached.stored_at).max(0);
+        let margin:   i64 = (lifetime as f64 * refresh_margin.clamp(0.0, 1.0)) as i64;
+
+        let refresh_at: i64 = cached.token.valid_for.saturating_sub(margin);
+
+        if token_is_expired_at(&cached.token, clock) || clock.now_millis() >= refresh_at {
+            None
+        } else {
+            Some(cached.token.clone())
+        }
+    }
+
+    /// Stores the token for `endpoint`, replacing any
+    /// previous one.
+    ///
+    /// # Arguments
+    /// * `endpoint`: The endpoint the token was issued for.
+    /// * `token`:    The issued token.
+    /// * `clock`:    Source of the current time.
+    pub fn insert(
+        &self,
+        endpoint: &str,
+        token:    IronShieldToken,
+        clock:    &dyn Clock,
+    ) {
+        self.tokens
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(endpoint.to_string(), CachedToken { token, stored_at: clock.now_millis() });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::clock::FixedClock;
+
+    /// # Arguments
+    /// * `valid_for`: End of the token's validity.
+    ///
+    /// # Returns
+    /// * `IronShieldToken`: An unsigned token.
+    fn token(valid_for: i64) -> IronShieldToken {
+        IronShieldToken::new([0; 64], valid_for, [0; 32], [0; 64])
+    }
+
+    /// # Arguments
+    /// * `seed`: Seed for the challenge, so seeds give
+    ///           distinct challenges.
+    ///
+    /// # Returns
+    /// * `(IronShieldChallenge, IronShieldChallengeResponse)`:
+    ///   A trivial challenge and its solution.
+    #[cfg(feature = "test-util")]
+    fn solved(seed: u64) -> (IronShieldChallenge, IronShieldChallengeResponse) {
+        let challenge = crate::client::challenge::generate_challenge_with_seed(0, "https://example.com", seed);
+        let response = crate::client::solve::solve_blocking(
+            challenge.clone(),
+            crate::client::solve::SolveConfig::new(&crate::client::config::ClientConfig::default(), false),
+        ).unwrap();
+
+        (challenge, response)
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn test_solve_cache_get_and_insert() {
+        let cache = SolveCache::new(2);
+        let (challenge, response) = solved(1);
+        let (other, _) = solved(2);
+
+        assert!(cache.get(&challenge).is_none());
+        cache.insert(&challenge, response.clone());
+        cache.insert(&challenge, response.clone());
+
+        assert_eq!(cache.len(), 1);
+        assert_eq!(serde_json::to_value(cache.get(&challenge)).unwrap(), serde_json::to_value(&response).unwrap());
+        assert!(cache.get(&other).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn test_solve_cache_evicts_least_recently_used() {
+        let cache = SolveCache::new(2);
+        let (first, first_response) = solved(1);
+        let (second, second_response) = solved(2);
+        let (third, third_response) = solved(3);
+
+        cache.insert(&first, first_response);
+        cache.insert(&second, second_response);
+
+        // Using `first` leaves `second` as the eviction candidate.
+        assert!(cache.get(&first).is_some());
+        cache.insert(&third, third_response);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&first).is_some());
+        assert!(cache.get(&second).is_none());
+        assert!(cache.get(&third).is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn test_solve_cache_with_zero_size_stores_nothing() {
+        let cache = SolveCache::new(0);
+        let (challenge, response) = solved(1);
+
+        cache.insert(&challenge, response);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_token_cache_reuses_until_margin() {
+        let clock = FixedClock::new(0);
+        let cache = TokenCache::new();
+        cache.insert("https://example.com", token(10_000), &clock);
+
+        assert!(cache.get("https://example.com", &clock, 0.1).is_some());
+        assert!(cache.get("https://other.example.com", &clock, 0.1).is_none());
+
+        // The last 10% of the 10s lifetime starts at 9s.
+        clock.set(8_999);
+        assert!(cache.get("https://example.com", &clock, 0.1).is_some());
+        clock.set(9_000);
+        assert!(cache.get("https://example.com", &clock, 0.1).is_none());
+    }
+
+    #[test]
+    fn test_token_cache_drops_expired_tokens() {
+        let clock = FixedClock::new(0);
+        let cache = TokenCache::new();
+        cache.insert("https://example.com", token(10_000), &clock);
+
+        clock.set(9_999);
+        assert!(cache.get("https://example.com", &clock, 0.0).is_some());
+        clock.set(10_000);
+        assert!(cache.get("https://example.com", &clock, 0.0).is_none());
+    }
+}
@@ -0,0 +1,479 @@
+//! # Challenge inspection helpers.
+
+use ironshield_types::{
+    chrono,
+    create_signing_message,
+    IronShieldChallenge
+};
+
+#[cfg(feature = "test-util")]
+use crate::client::solve::leading_zero_target;
+use crate::client::clock::{
+    Clock,
+    SystemClock
+};
+use crate::client::stats::format_number_with_commas;
+
+use std::time::Duration;
+
+/// How long generated challenges stay valid.
+#[cfg(feature = "test-util")]
+const GENERATED_CHALLENGE_LIFETIME_MS: i64 = 5 * 60 * 1000;
+
+/// A fetched challenge with the metadata needed to present
+/// and solve it, as returned by
+/// `IronShieldClient::prepare_challenge`.
+///
+/// * `challenge`:          The challenge to solve.
+/// * `difficulty_label`:   See `difficulty_label`.
+/// * `estimated_attempts`: Attempts a solve is expected to
+///                         take.
+/// * `expires_at`:         When the challenge expires, as
+///                         Unix epoch milliseconds.
+#[derive(Debug, Clone)]
+pub struct PreparedChallenge {
+    pub challenge:          IronShieldChallenge,
+    pub difficulty_label:   &'static str,
+    pub estimated_attempts: u64,
+    pub expires_at:         i64,
+}
+
+impl PreparedChallenge {
+    /// # Arguments
+    /// * `challenge`: The challenge to prepare.
+    ///
+    /// # Returns
+    /// * `Self`: The challenge with its metadata derived.
+    pub fn new(challenge: IronShieldChallenge) -> Self {
+        Self {
+            difficulty_label:   difficulty_label(challenge.recommended_attempts),
+            estimated_attempts: challenge.recommended_attempts,
+            expires_at:         challenge.expiration_time,
+            challenge,
+        }
+    }
+}
+
+/// How hard an endpoint's challenges currently are, as
+/// returned by `IronShieldClient::probe_difficulty`.
+///
+/// * `bits`:                 Leading zero bits a solution needs.
+/// * `recommended_attempts`: Attempts the API expects a solve
+///                           to take.
+/// * `label`:                See `difficulty_label`.
+/// * `estimated_solve_time`: `recommended_attempts` at
+///                           `ClientConfig::expected_hash_rate`,
+///                           if set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DifficultyInfo {
+    pub bits:                 u32,
+    pub recommended_attempts: u64,
+    pub label:                &'static str,
+    pub estimated_solve_time: Option<Duration>,
+}
+
+impl DifficultyInfo {
+    /// # Arguments
+    /// * `challenge`: The challenge to inspect.
+    /// * `hash_rate`: Attempts per second to estimate the
+    ///                solve time with, if known.
+    ///
+    /// # Returns
+    /// * `Self`: The challenge's difficulty.
+    pub fn new(challenge: &IronShieldChallenge, hash_rate: Option<u64>) -> Self {
+        Self {
+            bits:                 difficulty_bits(challenge),
+            recommended_attempts: challenge.recommended_attempts,
+            label:                difficulty_label(challenge.recommended_attempts),
+            estimated_solve_time: hash_rate
+                .filter(|rate| *rate > 0)
+                .map(|rate| Duration::from_secs_f64(challenge.recommended_attempts as f64 / rate as f64)),
+        }
+    }
+}
+
+/// Human-readable label for a challenge's difficulty.
+///
+/// # Arguments
+/// * `recommended_attempts`: The challenge's recommended
+///                           number of attempts.
+///
+/// # Returns
+/// * `&'static str`: `Trivial` below 10,000 attempts,
+///                   `Easy` below 1,000,000, `Medium` below
+///                   10,000,000, `Hard` below 100,000,000,
+///                   and `Extreme` otherwise.
+pub fn difficulty_label(recommended_attempts: u64) -> &'static str {
+    match recommended_attempts {
+        0..10_000                => "Trivial",
+        10_000..1_000_000        => "Easy",
+        1_000_000..10_000_000    => "Medium",
+        10_000_000..100_000_000  => "Hard",
+        _                        => "Extreme",
+    }
+}
+
+/// Number of leading zero bits in a challenge's target.
+///
+/// A hash must be below the target, so this is roughly
+/// the number of leading zero bits a solution needs.
+///
+/// # Arguments
+/// * `challenge`: The challenge to inspect.
+///
+/// # Returns
+/// * `u32`: Leading zero bits of `challenge_param`.
+pub fn difficulty_bits(challenge: &IronShieldChallenge) -> u32 {
+    let mut bits: u32 = 0;
+
+    for byte in challenge.challenge_param.iter() {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+
+    bits
+}
+
+/// The exact bytes the API signs and verifies a challenge
+/// over, e.g. to reproduce signature checks with an HSM or
+/// other external tooling.
+///
+/// Delegates to `ironshield_types`, so the encoding always
+/// matches the one used for `challenge_signature`: the
+/// nonce, creation and expiration times, website ID, and
+/// hex-encoded target and public key, joined by `|`.
+///
+/// # Arguments
+/// * `challenge`: The challenge to encode.
+///
+/// # Returns
+/// * `Vec<u8>`: The canonical UTF-8 signing message.
+pub fn challenge_canonical_bytes(challenge: &IronShieldChallenge) -> Vec<u8> {
+    create_signing_message(
+        &challenge.random_nonce,
+        challenge.created_time,
+        challenge.expiration_time,
+        &challenge.website_id,
+        &challenge.challenge_param,
+        &challenge.public_key,
+    ).into_bytes()
+}
+
+/// Whether a previously obtained challenge is still worth
+/// solving.
+///
+/// # Arguments
+/// * `challenge`: The challenge to check.
+/// * `max_age`:   Oldest the challenge may be.
+///
+/// # Returns
+/// * `bool`: `true` if the challenge was created less than
+///           `max_age` ago and has not expired.
+pub fn challenge_is_fresh(challenge: &IronShieldChallenge, max_age: Duration) -> bool {
+    challenge_is_fresh_at(challenge, max_age, &SystemClock)
+}
+
+/// Like `challenge_is_fresh`, reading the time from `clock`.
+///
+/// # Arguments
+/// * `challenge`: The challenge to check.
+/// * `max_age`:   Oldest the challenge may be.
+/// * `clock`:     Source of the current time.
+///
+/// # Returns
+/// * `bool`: Whether the challenge is fresh according to
+///           `clock`.
+pub fn challenge_is_fresh_at(
+    challenge: &IronShieldChallenge,
+    max_age:   Duration,
+    clock:     &dyn Clock,
+) -> bool {
+    let now: i64 = clock.now_millis();
+    let age_ms: i64 = now.saturating_sub(challenge.created_time);
+    let max_age_ms: i64 = i64::try_from(max_age.as_millis()).unwrap_or(i64::MAX);
+
+    age_ms < max_age_ms && now < challenge.expiration_time
+}
+
+/// Formats a Unix millisecond timestamp for display.
+///
+/// # Arguments
+/// * `millis`: Unix epoch milliseconds.
+///
+/// # Returns
+/// * `String`: An RFC 3339 UTC timestamp, or the raw value
+///             if it is out of range.
+fn format_timestamp(millis: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis)
+        .map(|time| time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true))
+        .unwrap_or_else(|| format!("{}ms", millis))
+}
+
+/// Renders a challenge's key fields for debugging.
+///
+/// # Arguments
+/// * `challenge`: The challenge to describe.
+///
+/// # Returns
+/// * `String`: A multi-line description including the
+///             difficulty label, human-readable timestamps,
+///             and time until expiry.
+pub fn describe_challenge(challenge: &IronShieldChallenge) -> String {
+    describe_challenge_at(challenge, &SystemClock)
+}
+
+/// Like `describe_challenge`, reading the time from `clock`,
+/// e.g. `ClientConfig::clock`.
+///
+/// # Arguments
+/// * `challenge`: The challenge to describe.
+/// * `clock`:     Source of the current time.
+///
+/// # Returns
+/// * `String`: The description, with the time until expiry
+///             according to `clock`.
+pub fn describe_challenge_at(challenge: &IronShieldChallenge, clock: &dyn Clock) -> String {
+    let remaining_ms: i64 = challenge.expiration_time.saturating_sub(clock.now_millis());
+
+    let expiry: String = if remaining_ms > 0 {
+        format!("expires in {:.1}s", remaining_ms as f64 / 1000.0)
+    } else {
+        format!("expired {:.1}s ago", -remaining_ms as f64 / 1000.0)
+    };
+
+    let target: String = challenge.challenge_param
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    format!(
+        "Challenge for website '{}'\n\
+         \x20 Difficulty:   {} ({} leading zero bits)\n\
+         \x20 Attempts:     {} recommended\n\
+         \x20 Target:       {}\n\
+         \x20 Nonce:        {}\n\
+         \x20 Created:      {}\n\
+         \x20 Expires:      {} ({})",
+        challenge.website_id,
+        difficulty_label(challenge.recommended_attempts),
+        difficulty_bits(challenge),
+        format_number_with_commas(challenge.recommended_attempts),
+        target,
+        challenge.random_nonce,
+        format_timestamp(challenge.created_time),
+        format_timestamp(challenge.expiration_time),
+        expiry,
+    )
+}
+
+/// Builds a well-formed challenge locally, e.g. to load-test
+/// the solve path without the API. Equivalent to
+/// `generate_challenge_with_seed` with a seed of `0`.
+///
+/// # Arguments
+/// * `difficulty_bits`: Leading zero bits a solution needs.
+/// * `endpoint`:        Stored as the challenge's website ID.
+///
+/// # Returns
+/// * `IronShieldChallenge`: A solvable, unsigned challenge.
+#[cfg(feature = "test-util")]
+pub fn generate_challenge(difficulty_bits: u32, endpoint: &str) -> IronShieldChallenge {
+    generate_challenge_with_seed(difficulty_bits, endpoint, 0)
+}
+
+/// Builds a well-formed challenge locally from a seed.
+///
+/// The nonce and target depend only on the arguments, so
+/// the same seed always yields the same solution. The
+/// timestamps are taken from the current time so the
+/// challenge is not expired. The challenge is not signed
+/// and will not validate server-side.
+///
+/// # Arguments
+/// * `difficulty_bits`: Leading zero bits a solution needs.
+/// * `endpoint`:        Stored as the challenge's website ID.
+/// * `seed`:            Seed for the random nonce.
+///
+/// # Returns
+/// * `IronShieldChallenge`: A solvable, unsigned challenge.
+///
+/// # Example
+/// ```
+/// use ironshield::client::challenge::{difficulty_bits, generate_challenge_with_seed};
+///
+/// let challenge = generate_challenge_with_seed(8, "https://example.com", 42);
+/// assert_eq!(difficulty_bits(&challenge), 8);
+/// ```
+#[cfg(feature = "test-util")]
+pub fn generate_challenge_with_seed(
+    difficulty_bits: u32,
+    endpoint:        &str,
+    seed:            u64,
+) -> IronShieldChallenge {
+    let mut state: u64 = seed;
+    let random_nonce: String = (0..2)
+        .map(|_| format!("{:016x}", splitmix64(&mut state)))
+        .collect();
+    let created_time: i64 = chrono::Utc::now().timestamp_millis();
+
+    IronShieldChallenge {
+        random_nonce,
+        created_time,
+        expiration_time:      created_time + GENERATED_CHALLENGE_LIFETIME_MS,
+        website_id:           endpoint.to_string(),
+        challenge_param:      leading_zero_target(difficulty_bits),
+        recommended_attempts: 1u64.checked_shl(difficulty_bits).unwrap_or(u64::MAX),
+        public_key:           [0; 32],
+        challenge_signature:  [0; 64],
+    }
+}
+
+/// Advances a SplitMix64 generator.
+///
+/// # Arguments
+/// * `state`: The generator state.
+///
+/// # Returns
+/// * `u64`: The next pseudo-random value.
+#[cfg(feature = "test-util")]
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+    let mut z: u64 = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_difficulty_label() {
+        assert_eq!(difficulty_label(0), "Trivial");
+        assert_eq!(difficulty_label(10_000), "Easy");
+        assert_eq!(difficulty_label(5_000_000), "Medium");
+        assert_eq!(difficulty_label(99_999_999), "Hard");
+        assert_eq!(difficulty_label(u64::MAX), "Extreme");
+    }
+
+    #[test]
+    fn test_challenge_canonical_bytes_round_trip() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let public_key = signing_key.verifying_key().to_bytes();
+        let mut challenge = IronShieldChallenge {
+            random_nonce:         "0123456789abcdef".to_string(),
+            created_time:         1_700_000_000_000,
+            expiration_time:      1_700_000_030_000,
+            website_id:           "https://example.com".to_string(),
+            challenge_param:      [0x0F; 32],
+            recommended_attempts: 4_096,
+            public_key,
+            challenge_signature:  [0; 64],
+        };
+
+        // A signature over our canonical bytes must satisfy the
+        // verifier in `ironshield_types`, so any drift between the
+        // two encodings fails here.
+        challenge.challenge_signature = signing_key.sign(&challenge_canonical_bytes(&challenge)).to_bytes();
+        assert!(ironshield_types::verify_challenge_signature_with_key(&challenge, &public_key).is_ok());
+
+        challenge.website_id = "https://example.org".to_string();
+        assert!(ironshield_types::verify_challenge_signature_with_key(&challenge, &public_key).is_err());
+    }
+
+    #[test]
+    fn test_difficulty_info() {
+        let challenge = IronShieldChallenge {
+            random_nonce:         String::new(),
+            created_time:         0,
+            expiration_time:      0,
+            website_id:           String::new(),
+            challenge_param:      [0x0F; 32],
+            recommended_attempts: 1_000_000,
+            public_key:           [0; 32],
+            challenge_signature:  [0; 64],
+        };
+
+        let info = DifficultyInfo::new(&challenge, Some(500_000));
+        assert_eq!(info.bits, 4);
+        assert_eq!(info.estimated_solve_time, Some(Duration::from_secs(2)));
+        assert_eq!(DifficultyInfo::new(&challenge, None).estimated_solve_time, None);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(784_111_777_000), "1994-11-06T08:49:37.000Z");
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_challenge_is_fresh_at() {
+        use crate::client::clock::FixedClock;
+
+        let challenge = generate_challenge(0, "https://example.com");
+        let clock = FixedClock::new(challenge.created_time);
+        let max_age = Duration::from_secs(10);
+
+        clock.advance(Duration::from_millis(9_999));
+        assert!(challenge_is_fresh_at(&challenge, max_age, &clock));
+
+        clock.advance(Duration::from_millis(1));
+        assert!(!challenge_is_fresh_at(&challenge, max_age, &clock));
+
+        clock.set(challenge.expiration_time);
+        assert!(!challenge_is_fresh_at(&challenge, Duration::from_secs(3600), &clock));
+    }
+
+    #[test]
+    fn test_describe_challenge_at() {
+        use crate::client::clock::FixedClock;
+
+        let challenge = IronShieldChallenge {
+            random_nonce:         "0123456789abcdef".to_string(),
+            created_time:         1_700_000_000_000,
+            expiration_time:      1_700_000_030_000,
+            website_id:           "https://example.com".to_string(),
+            challenge_param:      [0x0F; 32],
+            recommended_attempts: 4_096,
+            public_key:           [0; 32],
+            challenge_signature:  [0; 64],
+        };
+        let clock = FixedClock::new(challenge.created_time);
+
+        assert!(describe_challenge_at(&challenge, &clock).ends_with("(expires in 30.0s)"));
+
+        clock.set(challenge.expiration_time + 1_500);
+        assert!(describe_challenge_at(&challenge, &clock).ends_with("(expired 1.5s ago)"));
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_prepared_challenge() {
+        let challenge = generate_challenge(16, "https://example.com");
+        let prepared = PreparedChallenge::new(challenge.clone());
+
+        assert_eq!(prepared.difficulty_label, "Easy");
+        assert_eq!(prepared.estimated_attempts, 65_536);
+        assert_eq!(prepared.expires_at, challenge.expiration_time);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_generate_challenge_is_deterministic() {
+        let first = generate_challenge_with_seed(12, "https://example.com", 7);
+        let second = generate_challenge_with_seed(12, "https://example.com", 7);
+        let other = generate_challenge_with_seed(12, "https://example.com", 8);
+
+        assert_eq!(first.random_nonce, second.random_nonce);
+        assert_ne!(first.random_nonce, other.random_nonce);
+        assert_eq!(first.website_id, "https://example.com");
+        assert_eq!(difficulty_bits(&first), 12);
+        assert_eq!(first.recommended_attempts, 4096);
+    }
+}
@@ -0,0 +1,186 @@
+//! # Circuit breaker for API requests.
+
+use std::sync::{
+    Mutex,
+    PoisonError
+};
+use std::time::{
+    Duration,
+    Instant
+};
+
+/// Whether a `CircuitBreaker` lets requests through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent normally.
+    Closed,
+    /// Requests fail immediately until the cooldown ends.
+    Open,
+    /// The cooldown has ended and a single probe request
+    /// decides whether the circuit closes again.
+    HalfOpen,
+}
+
+/// Stops sending API requests after repeated failures.
+///
+/// After `threshold` consecutive failures the circuit opens
+/// and requests fail immediately without a network call.
+/// Once `cooldown` has passed, one probe request is let
+/// through: success closes the circuit, failure re-opens it
+/// for another cooldown. Network errors and `5xx` responses
+/// count as failures.
+///
+/// Attach to a `ClientConfig` with `set_circuit_breaker` and
+/// keep a clone of the `Arc` to read the state, e.g. from a
+/// health endpoint.
+///
+/// * `threshold`: Consecutive failures that open the circuit.
+/// * `cooldown`:  How long the circuit stays open.
+/// * `state`:     Failure count and open/probe bookkeeping.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown:  Duration,
+    state:     Mutex<BreakerState>,
+}
+
+/// Mutable state of a `CircuitBreaker`.
+///
+/// * `failures`:        Consecutive failures so far.
+/// * `opened_at`:       When the circuit last opened, if open.
+/// * `probe_in_flight`: Whether the half-open probe has been
+///                      let through.
+#[derive(Debug, Default)]
+struct BreakerState {
+    failures:        u32,
+    opened_at:       Option<Instant>,
+    probe_in_flight: bool,
+}
+
+impl CircuitBreaker {
+    /// # Arguments
+    /// * `threshold`: Consecutive failures that open the
+    ///                circuit. `0` is treated as `1`.
+    /// * `cooldown`:  How long the circuit stays open
+    ///                before probing.
+    ///
+    /// # Returns
+    /// * `Self`: A closed circuit breaker.
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: std::cmp::max(1, threshold),
+            cooldown,
+            state:     Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// # Returns
+    /// * `CircuitState`: The breaker's current state.
+    pub fn state(&self) -> CircuitState {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match state.opened_at {
+            None                                                    => CircuitState::Closed,
+            Some(_) if state.probe_in_flight                        => CircuitState::HalfOpen,
+            Some(opened_at) if opened_at.elapsed() >= self.cooldown => CircuitState::HalfOpen,
+            Some(_)                                                 => CircuitState::Open,
+        }
+    }
+
+    /// # Returns
+    /// * `u32`: Consecutive failures recorded so far.
+    pub fn consecutive_failures(&self) -> u32 {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner).failures
+    }
+
+    /// Decides whether a request may be sent. In the half-open
+    /// state only the first caller is let through as a probe.
+    /// If the probe never reports back, e.g. because its future
+    /// was dropped, another probe is allowed after a further
+    /// cooldown.
+    ///
+    /// # Returns
+    /// * `bool`: `true` if the request may be sent.
+    pub(crate) fn try_acquire(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match state.opened_at {
+            None => true,
+            Some(opened_at) if opened_at.elapsed() >= self.cooldown => {
+                state.opened_at = Some(Instant::now());
+                state.probe_in_flight = true;
+                true
+            },
+            Some(_) => false,
+        }
+    }
+
+    /// Closes the circuit after a successful request.
+    pub(crate) fn record_success(&self) {
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner) = BreakerState::default();
+    }
+
+    /// Counts a failed request, opening the circuit once the
+    /// threshold is reached or when a probe fails.
+    pub(crate) fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        state.failures = state.failures.saturating_add(1);
+
+        if state.probe_in_flight || state.failures >= self.threshold {
+            state.opened_at = Some(Instant::now());
+            state.probe_in_flight = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+
+        assert!(breaker.try_acquire());
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        assert!(breaker.try_acquire());
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.try_acquire());
+    }
+
+    #[test]
+    fn test_half_open_lets_one_probe_through() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(20));
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        assert!(breaker.try_acquire());
+        assert!(!breaker.try_acquire());
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn test_failed_probe_reopens() {
+        let breaker = CircuitBreaker::new(3, Duration::from_millis(20));
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(breaker.try_acquire());
+        breaker.record_failure();
+
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.try_acquire());
+    }
+}
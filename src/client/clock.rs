@@ -0,0 +1,94 @@
+//! # Injectable time sources.
+
+use ironshield_types::chrono;
+
+use std::sync::atomic::{
+    AtomicI64,
+    Ordering
+};
+use std::time::Duration;
+
+/// Source of the current time for request timestamps and
+/// expiry checks.
+///
+/// Attach to a `ClientConfig` with `set_clock` to control
+/// time in tests; the system clock is used otherwise.
+pub trait Clock: Send + Sync {
+    /// # Returns
+    /// * `i64`: The current time as Unix epoch milliseconds.
+    fn now_millis(&self) -> i64;
+}
+
+/// The real system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> i64 {
+        chrono::Utc::now().timestamp_millis()
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use ironshield::client::clock::{Clock, FixedClock};
+///
+/// let clock = FixedClock::new(1_000);
+/// clock.advance(Duration::from_millis(500));
+/// assert_eq!(clock.now_millis(), 1_500);
+/// ```
+#[derive(Debug, Default)]
+pub struct FixedClock {
+    now: AtomicI64,
+}
+
+impl FixedClock {
+    /// # Arguments
+    /// * `now_millis`: The initial time as Unix epoch
+    ///                 milliseconds.
+    ///
+    /// # Returns
+    /// * `Self`: A clock stopped at `now_millis`.
+    pub fn new(now_millis: i64) -> Self {
+        Self { now: AtomicI64::new(now_millis) }
+    }
+
+    /// # Arguments
+    /// * `now_millis`: The new time as Unix epoch milliseconds.
+    pub fn set(&self, now_millis: i64) {
+        self.now.store(now_millis, Ordering::Relaxed);
+    }
+
+    /// # Arguments
+    /// * `duration`: How far to move the clock forward.
+    pub fn advance(&self, duration: Duration) {
+        let millis: i64 = i64::try_from(duration.as_millis()).unwrap_or(i64::MAX);
+        self.now.fetch_add(millis, Ordering::Relaxed);
+    }
+}
+
+impl Clock for FixedClock {
+    fn now_millis(&self) -> i64 {
+        self.now.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock() {
+        let clock = FixedClock::new(10);
+        assert_eq!(clock.now_millis(), 10);
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(clock.now_millis(), 1_010);
+
+        clock.set(5);
+        assert_eq!(clock.now_millis(), 5);
+    }
+}
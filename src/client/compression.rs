@@ -0,0 +1,41 @@
+//! # Gzip compression of outgoing request bodies.
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+
+use crate::handler::result::ResultHandler;
+
+use std::io::Write;
+
+/// Gzip-compresses a request body.
+///
+/// # Arguments
+/// * `body`: The serialized request body.
+///
+/// # Returns
+/// * `ResultHandler<Vec<u8>>`: The compressed body.
+pub fn gzip(body: &[u8]) -> ResultHandler<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body)?;
+
+    Ok(encoder.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_gzip_round_trip() {
+        let body = br#"{"solutions":[1,2,3]}"#.repeat(100);
+        let compressed = gzip(&body).unwrap();
+        assert!(compressed.len() < body.len());
+
+        let mut decompressed = Vec::new();
+        GzDecoder::new(compressed.as_slice()).read_to_end(&mut decompressed).unwrap();
+        assert_eq!(decompressed, body);
+    }
+}
@@ -4,16 +4,66 @@ use serde::{
 };
 
 use crate::USER_AGENT;
+use crate::client::cache::{
+    SolveCache,
+    TokenCache
+};
+use crate::client::circuit::CircuitBreaker;
+use crate::client::clock::{
+    Clock,
+    SystemClock
+};
+use crate::client::metrics::{
+    CpuBudget,
+    ErrorLog,
+    SolverMetrics
+};
+use crate::client::http::{
+    HttpClientBuilder,
+    IpFamily
+};
+use crate::client::progress::HashRateSmootherFactory;
+use crate::client::retry::{
+    RetryPolicy,
+    SubmitRetryPolicy
+};
+use crate::client::solve::{
+    PowConfigMapper,
+    Solver,
+    SolveStrategy,
+    ThreadLifecycleObserver
+};
 
-#[allow(unused_imports)]
 use crate::handler::error::{
-    ErrorHandler, 
+    ConfigErrorCode,
+    ConfigValidationError,
+    ErrorHandler,
     INVALID_ENDPOINT
 };
+use crate::handler::result::ResultHandler;
 
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::sync::{
+    Arc,
+    Mutex
+};
 use std::time::Duration;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Named bundles of timeouts and retry behaviour, applied
+/// with `ClientConfig::with_latency_profile`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatencyProfile {
+    /// A user is waiting: fail fast with one quick retry.
+    Interactive,
+    /// Jobs that tolerate long solves, with a few retries.
+    Batch,
+    /// Unattended work that is patient and retries often.
+    Background,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ClientConfig {
     pub api_base_url: String,
     pub num_threads:  Option<usize>,
@@ -21,6 +71,306 @@ pub struct ClientConfig {
     pub timeout:      Duration,
     pub user_agent:   String,
     pub verbose:      bool,
+    /// Key used to HMAC-SHA256 sign outgoing API requests.
+    /// Requires the `hmac` feature. Never serialized or
+    /// printed.
+    #[serde(default, skip_serializing)]
+    pub signing_key:  Option<Vec<u8>>,
+    /// Early-abort policy for solves as
+    /// `(needed_attempts, warmup_window)`. Solves with a
+    /// policy use the multithreaded solver, which counts
+    /// attempts. Once the warm-up window has passed, the solve
+    /// fails with a timeout if the observed hash rate cannot
+    /// reach `needed_attempts` before the solve's deadline:
+    /// `solve_timeout`, the challenge's expiry, or the limit
+    /// passed to the solve. Solves without a deadline are
+    /// never aborted. Aborts are logged to `log_writer`. The
+    /// worker threads are not stopped and keep searching in
+    /// the background.
+    #[serde(skip)]
+    pub min_hashrate_deadline: Option<(u64, Duration)>,
+    /// Hosts resolved to a fixed address instead of via
+    /// DNS, e.g. to point the API host at a local mock.
+    #[serde(default)]
+    pub dns_overrides: Vec<(String, SocketAddr)>,
+    /// Shared load counters updated by every solve
+    /// run with this configuration.
+    #[serde(skip)]
+    pub metrics: Option<Arc<SolverMetrics>>,
+    /// Share one network request between concurrent
+    /// `fetch_challenge` calls for the same endpoint.
+    ///
+    /// Challenges are single-use, so only enable this if
+    /// the API issues the same challenge to concurrent
+    /// callers of an endpoint.
+    #[serde(default)]
+    pub coalesce_fetches: bool,
+    /// Cache of solved challenges consulted before
+    /// solving.
+    #[serde(skip)]
+    pub solve_cache: Option<Arc<SolveCache>>,
+    /// Permits plain HTTP API URLs, both in `validate` and
+    /// `from_file` and in `IronShieldClient::new_insecure`.
+    /// `IronShieldClient::new` still requires HTTPS.
+    #[serde(default)]
+    pub allow_insecure: bool,
+    /// Request bodies of at least this many bytes are
+    /// gzip-compressed. Requires the `gzip` feature.
+    #[serde(default)]
+    pub compression_threshold: Option<usize>,
+    /// CPU time accounting shared by every solve run
+    /// with this configuration.
+    #[serde(skip)]
+    pub cpu_budget: Option<Arc<CpuBudget>>,
+    /// Re-fetch a challenge once if the API response
+    /// body cannot be parsed, e.g. during a deploy.
+    #[serde(default)]
+    pub refetch_on_malformed_challenge: bool,
+    /// Media type sent in the `Accept` header of every API
+    /// request, e.g. for versioned content negotiation.
+    /// Defaults to `application/json` when unset.
+    #[serde(default)]
+    pub accept_header: Option<String>,
+    /// Oldest a prefetched or cached challenge may be before
+    /// `validate_prefetched_challenge` discards it and fetches
+    /// a fresh one.
+    #[serde(default = "default_max_challenge_age", with = "duration_serde")]
+    pub max_challenge_age: Duration,
+    /// Stops sending API requests after repeated failures.
+    #[serde(skip)]
+    pub circuit_breaker: Option<Arc<CircuitBreaker>>,
+    /// Solve strategy per protected endpoint, keyed by exact
+    /// URL or a pattern where `*` matches any characters.
+    /// Consulted by the `validate_*` functions; endpoints
+    /// without a match use the caller's choice.
+    #[serde(default)]
+    pub endpoint_overrides: HashMap<String, SolveStrategy>,
+    /// Notified when multithreaded solver threads start and
+    /// stop.
+    #[serde(skip)]
+    pub thread_observer: Option<Arc<dyn ThreadLifecycleObserver>>,
+    /// Time source for request timestamps and expiry checks.
+    /// Uses the system clock when unset.
+    #[serde(skip)]
+    pub clock: Option<Arc<dyn Clock>>,
+    /// Largest API response body, in bytes, that will be
+    /// read. Larger responses fail with a `ProcessingError`.
+    #[serde(default)]
+    pub max_response_size: Option<usize>,
+    /// Deepest nesting of arrays and objects accepted in an
+    /// API response. Deeper responses fail with a
+    /// `ProcessingError` before being deserialized.
+    #[serde(default)]
+    pub max_response_depth: Option<usize>,
+    /// API keys sent as a bearer token. The first key is
+    /// used until the API answers `401`, when the next key is
+    /// tried once and kept for later requests if it works.
+    /// An empty list sends no `Authorization` header. Never
+    /// serialized or printed.
+    #[serde(default, skip_serializing)]
+    pub api_keys: Vec<String>,
+    /// Limit on establishing a connection to the API, separate
+    /// from the overall request `timeout`.
+    #[serde(default, with = "option_duration_serde")]
+    pub connect_timeout: Option<Duration>,
+    /// Longest a solve may run before failing with
+    /// `SolveError::TimedOut`. The timeout ends the wait, not
+    /// the search: worker threads keep running in the
+    /// background until their search ends.
+    #[serde(default, with = "option_duration_serde")]
+    pub solve_timeout: Option<Duration>,
+    /// How transient failures fetching a challenge are
+    /// retried.
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
+    /// How often a long solve logs a one-line liveness
+    /// summary to `log_writer`, regardless of `verbose`. Solves with a
+    /// heartbeat use the multithreaded solver, which counts
+    /// attempts.
+    #[serde(default, with = "option_duration_serde")]
+    pub heartbeat_interval: Option<Duration>,
+    /// Where heartbeat and hash-rate abort lines are written;
+    /// stderr when unset.
+    #[serde(skip)]
+    pub log_writer: Option<Arc<Mutex<dyn Write + Send>>>,
+    /// Use `num_threads` as given even when it exceeds the
+    /// available cores. Otherwise it is clamped to the core
+    /// count with a warning.
+    #[serde(default)]
+    pub allow_oversubscription: bool,
+    /// Hardest challenge, in leading zero bits of the target,
+    /// that will be solved. Harder challenges fail with
+    /// `ErrorHandler::ChallengeSolvingError` before any work
+    /// starts, so a misbehaving API cannot tie up the client.
+    #[serde(default)]
+    pub max_difficulty_bits: Option<u32>,
+    /// Creates the smoother each worker uses to compute the
+    /// hash rate reported to progress trackers. Defaults to
+    /// `CumulativeSmoother`.
+    #[serde(skip)]
+    pub hash_rate_smoother: Option<Arc<HashRateSmootherFactory>>,
+    /// IP family used to connect to the API. See `IpFamily`.
+    #[serde(default)]
+    pub ip_family: IpFamily,
+    /// How failed solution submissions are retried by
+    /// `validate_challenge`.
+    #[serde(default)]
+    pub submit_retry_policy: SubmitRetryPolicy,
+    /// Whether API connections also use the proxies and
+    /// certificates from the environment. See
+    /// `HttpClientBuilder::from_env`.
+    #[serde(default)]
+    pub network_from_env: bool,
+    /// Keeps the most recent failed API requests and
+    /// solves, when attached.
+    #[serde(skip)]
+    pub error_log: Option<Arc<ErrorLog>>,
+    /// Attaches a SHA-256 fingerprint of each API request
+    /// for the API's replay protection. Requires the
+    /// `hmac` feature.
+    #[serde(default)]
+    pub request_fingerprinting: bool,
+    /// Rejects fetched challenges whose signature does not
+    /// verify against `challenge_public_key`, before any
+    /// solving.
+    #[serde(default)]
+    pub verify_challenge_signature: bool,
+    /// The API's Ed25519 public key, required by
+    /// `verify_challenge_signature`.
+    #[serde(default)]
+    pub challenge_public_key: Option<[u8; 32]>,
+    /// Sends the thread count, hash rate, and solve time
+    /// with each solution submitted by `validate_challenge`,
+    /// for the API's adaptive difficulty. Off by default as
+    /// it reveals the client's hardware capability.
+    #[serde(default)]
+    pub report_client_metrics: bool,
+    /// Fraction of a cached token's lifetime, counted back
+    /// from expiry, within which `validate_challenge` no
+    /// longer reuses it from `token_cache`.
+    #[serde(default = "default_token_refresh_margin")]
+    pub token_refresh_margin: f64,
+    /// Tokens reused by `validate_challenge` per endpoint.
+    #[serde(skip)]
+    pub token_cache: Option<Arc<TokenCache>>,
+    /// How long an idle pooled connection is kept before
+    /// being closed. Longer keeps connections warm across
+    /// gaps between bursts, at the cost of held sockets;
+    /// `None` never closes them.
+    #[serde(default = "default_pool_idle_timeout", with = "option_duration_serde")]
+    pub pool_idle_timeout: Option<Duration>,
+    /// Interval between HTTP/2 keep-alive pings, which stop
+    /// the API or middleboxes from dropping the connection
+    /// and forcing a new TLS handshake. `None` sends no
+    /// pings.
+    #[serde(default = "default_http2_keep_alive_interval", with = "option_duration_serde")]
+    pub http2_keep_alive_interval: Option<Duration>,
+    /// Whether keep-alive pings are also sent on connections
+    /// with no request in flight. Keeps idle connections
+    /// warm for the next burst, at the cost of periodic
+    /// traffic and server resources while idle.
+    #[serde(default = "default_http2_keep_alive_while_idle")]
+    pub http2_keep_alive_while_idle: bool,
+    /// Solves challenges in place of the built-in proof-of-work
+    /// solver, e.g. a `FixedSolver` in tests.
+    #[serde(skip)]
+    pub solver: Option<Arc<dyn Solver>>,
+    /// Total bytes the client may send and receive in request
+    /// and response bodies. Once reached, new API requests
+    /// fail with a `ProcessingError`.
+    #[serde(default)]
+    pub max_transfer_bytes: Option<u64>,
+    /// Maps each challenge to the core solver's parameters in
+    /// place of `challenge_to_pow_config`.
+    #[serde(skip)]
+    pub pow_config: Option<Arc<PowConfigMapper>>,
+    /// Stack size, in bytes, of solver threads, e.g. where the
+    /// platform default stack is too small for the core solver.
+    /// When set, single-threaded solves also run on a dedicated
+    /// OS thread instead of Tokio's blocking pool.
+    #[serde(default)]
+    pub solver_stack_size: Option<usize>,
+    /// Attempts per second this machine solves at, e.g. the
+    /// `multi_hashrate` measured by `calibrate`, used to
+    /// estimate solve times.
+    #[serde(default)]
+    pub expected_hash_rate: Option<u64>,
+    /// Bounds solves by the challenge's expiry minus
+    /// `expiry_safety_margin`, failing with
+    /// `SolveError::TimedOut` once a solution could no longer
+    /// be submitted in time. A solve with its own deadline
+    /// stops at the sooner of the two; one with a cancel
+    /// handle keeps only its own limit. On by default;
+    /// turn it off if the local clock may run ahead of the
+    /// server's, as the expiry is compared with it.
+    #[serde(default = "default_auto_deadline_from_expiry")]
+    pub auto_deadline_from_expiry: bool,
+    /// Time reserved before a challenge's expiry for
+    /// submitting its solution, used by
+    /// `auto_deadline_from_expiry`.
+    #[serde(default = "default_expiry_safety_margin", with = "duration_serde")]
+    pub expiry_safety_margin: Duration,
+}
+
+impl std::fmt::Debug for ClientConfig {
+    /// Formats the configuration with the signing key
+    /// redacted so it never ends up in logs.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientConfig")
+            .field("api_base_url", &self.api_base_url)
+            .field("num_threads",  &self.num_threads)
+            .field("timeout",      &self.timeout)
+            .field("user_agent",   &self.user_agent)
+            .field("verbose",      &self.verbose)
+            .field("signing_key",  &self.signing_key.as_ref().map(|_| "<redacted>"))
+            .field("min_hashrate_deadline", &self.min_hashrate_deadline)
+            .field("dns_overrides", &self.dns_overrides)
+            .field("metrics", &self.metrics)
+            .field("coalesce_fetches", &self.coalesce_fetches)
+            .field("solve_cache", &self.solve_cache)
+            .field("allow_insecure", &self.allow_insecure)
+            .field("compression_threshold", &self.compression_threshold)
+            .field("cpu_budget", &self.cpu_budget)
+            .field("refetch_on_malformed_challenge", &self.refetch_on_malformed_challenge)
+            .field("accept_header", &self.accept_header)
+            .field("max_challenge_age", &self.max_challenge_age)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .field("endpoint_overrides", &self.endpoint_overrides)
+            .field("thread_observer", &self.thread_observer.as_ref().map(|_| "<observer>"))
+            .field("clock", &self.clock.as_ref().map(|_| "<clock>"))
+            .field("max_response_size", &self.max_response_size)
+            .field("max_response_depth", &self.max_response_depth)
+            .field("api_keys", &self.api_keys.iter().map(|_| "<redacted>").collect::<Vec<&str>>())
+            .field("connect_timeout", &self.connect_timeout)
+            .field("solve_timeout", &self.solve_timeout)
+            .field("retry_policy", &self.retry_policy)
+            .field("heartbeat_interval", &self.heartbeat_interval)
+            .field("log_writer", &self.log_writer.as_ref().map(|_| "<writer>"))
+            .field("allow_oversubscription", &self.allow_oversubscription)
+            .field("max_difficulty_bits", &self.max_difficulty_bits)
+            .field("hash_rate_smoother", &self.hash_rate_smoother.as_ref().map(|_| "<smoother>"))
+            .field("ip_family", &self.ip_family)
+            .field("submit_retry_policy", &self.submit_retry_policy)
+            .field("network_from_env", &self.network_from_env)
+            .field("error_log", &self.error_log)
+            .field("request_fingerprinting", &self.request_fingerprinting)
+            .field("verify_challenge_signature", &self.verify_challenge_signature)
+            .field("challenge_public_key", &self.challenge_public_key)
+            .field("report_client_metrics", &self.report_client_metrics)
+            .field("token_refresh_margin", &self.token_refresh_margin)
+            .field("token_cache", &self.token_cache.as_ref().map(|_| "<redacted>"))
+            .field("pool_idle_timeout", &self.pool_idle_timeout)
+            .field("http2_keep_alive_interval", &self.http2_keep_alive_interval)
+            .field("http2_keep_alive_while_idle", &self.http2_keep_alive_while_idle)
+            .field("solver", &self.solver.as_ref().map(|_| "<solver>"))
+            .field("max_transfer_bytes", &self.max_transfer_bytes)
+            .field("pow_config", &self.pow_config.as_ref().map(|_| "<mapper>"))
+            .field("solver_stack_size", &self.solver_stack_size)
+            .field("expected_hash_rate", &self.expected_hash_rate)
+            .field("auto_deadline_from_expiry", &self.auto_deadline_from_expiry)
+            .field("expiry_safety_margin", &self.expiry_safety_margin)
+            .finish()
+    }
 }
 
 impl Default for ClientConfig {
@@ -31,6 +381,53 @@ impl Default for ClientConfig {
             timeout:      Duration::from_secs(30),
             user_agent:   USER_AGENT.to_string(),
             verbose:      false,
+            signing_key:  None,
+            min_hashrate_deadline: None,
+            dns_overrides: Vec::new(),
+            metrics: None,
+            coalesce_fetches: false,
+            solve_cache: None,
+            allow_insecure: false,
+            compression_threshold: None,
+            cpu_budget: None,
+            refetch_on_malformed_challenge: false,
+            accept_header: None,
+            max_challenge_age: default_max_challenge_age(),
+            circuit_breaker: None,
+            endpoint_overrides: HashMap::new(),
+            thread_observer: None,
+            clock: None,
+            max_response_size: None,
+            max_response_depth: None,
+            api_keys: Vec::new(),
+            connect_timeout: None,
+            solve_timeout: None,
+            retry_policy: RetryPolicy::none(),
+            heartbeat_interval: None,
+            log_writer: None,
+            allow_oversubscription: false,
+            max_difficulty_bits: None,
+            hash_rate_smoother: None,
+            ip_family: IpFamily::Auto,
+            submit_retry_policy: SubmitRetryPolicy::none(),
+            network_from_env: false,
+            error_log: None,
+            request_fingerprinting: false,
+            verify_challenge_signature: false,
+            challenge_public_key: None,
+            report_client_metrics: false,
+            token_refresh_margin: default_token_refresh_margin(),
+            token_cache: None,
+            pool_idle_timeout: default_pool_idle_timeout(),
+            http2_keep_alive_interval: default_http2_keep_alive_interval(),
+            http2_keep_alive_while_idle: true,
+            solver: None,
+            max_transfer_bytes: None,
+            pow_config: None,
+            solver_stack_size: None,
+            expected_hash_rate: None,
+            auto_deadline_from_expiry: true,
+            expiry_safety_margin: default_expiry_safety_margin(),
         }
     }
 }
@@ -55,6 +452,53 @@ impl ClientConfig {
             timeout:      Duration::from_secs(60),
             user_agent:   format!("{}-dev", USER_AGENT),
             verbose:      true,
+            signing_key:  None,
+            min_hashrate_deadline: None,
+            dns_overrides: Vec::new(),
+            metrics: None,
+            coalesce_fetches: false,
+            solve_cache: None,
+            allow_insecure: false,
+            compression_threshold: None,
+            cpu_budget: None,
+            refetch_on_malformed_challenge: false,
+            accept_header: None,
+            max_challenge_age: default_max_challenge_age(),
+            circuit_breaker: None,
+            endpoint_overrides: HashMap::new(),
+            thread_observer: None,
+            clock: None,
+            max_response_size: None,
+            max_response_depth: None,
+            api_keys: Vec::new(),
+            connect_timeout: None,
+            solve_timeout: None,
+            retry_policy: RetryPolicy::none(),
+            heartbeat_interval: None,
+            log_writer: None,
+            allow_oversubscription: false,
+            max_difficulty_bits: None,
+            hash_rate_smoother: None,
+            ip_family: IpFamily::Auto,
+            submit_retry_policy: SubmitRetryPolicy::none(),
+            network_from_env: false,
+            error_log: None,
+            request_fingerprinting: false,
+            verify_challenge_signature: false,
+            challenge_public_key: None,
+            report_client_metrics: false,
+            token_refresh_margin: default_token_refresh_margin(),
+            token_cache: None,
+            pool_idle_timeout: default_pool_idle_timeout(),
+            http2_keep_alive_interval: default_http2_keep_alive_interval(),
+            http2_keep_alive_while_idle: true,
+            solver: None,
+            max_transfer_bytes: None,
+            pow_config: None,
+            solver_stack_size: None,
+            expected_hash_rate: None,
+            auto_deadline_from_expiry: true,
+            expiry_safety_margin: default_expiry_safety_margin(),
         }
     }
 
@@ -78,13 +522,181 @@ impl ClientConfig {
             timeout:      Duration::from_secs(5),
             user_agent:   format!("{}-test", USER_AGENT),
             verbose:      false,
+            signing_key:  None,
+            min_hashrate_deadline: None,
+            dns_overrides: Vec::new(),
+            metrics: None,
+            coalesce_fetches: false,
+            solve_cache: None,
+            allow_insecure: false,
+            compression_threshold: None,
+            cpu_budget: None,
+            refetch_on_malformed_challenge: false,
+            accept_header: None,
+            max_challenge_age: default_max_challenge_age(),
+            circuit_breaker: None,
+            endpoint_overrides: HashMap::new(),
+            thread_observer: None,
+            clock: None,
+            max_response_size: None,
+            max_response_depth: None,
+            api_keys: Vec::new(),
+            connect_timeout: None,
+            solve_timeout: None,
+            retry_policy: RetryPolicy::none(),
+            heartbeat_interval: None,
+            log_writer: None,
+            allow_oversubscription: false,
+            max_difficulty_bits: None,
+            hash_rate_smoother: None,
+            ip_family: IpFamily::Auto,
+            submit_retry_policy: SubmitRetryPolicy::none(),
+            network_from_env: false,
+            error_log: None,
+            request_fingerprinting: false,
+            verify_challenge_signature: false,
+            challenge_public_key: None,
+            report_client_metrics: false,
+            token_refresh_margin: default_token_refresh_margin(),
+            token_cache: None,
+            pool_idle_timeout: default_pool_idle_timeout(),
+            http2_keep_alive_interval: default_http2_keep_alive_interval(),
+            http2_keep_alive_while_idle: true,
+            solver: None,
+            max_transfer_bytes: None,
+            pow_config: None,
+            solver_stack_size: None,
+            expected_hash_rate: None,
+            auto_deadline_from_expiry: true,
+            expiry_safety_margin: default_expiry_safety_margin(),
+        }
+    }
+
+    /// Creates a production configuration for a self-hosted
+    /// IronShield instance.
+    ///
+    /// # Arguments
+    /// * `base_url`: The instance's API base URL. Must use HTTPS.
+    ///
+    /// # Returns
+    /// * `ResultHandler<Self>`: The default configuration pointed
+    ///                          at `base_url`, or an error if the
+    ///                          URL is not a valid HTTPS URL.
+    ///
+    /// # Example
+    /// ```
+    /// use ironshield::client::config::ClientConfig;
+    ///
+    /// let config = ClientConfig::for_instance("https://ironshield.example.com")?;
+    /// assert_eq!(config.api_base_url, "https://ironshield.example.com");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn for_instance(base_url: &str) -> ResultHandler<Self> {
+        let base_url: &str = base_url.trim_end_matches('/');
+
+        if base_url.len() <= "https://".len() || !base_url.starts_with("https://") {
+            return Err(ErrorHandler::config_error(
+                INVALID_ENDPOINT.message
+            ));
+        }
+
+        Ok(Self {
+            api_base_url: base_url.to_string(),
+            ..Self::default()
+        })
+    }
+
+    /// Creates the default configuration with networking
+    /// taken from the conventional environment variables
+    /// (see `HttpClientBuilder::from_env`), so containerized
+    /// deployments need no IronShield-specific setup.
+    /// `IRONSHIELD_ALLOW_HTTP` set to `1` or `true` also sets
+    /// `allow_insecure`, permitting plaintext `http://` API
+    /// URLs; it does not disable certificate verification,
+    /// which is `IRONSHIELD_INSECURE_TLS`. A warning is
+    /// printed when either is enabled.
+    ///
+    /// # Returns
+    /// * `ResultHandler<Self>`: The configuration, or an error
+    ///                          if the environment names an
+    ///                          unreadable certificate file.
+    pub fn from_env() -> ResultHandler<Self> {
+        // Fail here, not on the first request, if the environment is broken.
+        HttpClientBuilder::from_env()?;
+
+        let insecure: bool = std::env::var("IRONSHIELD_ALLOW_HTTP")
+            .is_ok_and(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true"));
+
+        if insecure {
+            eprintln!("WARNING: IRONSHIELD_ALLOW_HTTP is set; plaintext http:// API URLs are allowed.");
         }
+
+        Ok(Self {
+            network_from_env: true,
+            allow_insecure:   insecure,
+            ..Self::default()
+        })
+    }
+
+    /// Sets the connect, request, and solve timeouts and the
+    /// retry policy from a named profile.
+    ///
+    /// | Profile       | Connect | Request | Solve  | Retries | Backoff     |
+    /// |---------------|---------|---------|--------|---------|-------------|
+    /// | `Interactive` | 2s      | 5s      | 10s    | 1       | 100ms       |
+    /// | `Batch`       | 10s     | 30s     | 10min  | 3       | 500ms–5s    |
+    /// | `Background`  | 30s     | 60s     | 1h     | 8       | 1s–60s      |
+    ///
+    /// # Arguments
+    /// * `profile`: The profile to apply.
+    ///
+    /// # Returns
+    /// * `Self`: The configuration with the profile applied.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use ironshield::client::config::{ClientConfig, LatencyProfile};
+    ///
+    /// let config = ClientConfig::default().with_latency_profile(LatencyProfile::Interactive);
+    /// assert_eq!(config.timeout, Duration::from_secs(5));
+    /// ```
+    pub fn with_latency_profile(mut self, profile: LatencyProfile) -> Self {
+        let (connect, request, solve, retry_policy) = match profile {
+            LatencyProfile::Interactive => (2, 5, 10, RetryPolicy {
+                max_retries:     1,
+                initial_backoff: Duration::from_millis(100),
+                max_backoff:     Duration::from_millis(100),
+                per_validation_budget: None,
+            }),
+            LatencyProfile::Batch => (10, 30, 10 * 60, RetryPolicy {
+                max_retries:     3,
+                initial_backoff: Duration::from_millis(500),
+                max_backoff:     Duration::from_secs(5),
+                per_validation_budget: None,
+            }),
+            LatencyProfile::Background => (30, 60, 60 * 60, RetryPolicy {
+                max_retries:     8,
+                initial_backoff: Duration::from_secs(1),
+                max_backoff:     Duration::from_secs(60),
+                per_validation_budget: None,
+            }),
+        };
+
+        self.connect_timeout = Some(Duration::from_secs(connect));
+        self.timeout = Duration::from_secs(request);
+        self.solve_timeout = Some(Duration::from_secs(solve));
+        self.retry_policy = retry_policy;
+        self
     }
 
     /// Validates the current configuration, ensuring all values are within acceptable ranges.
     ///
     /// # Returns
-    /// * `Result<(), ErrorHandler>`: Success indication or validation error.
+    /// * `Result<(), ErrorHandler>`: Success indication or
+    ///                               `ErrorHandler::InvalidConfig`
+    ///                               with every error found by
+    ///                               `validate_all`.
     ///
     /// # Errors
     /// Returns an error if:
@@ -102,39 +714,366 @@ impl ClientConfig {
     /// ```
     #[cfg(feature = "toml")]
     pub fn validate(&self) -> Result<(), ErrorHandler> {
-        if self.api_base_url.is_empty() {
-            return Err(ErrorHandler::config_error(
-                "API base URL cannot be empty".to_string()
-            ));
+        self.validate_all().map_err(ErrorHandler::InvalidConfig)
+    }
+
+    /// Renders every setting with its resolved value, one
+    /// `key = value` line per field sorted by key, e.g. to log
+    /// at startup which settings are in effect. Values are
+    /// JSON-encoded and secrets are redacted.
+    ///
+    /// # Returns
+    /// * `String`: The summary.
+    ///
+    /// # Example
+    /// ```
+    /// use ironshield::client::config::ClientConfig;
+    ///
+    /// let summary = ClientConfig::default().effective_summary();
+    /// assert!(summary.contains("api_base_url = \"https://api.ironshield.cloud\""));
+    /// ```
+    pub fn effective_summary(&self) -> String {
+        let value: serde_json::Value = self.to_redacted_value();
+
+        value
+            .as_object()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .map(|(key, value)| format!("{} = {}", key, value))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            })
+            .unwrap_or_default()
+    }
+
+    /// Converts the configuration to JSON for programmatic
+    /// inspection.
+    ///
+    /// Secrets are replaced with `"<redacted>"`. Runtime
+    /// attachments that are not serialized, such as metrics or
+    /// caches, are reported as `"attached"` or `null`.
+    ///
+    /// # Returns
+    /// * `serde_json::Value`: A JSON object with one entry per
+    ///                        field.
+    pub fn to_redacted_value(&self) -> serde_json::Value {
+        let mut value: serde_json::Value = serde_json::to_value(self)
+            .unwrap_or_else(|_| serde_json::Value::Object(serde_json::Map::new()));
+
+        let attached = |present: bool| -> serde_json::Value {
+            if present { "attached".into() } else { serde_json::Value::Null }
+        };
+
+        if let Some(fields) = value.as_object_mut() {
+            fields.insert("signing_key".to_string(), match self.signing_key {
+                Some(_) => "<redacted>".into(),
+                None    => serde_json::Value::Null,
+            });
+            fields.insert("api_keys".to_string(), self.api_keys.iter().map(|_| "<redacted>").collect());
+            fields.insert("min_hashrate_deadline".to_string(), match self.min_hashrate_deadline {
+                Some((needed_attempts, warmup)) => serde_json::json!({
+                    "needed_attempts": needed_attempts,
+                    "warmup_secs":     warmup.as_secs_f64(),
+                }),
+                None => serde_json::Value::Null,
+            });
+            fields.insert("metrics".to_string(), attached(self.metrics.is_some()));
+            fields.insert("solve_cache".to_string(), attached(self.solve_cache.is_some()));
+            fields.insert("cpu_budget".to_string(), attached(self.cpu_budget.is_some()));
+            fields.insert("circuit_breaker".to_string(), attached(self.circuit_breaker.is_some()));
+            fields.insert("log_writer".to_string(), attached(self.log_writer.is_some()));
+            fields.insert("thread_observer".to_string(), attached(self.thread_observer.is_some()));
+            fields.insert("clock".to_string(), attached(self.clock.is_some()));
+            fields.insert("hash_rate_smoother".to_string(), attached(self.hash_rate_smoother.is_some()));
+            fields.insert("error_log".to_string(), attached(self.error_log.is_some()));
+            fields.insert("token_cache".to_string(), attached(self.token_cache.is_some()));
+            fields.insert("solver".to_string(), attached(self.solver.is_some()));
+            fields.insert("pow_config".to_string(), attached(self.pow_config.is_some()));
         }
 
-        if !self.api_base_url.starts_with("https://") {
-            return Err(ErrorHandler::config_error(
-                INVALID_ENDPOINT.message.to_string()
+        value
+    }
+
+    /// Lists the fields that differ between two configurations,
+    /// e.g. a user's configuration and `ClientConfig::default()`.
+    ///
+    /// Secrets are compared but shown as `"<redacted>"`. Runtime
+    /// attachments, such as metrics or caches, are shown as
+    /// `"attached"` or `"none"` and only differ if one side has
+    /// one and the other does not.
+    ///
+    /// # Arguments
+    /// * `other`: The configuration to compare against.
+    ///
+    /// # Returns
+    /// * `Vec<(&'static str, String, String)>`: The field name, this
+    ///                                          configuration's value,
+    ///                                          and `other`'s value
+    ///                                          for each difference,
+    ///                                          in declaration order.
+    ///
+    /// # Example
+    /// ```
+    /// use ironshield::client::config::ClientConfig;
+    ///
+    /// let diff = ClientConfig::development().diff(&ClientConfig::default());
+    /// assert!(diff.iter().any(|(field, _, _)| *field == "verbose"));
+    /// ```
+    pub fn diff(&self, other: &ClientConfig) -> Vec<(&'static str, String, String)> {
+        // Destructured without `..` so that adding a field fails to
+        // compile until it is compared here.
+        let ClientConfig {
+            api_base_url,
+            num_threads,
+            timeout,
+            user_agent,
+            verbose,
+            signing_key,
+            min_hashrate_deadline,
+            dns_overrides,
+            metrics,
+            coalesce_fetches,
+            solve_cache,
+            allow_insecure,
+            compression_threshold,
+            cpu_budget,
+            refetch_on_malformed_challenge,
+            accept_header,
+            max_challenge_age,
+            circuit_breaker,
+            endpoint_overrides,
+            thread_observer,
+            clock,
+            max_response_size,
+            max_response_depth,
+            api_keys,
+            connect_timeout,
+            solve_timeout,
+            retry_policy,
+            heartbeat_interval,
+            log_writer,
+            allow_oversubscription,
+            max_difficulty_bits,
+            hash_rate_smoother,
+            ip_family,
+            submit_retry_policy,
+            network_from_env,
+            error_log,
+            request_fingerprinting,
+            verify_challenge_signature,
+            challenge_public_key,
+            report_client_metrics,
+            token_refresh_margin,
+            token_cache,
+            pool_idle_timeout,
+            http2_keep_alive_interval,
+            http2_keep_alive_while_idle,
+            solver,
+            max_transfer_bytes,
+            pow_config,
+            solver_stack_size,
+            expected_hash_rate,
+            auto_deadline_from_expiry,
+            expiry_safety_margin,
+        } = self;
+
+        fn shown<T: std::fmt::Debug>(value: &T) -> String {
+            format!("{:?}", value)
+        }
+        fn attached<T: ?Sized>(value: &Option<Arc<T>>) -> String {
+            if value.is_some() { "attached" } else { "none" }.to_string()
+        }
+        fn redacted<T>(value: &Option<T>) -> String {
+            if value.is_some() { "<redacted>" } else { "none" }.to_string()
+        }
+        fn sorted(overrides: &HashMap<String, SolveStrategy>) -> String {
+            shown(&overrides.iter().collect::<std::collections::BTreeMap<_, _>>())
+        }
+
+        let fields: [(&'static str, bool, String, String); 52] = [
+            ("api_base_url", false, shown(api_base_url), shown(&other.api_base_url)),
+            ("num_threads", false, shown(num_threads), shown(&other.num_threads)),
+            ("timeout", false, shown(timeout), shown(&other.timeout)),
+            ("user_agent", false, shown(user_agent), shown(&other.user_agent)),
+            ("verbose", false, shown(verbose), shown(&other.verbose)),
+            ("signing_key", *signing_key != other.signing_key, redacted(signing_key), redacted(&other.signing_key)),
+            ("min_hashrate_deadline", false, shown(min_hashrate_deadline), shown(&other.min_hashrate_deadline)),
+            ("dns_overrides", false, shown(dns_overrides), shown(&other.dns_overrides)),
+            ("metrics", false, attached(metrics), attached(&other.metrics)),
+            ("coalesce_fetches", false, shown(coalesce_fetches), shown(&other.coalesce_fetches)),
+            ("solve_cache", false, attached(solve_cache), attached(&other.solve_cache)),
+            ("allow_insecure", false, shown(allow_insecure), shown(&other.allow_insecure)),
+            ("compression_threshold", false, shown(compression_threshold), shown(&other.compression_threshold)),
+            ("cpu_budget", false, attached(cpu_budget), attached(&other.cpu_budget)),
+            ("refetch_on_malformed_challenge", false, shown(refetch_on_malformed_challenge), shown(&other.refetch_on_malformed_challenge)),
+            ("accept_header", false, shown(accept_header), shown(&other.accept_header)),
+            ("max_challenge_age", false, shown(max_challenge_age), shown(&other.max_challenge_age)),
+            ("circuit_breaker", false, attached(circuit_breaker), attached(&other.circuit_breaker)),
+            ("endpoint_overrides", false, sorted(endpoint_overrides), sorted(&other.endpoint_overrides)),
+            ("thread_observer", false, attached(thread_observer), attached(&other.thread_observer)),
+            ("clock", false, attached(clock), attached(&other.clock)),
+            ("max_response_size", false, shown(max_response_size), shown(&other.max_response_size)),
+            ("max_response_depth", false, shown(max_response_depth), shown(&other.max_response_depth)),
+            ("api_keys", *api_keys != other.api_keys, shown(&vec!["<redacted>"; api_keys.len()]), shown(&vec!["<redacted>"; other.api_keys.len()])),
+            ("connect_timeout", false, shown(connect_timeout), shown(&other.connect_timeout)),
+            ("solve_timeout", false, shown(solve_timeout), shown(&other.solve_timeout)),
+            ("retry_policy", false, shown(retry_policy), shown(&other.retry_policy)),
+            ("heartbeat_interval", false, shown(heartbeat_interval), shown(&other.heartbeat_interval)),
+            ("log_writer", false, attached(log_writer), attached(&other.log_writer)),
+            ("allow_oversubscription", false, shown(allow_oversubscription), shown(&other.allow_oversubscription)),
+            ("max_difficulty_bits", false, shown(max_difficulty_bits), shown(&other.max_difficulty_bits)),
+            ("hash_rate_smoother", false, attached(hash_rate_smoother), attached(&other.hash_rate_smoother)),
+            ("ip_family", false, shown(ip_family), shown(&other.ip_family)),
+            ("submit_retry_policy", false, shown(submit_retry_policy), shown(&other.submit_retry_policy)),
+            ("network_from_env", false, shown(network_from_env), shown(&other.network_from_env)),
+            ("error_log", false, attached(error_log), attached(&other.error_log)),
+            ("request_fingerprinting", false, shown(request_fingerprinting), shown(&other.request_fingerprinting)),
+            ("verify_challenge_signature", false, shown(verify_challenge_signature), shown(&other.verify_challenge_signature)),
+            ("challenge_public_key", false, shown(challenge_public_key), shown(&other.challenge_public_key)),
+            ("report_client_metrics", false, shown(report_client_metrics), shown(&other.report_client_metrics)),
+            ("token_refresh_margin", false, shown(token_refresh_margin), shown(&other.token_refresh_margin)),
+            ("token_cache", false, attached(token_cache), attached(&other.token_cache)),
+            ("pool_idle_timeout", false, shown(pool_idle_timeout), shown(&other.pool_idle_timeout)),
+            ("http2_keep_alive_interval", false, shown(http2_keep_alive_interval), shown(&other.http2_keep_alive_interval)),
+            ("http2_keep_alive_while_idle", false, shown(http2_keep_alive_while_idle), shown(&other.http2_keep_alive_while_idle)),
+            ("solver", false, attached(solver), attached(&other.solver)),
+            ("max_transfer_bytes", false, shown(max_transfer_bytes), shown(&other.max_transfer_bytes)),
+            ("pow_config", false, attached(pow_config), attached(&other.pow_config)),
+            ("solver_stack_size", false, shown(solver_stack_size), shown(&other.solver_stack_size)),
+            ("expected_hash_rate", false, shown(expected_hash_rate), shown(&other.expected_hash_rate)),
+            ("auto_deadline_from_expiry", false, shown(auto_deadline_from_expiry), shown(&other.auto_deadline_from_expiry)),
+            ("expiry_safety_margin", false, shown(expiry_safety_margin), shown(&other.expiry_safety_margin)),
+        ];
+
+        fields
+            .into_iter()
+            .filter(|(_, secret_differs, ours, theirs)| *secret_differs || ours != theirs)
+            .map(|(field, _, ours, theirs)| (field, ours, theirs))
+            .collect()
+    }
+
+    /// Looks up the solve strategy configured for an endpoint.
+    ///
+    /// An exact key wins over patterns; among matching patterns
+    /// the longest (most specific) one wins.
+    ///
+    /// # Arguments
+    /// * `endpoint`: The protected endpoint URL.
+    ///
+    /// # Returns
+    /// * `Option<SolveStrategy>`: The override, if any matches.
+    ///
+    /// # Example
+    /// ```
+    /// use ironshield::client::config::ClientConfig;
+    /// use ironshield::client::solve::SolveStrategy;
+    ///
+    /// let mut config = ClientConfig::default();
+    /// config.endpoint_overrides.insert("https://example.com/api/*".to_string(), SolveStrategy::SingleThreaded);
+    /// assert_eq!(config.strategy_for("https://example.com/api/items"), Some(SolveStrategy::SingleThreaded));
+    /// assert_eq!(config.strategy_for("https://example.com/login"), None);
+    /// ```
+    pub fn strategy_for(&self, endpoint: &str) -> Option<SolveStrategy> {
+        if let Some(strategy) = self.endpoint_overrides.get(endpoint) {
+            return Some(*strategy);
+        }
+
+        self.endpoint_overrides
+            .iter()
+            .filter(|(pattern, _)| pattern.contains('*') && wildcard_match(pattern, endpoint))
+            .max_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| b.cmp(a)))
+            .map(|(_, strategy)| *strategy)
+    }
+
+    /// Runs every validation check and collects all failures,
+    /// e.g. to show a complete "fix your config" report for a
+    /// configuration loaded from any serde source.
+    ///
+    /// # Returns
+    /// * `Result<(), Vec<ConfigValidationError>>`: Success indication or
+    ///                                             every validation error,
+    ///                                             naming its field, in
+    ///                                             the order the fields
+    ///                                             are declared. Never an
+    ///                                             empty vector.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use ironshield::client::config::ClientConfig;
+    ///
+    /// let mut config = ClientConfig::default();
+    /// config.timeout = Duration::ZERO;
+    /// config.user_agent = String::new();
+    ///
+    /// let errors = config.validate_all().unwrap_err();
+    /// assert_eq!(errors.iter().map(|e| e.field).collect::<Vec<_>>(), ["timeout", "user_agent"]);
+    /// ```
+    pub fn validate_all(&self) -> Result<(), Vec<ConfigValidationError>> {
+        let mut errors: Vec<ConfigValidationError> = Vec::new();
+
+        if self.api_base_url.is_empty() {
+            errors.push(ConfigValidationError::new(
+                "api_base_url",
+                ConfigErrorCode::Empty,
+                "API base URL cannot be empty"
+            ));
+        } else if !self.api_base_url.starts_with("https://")
+            && !(self.allow_insecure && self.api_base_url.starts_with("http://"))
+        {
+            errors.push(ConfigValidationError::new(
+                "api_base_url",
+                ConfigErrorCode::InsecureUrl,
+                INVALID_ENDPOINT.message
             ));
         }
 
         if self.timeout.is_zero() {
-            return Err(ErrorHandler::config_error(
-                "Timeout must be greater than zero".to_string()
+            errors.push(ConfigValidationError::new(
+                "timeout",
+                ConfigErrorCode::ZeroDuration,
+                "Timeout must be greater than zero"
             ));
         }
 
-        if let Some(threads) = self.num_threads {
-            if threads == 0 {
-                return Err(ErrorHandler::config_error(
-                    "Number of threads must be greater than zero".to_string()
-                ));
-            }
+        if self.num_threads == Some(0) {
+            errors.push(ConfigValidationError::new(
+                "num_threads",
+                ConfigErrorCode::ZeroThreads,
+                "Number of threads must be greater than zero"
+            ));
         }
 
         if self.user_agent.is_empty() {
-            return Err(ErrorHandler::config_error(
-                "User agent cannot be empty".to_string()
+            errors.push(ConfigValidationError::new(
+                "user_agent",
+                ConfigErrorCode::Empty,
+                "User agent cannot be empty"
             ));
         }
 
-        Ok(())
+        if self.verify_challenge_signature && self.challenge_public_key.is_none() {
+            errors.push(ConfigValidationError::new(
+                "challenge_public_key",
+                ConfigErrorCode::Empty,
+                "Challenge signature verification requires a public key"
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&self.token_refresh_margin) {
+            errors.push(ConfigValidationError::new(
+                "token_refresh_margin",
+                ConfigErrorCode::OutOfRange,
+                "Token refresh margin must be between 0 and 1"
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
     }
 
     /// Loads a configuration file from a TOML file,
@@ -167,10 +1106,7 @@ impl ClientConfig {
                         format!("Failed to parse TOML config file '{}': {}", path, e)
                     ))?;
 
-                config.validate()
-                      .map_err(|e| ErrorHandler::config_error(
-                          format!("Configuration validation failed: {}", e)
-                      ))?;
+                config.validate()?;
 
                 Ok(config)
             }
@@ -327,6 +1263,242 @@ impl ClientConfig {
         self
     }
 
+    /// # Arguments
+    /// * `metrics`: Shared counters to update while solving.
+    ///
+    /// # Returns
+    /// * `&mut Self`: Mutable reference for method chaining.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::Arc;
+    /// use ironshield::client::config::ClientConfig;
+    /// use ironshield::client::metrics::SolverMetrics;
+    ///
+    /// let metrics = Arc::new(SolverMetrics::new());
+    /// let mut config = ClientConfig::default();
+    /// config.set_metrics(Arc::clone(&metrics));
+    /// assert_eq!(metrics.in_flight_solves(), 0);
+    /// ```
+    pub fn set_metrics(&mut self, metrics: Arc<SolverMetrics>) -> &mut Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// # Arguments
+    /// * `log`: Log to record failed API requests and
+    ///          solves in.
+    ///
+    /// # Returns
+    /// * `&mut Self`: Mutable reference for method chaining.
+    pub fn set_error_log(&mut self, log: Arc<ErrorLog>) -> &mut Self {
+        self.error_log = Some(log);
+        self
+    }
+
+    /// # Arguments
+    /// * `cache`: Cache of solved challenges to consult
+    ///            before solving.
+    ///
+    /// # Returns
+    /// * `&mut Self`: Mutable reference for method chaining.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::Arc;
+    /// use ironshield::client::cache::SolveCache;
+    /// use ironshield::client::config::ClientConfig;
+    ///
+    /// let mut config = ClientConfig::default();
+    /// config.set_solve_cache(Arc::new(SolveCache::new(16)));
+    /// assert!(config.solve_cache.is_some());
+    /// ```
+    pub fn set_solve_cache(&mut self, cache: Arc<SolveCache>) -> &mut Self {
+        self.solve_cache = Some(cache);
+        self
+    }
+
+    /// # Arguments
+    /// * `cache`: Tokens for `validate_challenge` to reuse
+    ///            instead of validating again.
+    ///
+    /// # Returns
+    /// * `&mut Self`: Mutable reference for method chaining.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::Arc;
+    /// use ironshield::client::cache::TokenCache;
+    /// use ironshield::client::config::ClientConfig;
+    ///
+    /// let mut config = ClientConfig::default();
+    /// config.set_token_cache(Arc::new(TokenCache::new()));
+    /// assert!(config.token_cache.is_some());
+    /// ```
+    pub fn set_token_cache(&mut self, cache: Arc<TokenCache>) -> &mut Self {
+        self.token_cache = Some(cache);
+        self
+    }
+
+    /// # Arguments
+    /// * `budget`: CPU time accounting to charge solves to.
+    ///
+    /// # Returns
+    /// * `&mut Self`: Mutable reference for method chaining.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use ironshield::client::config::ClientConfig;
+    /// use ironshield::client::metrics::CpuBudget;
+    ///
+    /// let budget = Arc::new(CpuBudget::new(Duration::from_secs(3600)));
+    /// let mut config = ClientConfig::default();
+    /// config.set_cpu_budget(Arc::clone(&budget));
+    /// assert_eq!(budget.cpu_seconds_used(), 0.0);
+    /// ```
+    pub fn set_cpu_budget(&mut self, budget: Arc<CpuBudget>) -> &mut Self {
+        self.cpu_budget = Some(budget);
+        self
+    }
+
+    /// # Arguments
+    /// * `breaker`: Circuit breaker consulted before every
+    ///              API request.
+    ///
+    /// # Returns
+    /// * `&mut Self`: Mutable reference for method chaining.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use ironshield::client::circuit::{CircuitBreaker, CircuitState};
+    /// use ironshield::client::config::ClientConfig;
+    ///
+    /// let breaker = Arc::new(CircuitBreaker::new(5, Duration::from_secs(30)));
+    /// let mut config = ClientConfig::default();
+    /// config.set_circuit_breaker(Arc::clone(&breaker));
+    /// assert_eq!(breaker.state(), CircuitState::Closed);
+    /// ```
+    pub fn set_circuit_breaker(&mut self, breaker: Arc<CircuitBreaker>) -> &mut Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// # Arguments
+    /// * `observer`: Notified when multithreaded solver threads
+    ///               start and stop.
+    ///
+    /// # Returns
+    /// * `&mut Self`: Mutable reference for method chaining.
+    pub fn set_thread_observer(&mut self, observer: Arc<dyn ThreadLifecycleObserver>) -> &mut Self {
+        self.thread_observer = Some(observer);
+        self
+    }
+
+    /// # Arguments
+    /// * `writer`: Receives heartbeat and abort lines instead
+    ///             of stderr.
+    ///
+    /// # Returns
+    /// * `&mut Self`: Mutable reference for method chaining.
+    pub fn set_log_writer(&mut self, writer: Arc<Mutex<dyn Write + Send>>) -> &mut Self {
+        self.log_writer = Some(writer);
+        self
+    }
+
+    /// # Arguments
+    /// * `solver`: Solves challenges in place of the
+    ///             built-in proof-of-work solver.
+    ///
+    /// # Returns
+    /// * `&mut Self`: Mutable reference for method chaining.
+    pub fn set_solver(&mut self, solver: Arc<dyn Solver>) -> &mut Self {
+        self.solver = Some(solver);
+        self
+    }
+
+    /// # Arguments
+    /// * `mapper`: Maps each challenge to the core solver's
+    ///             parameters, e.g. `challenge_to_pow_config`
+    ///             with a tunable adjusted.
+    ///
+    /// # Returns
+    /// * `&mut Self`: Mutable reference for method chaining.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::Arc;
+    /// use ironshield::client::config::ClientConfig;
+    /// use ironshield::client::solve::challenge_to_pow_config;
+    ///
+    /// let mut config = ClientConfig::default();
+    /// config.set_pow_config(Arc::new(|challenge, solve_config| {
+    ///     challenge_to_pow_config(challenge, solve_config)
+    /// }));
+    /// assert!(config.pow_config.is_some());
+    /// ```
+    pub fn set_pow_config(&mut self, mapper: Arc<PowConfigMapper>) -> &mut Self {
+        self.pow_config = Some(mapper);
+        self
+    }
+
+    /// # Arguments
+    /// * `clock`: Time source for request timestamps and
+    ///            expiry checks.
+    ///
+    /// # Returns
+    /// * `&mut Self`: Mutable reference for method chaining.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::Arc;
+    /// use ironshield::client::clock::FixedClock;
+    /// use ironshield::client::config::ClientConfig;
+    ///
+    /// let mut config = ClientConfig::default();
+    /// config.set_clock(Arc::new(FixedClock::new(1_000)));
+    /// assert_eq!(config.clock().now_millis(), 1_000);
+    /// ```
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) -> &mut Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Replaces the hash-rate calculation used for progress
+    /// reports, e.g. with a sliding window for a more
+    /// responsive readout.
+    ///
+    /// # Arguments
+    /// * `factory`: Creates one smoother per worker thread.
+    ///
+    /// # Returns
+    /// * `&mut Self`: Mutable reference for method chaining.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::Arc;
+    /// use ironshield::client::config::ClientConfig;
+    /// use ironshield::client::progress::{CumulativeSmoother, HashRateSmoother};
+    ///
+    /// let mut config = ClientConfig::default();
+    /// config.set_hash_rate_smoother(Arc::new(|| {
+    ///     Box::new(CumulativeSmoother::default()) as Box<dyn HashRateSmoother>
+    /// }));
+    /// ```
+    pub fn set_hash_rate_smoother(&mut self, factory: Arc<HashRateSmootherFactory>) -> &mut Self {
+        self.hash_rate_smoother = Some(factory);
+        self
+    }
+
+    /// # Returns
+    /// * `&dyn Clock`: The attached clock, or the system clock.
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_deref().unwrap_or(&SystemClock)
+    }
+
     /// # Arguments
     /// * `user_agent`: The new user agent string.
     ///
@@ -354,6 +1526,85 @@ impl ClientConfig {
     }
 }
 
+/// Matches `text` against a pattern in which `*` matches
+/// any run of characters, including none.
+///
+/// # Arguments
+/// * `pattern`: The pattern to match.
+/// * `text`:    The text to test.
+///
+/// # Returns
+/// * `bool`: `true` if the whole of `text` matches.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: &[u8] = pattern.as_bytes();
+    let text: &[u8] = text.as_bytes();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last `*` absorb one more character.
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, t));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|byte| *byte == b'*')
+}
+
+/// # Returns
+/// * `Duration`: The default `ClientConfig::max_challenge_age`.
+fn default_max_challenge_age() -> Duration {
+    Duration::from_secs(60)
+}
+
+/// # Returns
+/// * `f64`: The default `ClientConfig::token_refresh_margin`.
+fn default_token_refresh_margin() -> f64 {
+    0.1
+}
+
+/// # Returns
+/// * `Option<Duration>`: The default `ClientConfig::pool_idle_timeout`.
+fn default_pool_idle_timeout() -> Option<Duration> {
+    Some(Duration::from_secs(90))
+}
+
+/// # Returns
+/// * `Option<Duration>`: The default
+///                       `ClientConfig::http2_keep_alive_interval`.
+fn default_http2_keep_alive_interval() -> Option<Duration> {
+    Some(Duration::from_secs(30))
+}
+
+/// # Returns
+/// * `bool`: The default `ClientConfig::http2_keep_alive_while_idle`.
+fn default_http2_keep_alive_while_idle() -> bool {
+    true
+}
+
+/// # Returns
+/// * `bool`: The default `ClientConfig::auto_deadline_from_expiry`.
+fn default_auto_deadline_from_expiry() -> bool {
+    true
+}
+
+/// # Returns
+/// * `Duration`: The default `ClientConfig::expiry_safety_margin`.
+fn default_expiry_safety_margin() -> Duration {
+    Duration::from_secs(2)
+}
+
 /// Custom serialization/deserialization for `Duration` fields.
 ///
 /// Provides serde support for `Duration` fields,
@@ -418,11 +1669,175 @@ mod duration_serde {
     }
 }
 
+/// Serializes `Option<Duration>` fields as optional
+/// milliseconds, like `RetryPolicy`'s backoffs, so that
+/// sub-second values survive a round trip.
+mod option_duration_serde {
+    use serde::{
+        Deserialize,
+        Deserializer,
+        Serializer
+    };
+    use std::time::Duration;
+
+    pub fn serialize<S>(
+        duration: &Option<Duration>,
+        serializer: S
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match duration {
+            Some(duration) => serializer.serialize_some(&(duration.as_millis() as u64)),
+            None           => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(
+        deserializer: D
+    ) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_millis))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    #[allow(unused_imports)]
     use super::*;
 
+    #[test]
+    fn test_for_instance() {
+        let config = ClientConfig::for_instance("https://shield.example.com/").unwrap();
+        assert_eq!(config.api_base_url, "https://shield.example.com");
+        assert!(!config.verbose);
+
+        assert!(ClientConfig::for_instance("http://shield.example.com").is_err());
+        assert!(ClientConfig::for_instance("https://").is_err());
+    }
+
+    #[test]
+    fn test_redacted_value_hides_signing_key() {
+        let mut config = ClientConfig::default();
+        config.signing_key = Some(b"secret".to_vec());
+        config.api_keys = vec!["secret-key".to_string()];
+        config.set_metrics(Arc::new(SolverMetrics::new()));
+
+        let value = config.to_redacted_value();
+        assert_eq!(value["signing_key"], "<redacted>");
+        assert_eq!(value["api_keys"], serde_json::json!(["<redacted>"]));
+        assert_eq!(value["metrics"], "attached");
+        assert!(value["solve_cache"].is_null());
+        assert!(!format!("{:?}", config).contains("secret"));
+
+        let summary = config.effective_summary();
+        assert!(summary.contains("signing_key = \"<redacted>\""));
+        assert!(!summary.contains("secret"));
+    }
+
+    #[test]
+    fn test_diff_redacts_secrets() {
+        let mut config = ClientConfig::default();
+        config.signing_key = Some(b"secret".to_vec());
+        config.verbose = true;
+
+        let mut other = ClientConfig::default();
+        other.signing_key = Some(b"other-secret".to_vec());
+
+        let diff = config.diff(&other);
+        assert_eq!(diff, vec![
+            ("verbose", "true".to_string(), "false".to_string()),
+            ("signing_key", "<redacted>".to_string(), "<redacted>".to_string()),
+        ]);
+        assert!(config.diff(&config.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("https://*.example.com/*", "https://api.example.com/items"));
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("a*b*c", "abbbc"));
+        assert!(!wildcard_match("https://example.com/*", "https://example.org/"));
+        assert!(!wildcard_match("a*b", "abc"));
+    }
+
+    #[test]
+    fn test_strategy_for_prefers_most_specific() {
+        let mut config = ClientConfig::default();
+        config.endpoint_overrides.insert("https://example.com/*".to_string(), SolveStrategy::SingleThreaded);
+        config.endpoint_overrides.insert("https://example.com/heavy/*".to_string(), SolveStrategy::MultiThreaded);
+        config.endpoint_overrides.insert("https://example.com/heavy/cheap".to_string(), SolveStrategy::SingleThreaded);
+
+        assert_eq!(config.strategy_for("https://example.com/a"), Some(SolveStrategy::SingleThreaded));
+        assert_eq!(config.strategy_for("https://example.com/heavy/b"), Some(SolveStrategy::MultiThreaded));
+        assert_eq!(config.strategy_for("https://example.com/heavy/cheap"), Some(SolveStrategy::SingleThreaded));
+        assert_eq!(config.strategy_for("https://other.com/"), None);
+    }
+
+    #[test]
+    fn test_validate_all_collects_every_error() {
+        let mut config = ClientConfig::default();
+        assert!(config.validate_all().is_ok());
+
+        config.api_base_url = String::new();
+        config.timeout = Duration::ZERO;
+        config.num_threads = Some(0);
+        config.user_agent = String::new();
+
+        let errors = config.validate_all().unwrap_err();
+        assert_eq!(
+            errors.iter().map(|e| (e.field, e.code)).collect::<Vec<_>>(),
+            vec![
+                ("api_base_url", ConfigErrorCode::Empty),
+                ("timeout", ConfigErrorCode::ZeroDuration),
+                ("num_threads", ConfigErrorCode::ZeroThreads),
+                ("user_agent", ConfigErrorCode::Empty),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_with_latency_profile() {
+        let interactive = ClientConfig::default().with_latency_profile(LatencyProfile::Interactive);
+        let batch = ClientConfig::default().with_latency_profile(LatencyProfile::Batch);
+        let background = ClientConfig::default().with_latency_profile(LatencyProfile::Background);
+
+        assert_eq!(interactive.connect_timeout, Some(Duration::from_secs(2)));
+        assert_eq!(interactive.solve_timeout, Some(Duration::from_secs(10)));
+        assert_eq!(interactive.retry_policy.max_retries, 1);
+
+        // Each profile is more patient than the last.
+        assert!(interactive.timeout < batch.timeout && batch.timeout < background.timeout);
+        assert!(interactive.solve_timeout < batch.solve_timeout && batch.solve_timeout < background.solve_timeout);
+        assert!(batch.retry_policy.max_retries < background.retry_policy.max_retries);
+        assert!(background.validate_all().is_ok());
+    }
+
+    #[test]
+    fn test_optional_durations_keep_milliseconds() {
+        let mut config = ClientConfig::default();
+        config.solve_timeout = Some(Duration::from_millis(1_500));
+
+        let value = serde_json::to_value(&config).unwrap();
+        assert_eq!(value["solve_timeout"], 1_500);
+
+        let config: ClientConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(config.solve_timeout, Some(Duration::from_millis(1_500)));
+    }
+
+    #[test]
+    fn test_signature_verification_requires_public_key() {
+        let mut config = ClientConfig::default();
+        config.verify_challenge_signature = true;
+
+        let errors = config.validate_all().unwrap_err();
+        assert_eq!(errors[0].field, "challenge_public_key");
+
+        config.challenge_public_key = Some([7; 32]);
+        assert!(config.validate_all().is_ok());
+    }
+
     #[test]
     #[cfg(feature = "toml")]
     fn test_default_config_is_valid() {
@@ -436,6 +1851,29 @@ mod tests {
         let mut config = ClientConfig::default();
         config.api_base_url = "http://insecure.example.com".to_string();
         assert!(config.validate().is_err());
+
+        config.allow_insecure = true;
+        assert!(config.validate().is_ok());
+
+        config.api_base_url = "ftp://insecure.example.com".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn test_from_file_accepts_http_when_insecure_allowed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ironshield.toml");
+        let path = path.to_str().unwrap();
+
+        let mut config = ClientConfig::default();
+        config.api_base_url = "http://localhost:3000".to_string();
+        config.allow_insecure = true;
+        config.save_to_file(path).unwrap();
+
+        let loaded = ClientConfig::from_file(path).unwrap();
+        assert_eq!(loaded.api_base_url, "http://localhost:3000");
+        assert!(loaded.allow_insecure);
     }
 
     #[test]
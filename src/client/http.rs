@@ -1,35 +1,114 @@
-use reqwest::Client;
+use reqwest::{
+    Certificate,
+    Client,
+    NoProxy,
+    Proxy
+};
+use serde::{
+    Deserialize,
+    Serialize
+};
 
 use crate::constant::USER_AGENT;
 use crate::handler::error::ErrorHandler;
 use crate::handler::result::ResultHandler;
 
+use std::net::{
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr
+};
+use std::path::Path;
 use std::time::Duration;
 
+/// Which IP family API connections use.
+///
+/// With `Auto`, both families are tried happy-eyeballs
+/// style: if the preferred address does not connect within
+/// a few hundred milliseconds, the other family is raced
+/// against it, all within the connect timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpFamily {
+    /// Use whichever family connects first.
+    #[default]
+    Auto,
+    /// Only connect over IPv4, e.g. where IPv6 is
+    /// advertised but broken.
+    V4,
+    /// Only connect over IPv6.
+    V6,
+}
+
 /// Builder pattern for HTTP client configuration.
 ///
-/// * `timeout`:              The request timeout duration.
-/// * `user_agent`:           The user-agent header value.
-/// * `accept_invalid_certs`: Whether to accept invalid SSL
-///                           certs. Hopefully never `true`
-///                           in a prod environment.
+/// * `timeout`:                     The request timeout duration.
+/// * `connect_timeout`:             The connection timeout, if any.
+/// * `user_agent`:                  The user-agent header value.
+/// * `accept_invalid_certs`:        Whether to accept invalid SSL
+///                                  certs. Hopefully never `true`
+///                                  in a prod environment.
+/// * `dns_overrides`:               Hosts resolved to a fixed
+///                                  address instead of via DNS.
+/// * `local_address`:               Local address to connect from,
+///                                  which also fixes the IP family.
+/// * `http_proxy`:                  Proxy URL for `http://` requests.
+/// * `https_proxy`:                 Proxy URL for `https://` requests.
+/// * `no_proxy`:                    Comma-separated hosts that bypass
+///                                  the proxies, as in `NO_PROXY`.
+/// * `root_certificates`:           Extra trusted root certificates.
+/// * `pool_idle_timeout`:           How long idle connections are
+///                                  kept, `None` for indefinitely.
+/// * `http2_keep_alive_interval`:   Interval between HTTP/2
+///                                  keep-alive pings, if any.
+/// * `http2_keep_alive_while_idle`: Whether pings are sent
+///                                  on idle connections.
 pub struct HttpClientBuilder {
-    timeout:              Duration,
-    user_agent:           String,
-    accept_invalid_certs: bool,
+    timeout:                     Duration,
+    connect_timeout:             Option<Duration>,
+    user_agent:                  String,
+    accept_invalid_certs:        bool,
+    dns_overrides:               Vec<(String, SocketAddr)>,
+    local_address:               Option<IpAddr>,
+    http_proxy:                  Option<String>,
+    https_proxy:                 Option<String>,
+    no_proxy:                    Option<String>,
+    root_certificates:           Vec<Certificate>,
+    pool_idle_timeout:           Option<Duration>,
+    http2_keep_alive_interval:   Option<Duration>,
+    http2_keep_alive_while_idle: bool,
 }
 
 impl Default for HttpClientBuilder {
     /// Default configuration for `HttpClientBuilder`.
     ///
     /// * Timeout: 30 seconds.
+    /// * Connect timeout: None.
     /// * User-Agent: dependent on `constant::USER_AGENT`.
     /// * SSL certification validation: Enabled.
+    /// * DNS overrides: None.
+    /// * Local address: Chosen by the OS.
+    /// * Proxies: None set explicitly.
+    /// * Root certificates: The system's only.
+    /// * Idle connections: Kept for 90 seconds.
+    /// * HTTP/2 keep-alive: Pings every 30 seconds, also
+    ///   while idle.
     fn default() -> Self {
         Self {
-            timeout:              Duration::from_secs(30),
-            user_agent:           USER_AGENT.to_string(),
-            accept_invalid_certs: false,
+            timeout:                     Duration::from_secs(30),
+            connect_timeout:             None,
+            user_agent:                  USER_AGENT.to_string(),
+            accept_invalid_certs:        false,
+            dns_overrides:               Vec::new(),
+            local_address:               None,
+            http_proxy:                  None,
+            https_proxy:                 None,
+            no_proxy:                    None,
+            root_certificates:           Vec::new(),
+            pool_idle_timeout:           Some(Duration::from_secs(90)),
+            http2_keep_alive_interval:   Some(Duration::from_secs(30)),
+            http2_keep_alive_while_idle: true,
         }
     }
 }
@@ -42,6 +121,75 @@ impl HttpClientBuilder {
         Self::default()
     }
 
+    /// Creates a builder configured from the conventional
+    /// environment variables, as curl does:
+    ///
+    /// * `HTTP_PROXY`, `HTTPS_PROXY`, `NO_PROXY`: Proxies and
+    ///   the hosts that bypass them. Lowercase names are
+    ///   used when the uppercase ones are unset.
+    /// * `SSL_CERT_FILE`: A PEM bundle of extra trusted root
+    ///   certificates.
+    /// * `SSL_CERT_DIR`: `:`-separated directories of PEM
+    ///   certificates. Files that are not PEM are skipped.
+    /// * `IRONSHIELD_INSECURE_TLS`: `1` or `true` accepts
+    ///   invalid certificates. Plaintext `http://` API URLs
+    ///   are governed separately, by `IRONSHIELD_ALLOW_HTTP`
+    ///   (see `ClientConfig::from_env`).
+    ///
+    /// Builder calls made afterwards override these.
+    ///
+    /// # Returns
+    /// * `ResultHandler<Self>`: The configured builder, or an
+    ///                          error if a certificate file
+    ///                          cannot be read.
+    pub fn from_env() -> ResultHandler<Self> {
+        Self::from_env_with(|name| std::env::var(name).ok())
+    }
+
+    /// Like `from_env`, reading variables with `lookup`.
+    ///
+    /// # Arguments
+    /// * `lookup`: Returns the value of a variable, if set.
+    ///
+    /// # Returns
+    /// * `ResultHandler<Self>`: See `from_env`.
+    fn from_env_with(lookup: impl Fn(&str) -> Option<String>) -> ResultHandler<Self> {
+        let var = |name: &str| -> Option<String> {
+            lookup(name)
+                .or_else(|| lookup(&name.to_lowercase()))
+                .filter(|value| !value.trim().is_empty())
+        };
+        let mut builder: Self = Self::new();
+
+        builder.http_proxy = var("HTTP_PROXY");
+        builder.https_proxy = var("HTTPS_PROXY");
+        builder.no_proxy = var("NO_PROXY");
+        builder.accept_invalid_certs = var("IRONSHIELD_INSECURE_TLS")
+            .is_some_and(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true"));
+
+        if let Some(file) = lookup("SSL_CERT_FILE") {
+            let pem: Vec<u8> = read_certificate_file(Path::new(&file))?;
+            builder.root_certificates.extend(Certificate::from_pem_bundle(&pem).map_err(|e| {
+                ErrorHandler::config_error(format!("Invalid certificates in SSL_CERT_FILE '{}': {}", file, e))
+            })?);
+        }
+
+        for dir in lookup("SSL_CERT_DIR").iter().flat_map(|dirs| dirs.split(':')).filter(|dir| !dir.is_empty()) {
+            let entries = std::fs::read_dir(dir).map_err(|e| {
+                ErrorHandler::config_error(format!("Cannot read SSL_CERT_DIR '{}': {}", dir, e))
+            })?;
+
+            for path in entries.flatten().map(|entry| entry.path()).filter(|path| path.is_file()) {
+                let pem: Vec<u8> = read_certificate_file(&path)?;
+                if let Ok(certificates) = Certificate::from_pem_bundle(&pem) {
+                    builder.root_certificates.extend(certificates);
+                }
+            }
+        }
+
+        Ok(builder)
+    }
+
     /// # Arguments
     /// * `duration`: The timeout duration for the HTTP request.
     ///
@@ -52,6 +200,57 @@ impl HttpClientBuilder {
         self
     }
 
+    /// # Arguments
+    /// * `duration`: The timeout for establishing a connection.
+    ///
+    /// # Returns
+    /// * `Self`: The builder instance for method chaining.
+    pub fn connect_timeout(mut self, duration: Duration) -> Self {
+        self.connect_timeout = Some(duration);
+        self
+    }
+
+    /// Keeping idle connections longer avoids new TLS
+    /// handshakes after a quiet spell, but holds sockets
+    /// on both ends in the meantime.
+    ///
+    /// # Arguments
+    /// * `timeout`: How long an idle connection is kept,
+    ///              `None` for indefinitely.
+    ///
+    /// # Returns
+    /// * `Self`: The builder instance for method chaining.
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = timeout;
+        self
+    }
+
+    /// # Arguments
+    /// * `interval`: Interval between HTTP/2 keep-alive
+    ///               pings, `None` to send none.
+    ///
+    /// # Returns
+    /// * `Self`: The builder instance for method chaining.
+    pub fn http2_keep_alive_interval(mut self, interval: Option<Duration>) -> Self {
+        self.http2_keep_alive_interval = interval;
+        self
+    }
+
+    /// Pinging idle connections keeps them warm for the next
+    /// burst, at the cost of periodic traffic the server has
+    /// to answer.
+    ///
+    /// # Arguments
+    /// * `enabled`: Whether keep-alive pings are sent on
+    ///              connections with no request in flight.
+    ///
+    /// # Returns
+    /// * `Self`: The builder instance for method chaining.
+    pub fn http2_keep_alive_while_idle(mut self, enabled: bool) -> Self {
+        self.http2_keep_alive_while_idle = enabled;
+        self
+    }
+
     /// # Arguments
     /// * `agent`: The User-Agent string to use in a
     ///            request.
@@ -75,18 +274,259 @@ impl HttpClientBuilder {
         self
     }
 
-    /// Builds the configured HTTP client.
+    /// Resolves `host` to `addr` instead of using DNS.
+    /// The port of `addr` is ignored in favour of the
+    /// port in the request URL, or its scheme default.
+    ///
+    /// # Arguments
+    /// * `host`: The host name to override.
+    /// * `addr`: The address to connect to for `host`.
+    ///
+    /// # Returns
+    /// * `Self`: The builder instance for method chaining.
+    pub fn resolve(mut self, host: &str, addr: SocketAddr) -> Self {
+        self.dns_overrides.push((host.to_string(), addr));
+        self
+    }
+
+    /// Binds outgoing connections to a local address. Only
+    /// servers of the same IP family are reachable.
+    ///
+    /// # Arguments
+    /// * `addr`: The local address to connect from.
+    ///
+    /// # Returns
+    /// * `Self`: The builder instance for method chaining.
+    pub fn local_address(mut self, addr: IpAddr) -> Self {
+        self.local_address = Some(addr);
+        self
+    }
+
+    /// Restricts connections to one IP family by binding to
+    /// that family's unspecified address. `Auto` leaves the
+    /// choice, and any `local_address`, unchanged.
+    ///
+    /// # Arguments
+    /// * `family`: The IP family to use.
+    ///
+    /// # Returns
+    /// * `Self`: The builder instance for method chaining.
+    pub fn ip_family(self, family: IpFamily) -> Self {
+        match family {
+            IpFamily::Auto => self,
+            IpFamily::V4   => self.local_address(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            IpFamily::V6   => self.local_address(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+        }
+    }
+
+    /// # Arguments
+    /// * `url`: The proxy for `http://` requests.
+    ///
+    /// # Returns
+    /// * `Self`: The builder instance for method chaining.
+    pub fn http_proxy(mut self, url: &str) -> Self {
+        self.http_proxy = Some(url.to_string());
+        self
+    }
+
+    /// # Arguments
+    /// * `url`: The proxy for `https://` requests.
+    ///
+    /// # Returns
+    /// * `Self`: The builder instance for method chaining.
+    pub fn https_proxy(mut self, url: &str) -> Self {
+        self.https_proxy = Some(url.to_string());
+        self
+    }
+
+    /// # Arguments
+    /// * `hosts`: Comma-separated hosts, domains, or CIDR
+    ///            ranges that bypass the proxies.
+    ///
+    /// # Returns
+    /// * `Self`: The builder instance for method chaining.
+    pub fn no_proxy(mut self, hosts: &str) -> Self {
+        self.no_proxy = Some(hosts.to_string());
+        self
+    }
+
+    /// Trusts `certificate` in addition to the system's
+    /// root certificates.
+    ///
+    /// # Arguments
+    /// * `certificate`: The root certificate to trust.
+    ///
+    /// # Returns
+    /// * `Self`: The builder instance for method chaining.
+    pub fn add_root_certificate(mut self, certificate: Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Builds the configured HTTP client, warning if it
+    /// accepts invalid certificates.
     ///
     /// # Returns
     /// `ResultHandler<Client>`: A configured client or an
     ///                          error if the client could
     ///                          not be constructed.
     pub fn build(self) -> ResultHandler<Client> {
-        Client::builder()
+        if self.accept_invalid_certs {
+            eprintln!("WARNING: TLS certificate verification is disabled for IronShield API requests.");
+        }
+
+        let mut builder = Client::builder()
             .timeout(self.timeout)
             .user_agent(self.user_agent)
             .danger_accept_invalid_certs(self.accept_invalid_certs)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .http2_keep_alive_interval(self.http2_keep_alive_interval)
+            .http2_keep_alive_while_idle(self.http2_keep_alive_while_idle);
+
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+
+        if let Some(local_address) = self.local_address {
+            builder = builder.local_address(local_address);
+        }
+
+        if let Some(url) = &self.http_proxy {
+            builder = builder.proxy(bypassed_proxy(Proxy::http(url), url, self.no_proxy.as_deref())?);
+        }
+
+        if let Some(url) = &self.https_proxy {
+            builder = builder.proxy(bypassed_proxy(Proxy::https(url), url, self.no_proxy.as_deref())?);
+        }
+
+        for certificate in self.root_certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+
+        for (host, addr) in &self.dns_overrides {
+            if host.is_empty() {
+                return Err(ErrorHandler::config_error(
+                    "DNS override host cannot be empty"
+                ));
+            }
+
+            if addr.ip().is_unspecified() {
+                return Err(ErrorHandler::config_error(format!(
+                    "DNS override for '{}' has an unspecified address: {}", host, addr
+                )));
+            }
+
+            builder = builder.resolve(host, *addr);
+        }
+
+        builder
             .build()
             .map_err(ErrorHandler::from_network_error)
     }
-}
\ No newline at end of file
+}
+
+/// # Arguments
+/// * `proxy`:    The proxy built from `url`.
+/// * `url`:      The proxy URL, for the error message.
+/// * `no_proxy`: Hosts that bypass the proxy, if any.
+///
+/// # Returns
+/// * `ResultHandler<Proxy>`: The proxy with its bypass list,
+///                           or an error if `url` is invalid.
+fn bypassed_proxy(
+    proxy:    reqwest::Result<Proxy>,
+    url:      &str,
+    no_proxy: Option<&str>,
+) -> ResultHandler<Proxy> {
+    let proxy: Proxy = proxy.map_err(|e| {
+        ErrorHandler::config_error(format!("Invalid proxy URL '{}': {}", url, e))
+    })?;
+
+    Ok(proxy.no_proxy(no_proxy.and_then(NoProxy::from_string)))
+}
+
+/// # Arguments
+/// * `path`: A certificate file.
+///
+/// # Returns
+/// * `ResultHandler<Vec<u8>>`: Its contents.
+fn read_certificate_file(path: &Path) -> ResultHandler<Vec<u8>> {
+    std::fs::read(path).map_err(|e| {
+        ErrorHandler::config_error(format!("Cannot read certificate file '{}': {}", path.display(), e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_with_dns_override() {
+        let addr: SocketAddr = "127.0.0.1:8443".parse().unwrap();
+        let client = HttpClientBuilder::new()
+            .resolve("api.ironshield.cloud", addr)
+            .build();
+        assert!(client.is_ok());
+    }
+
+    #[test]
+    fn test_ip_family_binds_unspecified_address() {
+        let builder = HttpClientBuilder::new().ip_family(IpFamily::V6);
+        assert_eq!(builder.local_address, Some(IpAddr::V6(Ipv6Addr::UNSPECIFIED)));
+        assert!(builder.build().is_ok());
+
+        assert_eq!(HttpClientBuilder::new().ip_family(IpFamily::Auto).local_address, None);
+    }
+
+    #[test]
+    fn test_build_without_keep_alive() {
+        let builder = HttpClientBuilder::new()
+            .pool_idle_timeout(None)
+            .http2_keep_alive_interval(None)
+            .http2_keep_alive_while_idle(false);
+        assert_eq!(builder.http2_keep_alive_interval, None);
+        assert!(builder.build().is_ok());
+    }
+
+    #[test]
+    fn test_from_env_reads_proxies_and_insecure() {
+        let builder = HttpClientBuilder::from_env_with(|name| match name {
+            "https_proxy"             => Some("http://proxy.internal:3128".to_string()),
+            "NO_PROXY"                => Some("localhost,.internal".to_string()),
+            "IRONSHIELD_INSECURE_TLS" => Some("true".to_string()),
+            _                         => None,
+        }).unwrap();
+
+        assert_eq!(builder.https_proxy.as_deref(), Some("http://proxy.internal:3128"));
+        assert_eq!(builder.http_proxy, None);
+        assert!(builder.accept_invalid_certs);
+
+        let builder = builder.accept_invalid_certs(false).https_proxy("http://other:8080");
+        assert!(!builder.accept_invalid_certs);
+        assert_eq!(builder.https_proxy.as_deref(), Some("http://other:8080"));
+        assert!(builder.build().is_ok());
+
+        // Allowing plaintext HTTP leaves certificate checks on.
+        let builder = HttpClientBuilder::from_env_with(|name| {
+            (name == "IRONSHIELD_ALLOW_HTTP").then(|| "1".to_string())
+        }).unwrap();
+        assert!(!builder.accept_invalid_certs);
+    }
+
+    #[test]
+    fn test_from_env_rejects_missing_cert_file() {
+        let result = HttpClientBuilder::from_env_with(|name| {
+            (name == "SSL_CERT_FILE").then(|| "/nonexistent/ironshield-ca.pem".to_string())
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_build_rejects_invalid_dns_override() {
+        let addr: SocketAddr = "127.0.0.1:8443".parse().unwrap();
+        assert!(HttpClientBuilder::new().resolve("", addr).build().is_err());
+
+        let unspecified: SocketAddr = "0.0.0.0:443".parse().unwrap();
+        assert!(HttpClientBuilder::new().resolve("api.ironshield.cloud", unspecified).build().is_err());
+    }
+}
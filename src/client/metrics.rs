@@ -0,0 +1,399 @@
+//! # Lightweight solver load metrics and CPU accounting.
+
+use crate::handler::error::ErrorHandler;
+use crate::handler::result::ResultHandler;
+
+use std::collections::{
+    BTreeMap,
+    VecDeque
+};
+use std::fmt::Write;
+use std::time::Duration;
+use std::sync::{
+    Arc,
+    Mutex,
+    PoisonError,
+    atomic::{
+        AtomicI64,
+        AtomicU64,
+        AtomicUsize,
+        Ordering
+    }
+};
+
+/// Shared counters describing the solver's current load.
+///
+/// Attach to a `ClientConfig` with `set_metrics` and keep a
+/// clone of the `Arc` to read the counters, e.g. from a
+/// health endpoint. All reads are single atomic loads.
+///
+/// * `in_flight`:   Solves currently running.
+/// * `completed`:   Solves that produced a solution.
+/// * `hash_rate`:   Sum of the current hash rates of all
+///                  in-flight solves.
+/// * `submissions`: Solutions submitted to the API.
+/// * `errors`:      Failed solves and API requests by
+///                  `ErrorHandler` variant.
+#[derive(Debug, Default)]
+pub struct SolverMetrics {
+    in_flight:   AtomicUsize,
+    completed:   AtomicU64,
+    hash_rate:   AtomicI64,
+    submissions: AtomicU64,
+    errors:      Mutex<BTreeMap<&'static str, u64>>,
+}
+
+impl SolverMetrics {
+    /// # Returns
+    /// * `Self`: Metrics with all counters at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// # Returns
+    /// * `usize`: Number of solves currently running.
+    pub fn in_flight_solves(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// # Returns
+    /// * `u64`: Number of solves that found a solution.
+    pub fn total_solves_completed(&self) -> u64 {
+        self.completed.load(Ordering::Relaxed)
+    }
+
+    /// # Returns
+    /// * `u64`: Combined attempts per second of all
+    ///          in-flight solves.
+    pub fn aggregate_hashrate(&self) -> u64 {
+        std::cmp::max(0, self.hash_rate.load(Ordering::Relaxed)) as u64
+    }
+
+    /// # Returns
+    /// * `u64`: Number of solutions submitted to the API.
+    pub fn total_submissions(&self) -> u64 {
+        self.submissions.load(Ordering::Relaxed)
+    }
+
+    /// # Returns
+    /// * `BTreeMap<&'static str, u64>`: Failed solves and API
+    ///                                  requests, keyed by
+    ///                                  `ErrorHandler` variant.
+    pub fn errors_by_variant(&self) -> BTreeMap<&'static str, u64> {
+        self.errors.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Renders the counters in the OpenMetrics text exposition
+    /// format, e.g. to serve from a `/metrics` handler.
+    ///
+    /// # Returns
+    /// * `String`: The exposition, terminated by `# EOF`.
+    ///
+    /// # Example
+    /// ```
+    /// use ironshield::client::metrics::SolverMetrics;
+    ///
+    /// let text = SolverMetrics::new().render_openmetrics();
+    /// assert!(text.contains("ironshield_solves_completed_total 0"));
+    /// ```
+    pub fn render_openmetrics(&self) -> String {
+        let mut text = String::new();
+
+        // Writing to a `String` cannot fail.
+        let _ = writeln!(text, "# TYPE ironshield_solves_in_flight gauge");
+        let _ = writeln!(text, "# HELP ironshield_solves_in_flight Solves currently running.");
+        let _ = writeln!(text, "ironshield_solves_in_flight {}", self.in_flight_solves());
+        let _ = writeln!(text, "# TYPE ironshield_solves_completed counter");
+        let _ = writeln!(text, "# HELP ironshield_solves_completed Solves that produced a solution.");
+        let _ = writeln!(text, "ironshield_solves_completed_total {}", self.total_solves_completed());
+        let _ = writeln!(text, "# TYPE ironshield_submissions counter");
+        let _ = writeln!(text, "# HELP ironshield_submissions Solutions submitted to the API.");
+        let _ = writeln!(text, "ironshield_submissions_total {}", self.total_submissions());
+        let _ = writeln!(text, "# TYPE ironshield_errors counter");
+        let _ = writeln!(text, "# HELP ironshield_errors Failed solves and API requests by error variant.");
+        for (variant, count) in self.errors_by_variant() {
+            let _ = writeln!(text, "ironshield_errors_total{{variant=\"{}\"}} {}", variant, count);
+        }
+        let _ = writeln!(text, "# TYPE ironshield_hash_rate gauge");
+        let _ = writeln!(text, "# HELP ironshield_hash_rate Combined attempts per second of in-flight solves.");
+        let _ = writeln!(text, "ironshield_hash_rate {}", self.aggregate_hashrate());
+        text.push_str("# EOF\n");
+
+        text
+    }
+
+    /// Marks a solve as started.
+    ///
+    /// # Returns
+    /// * `InFlightSolve`: Guard that marks the solve as
+    ///                    finished when dropped.
+    pub(crate) fn begin_solve(self: &Arc<Self>) -> InFlightSolve {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlightSolve { metrics: Arc::clone(self) }
+    }
+
+    /// Records a solve that produced a solution.
+    pub(crate) fn record_completed(&self) {
+        self.completed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a solution submitted to the API.
+    pub(crate) fn record_submission(&self) {
+        self.submissions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// # Arguments
+    /// * `error`: A failed solve or API request.
+    pub(crate) fn record_error(&self, error: &ErrorHandler) {
+        *self.errors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(error.variant_name())
+            .or_insert(0) += 1;
+    }
+
+    /// # Arguments
+    /// * `delta`: Change in a single solve's hash rate.
+    pub(crate) fn adjust_hash_rate(&self, delta: i64) {
+        self.hash_rate.fetch_add(delta, Ordering::Relaxed);
+    }
+}
+
+/// Keeps a solve counted as in flight until dropped.
+pub(crate) struct InFlightSolve {
+    metrics: Arc<SolverMetrics>,
+}
+
+impl Drop for InFlightSolve {
+    fn drop(&mut self) {
+        self.metrics.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Accumulated CPU time spent solving, with an optional
+/// ceiling.
+///
+/// Every solver thread is charged the wall-clock time its
+/// search ran, which accounts for parallel work that a
+/// solve's wall-clock time alone undercounts. Charges are
+/// recorded when each search ends rather than when the solve
+/// returns, so the searches a cancelled, timed out or already
+/// solved solve leaves running are counted once they finish. Attach to a `ClientConfig` with
+/// `set_cpu_budget`; once the ceiling is reached, new solves
+/// fail with `ErrorHandler::ChallengeSolvingError`.
+///
+/// * `ceiling`:      Maximum CPU time, if limited.
+/// * `used_micros`:  CPU time charged so far in
+///                   microseconds.
+#[derive(Debug, Default)]
+pub struct CpuBudget {
+    ceiling:     Option<Duration>,
+    used_micros: AtomicU64,
+}
+
+impl CpuBudget {
+    /// # Arguments
+    /// * `ceiling`: Maximum CPU time to spend solving.
+    ///
+    /// # Returns
+    /// * `Self`: A budget with nothing used yet.
+    pub fn new(ceiling: Duration) -> Self {
+        Self {
+            ceiling:     Some(ceiling),
+            used_micros: AtomicU64::new(0),
+        }
+    }
+
+    /// # Returns
+    /// * `Self`: A budget that only measures CPU time.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// # Returns
+    /// * `f64`: CPU seconds charged so far.
+    pub fn cpu_seconds_used(&self) -> f64 {
+        self.used_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0
+    }
+
+    /// # Returns
+    /// * `ResultHandler<()>`: An error if the ceiling has been
+    ///                        reached.
+    pub fn check(&self) -> ResultHandler<()> {
+        let Some(ceiling) = self.ceiling else {
+            return Ok(());
+        };
+
+        if self.used_micros.load(Ordering::Relaxed) >= ceiling.as_micros() as u64 {
+            return Err(ErrorHandler::challenge_solving_error(format!(
+                "CPU budget of {:.2}s exhausted ({:.2}s used)",
+                ceiling.as_secs_f64(),
+                self.cpu_seconds_used()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Charges finished searches to the budget.
+    ///
+    /// # Arguments
+    /// * `thread_count`: Number of threads that searched.
+    /// * `elapsed`:      Wall-clock time each search took.
+    pub(crate) fn record(&self, thread_count: usize, elapsed: Duration) {
+        let micros: u64 = (elapsed.as_micros() as u64).saturating_mul(thread_count as u64);
+        self.used_micros.fetch_add(micros, Ordering::Relaxed);
+    }
+}
+
+/// A failed API request or solve, as kept by `ErrorLog`.
+///
+/// * `timestamp_ms`: When it failed, in Unix epoch
+///                   milliseconds from `ClientConfig::clock`.
+/// * `endpoint`:     The API URL requested, or `None` for
+///                   a solve.
+/// * `variant`:      The `ErrorHandler` variant.
+/// * `message`:      The error message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorRecord {
+    pub timestamp_ms: i64,
+    pub endpoint:     Option<String>,
+    pub variant:      &'static str,
+    pub message:      String,
+}
+
+/// The most recent failed API requests and solves, e.g. for
+/// a diagnostics panel. Attach to a `ClientConfig` with
+/// `set_error_log` and read it with
+/// `IronShieldClient::recent_errors`.
+///
+/// * `capacity`: Records kept before the oldest is dropped.
+/// * `records`:  Kept records, oldest first.
+#[derive(Debug)]
+pub struct ErrorLog {
+    capacity: usize,
+    records:  Mutex<VecDeque<ErrorRecord>>,
+}
+
+impl ErrorLog {
+    /// # Arguments
+    /// * `capacity`: Number of records to keep.
+    ///
+    /// # Returns
+    /// * `Self`: An empty log.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records:  Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// # Returns
+    /// * `Vec<ErrorRecord>`: The kept records, oldest first.
+    pub fn records(&self) -> Vec<ErrorRecord> {
+        self.records.lock().unwrap_or_else(PoisonError::into_inner).iter().cloned().collect()
+    }
+
+    /// Records a failure, dropping the oldest record if the
+    /// log is full.
+    ///
+    /// # Arguments
+    /// * `timestamp_ms`: When it failed.
+    /// * `endpoint`:     The API URL requested, if any.
+    /// * `error`:        The failure.
+    pub(crate) fn record(&self, timestamp_ms: i64, endpoint: Option<String>, error: &ErrorHandler) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut records = self.records.lock().unwrap_or_else(PoisonError::into_inner);
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+
+        records.push_back(ErrorRecord {
+            timestamp_ms,
+            endpoint,
+            variant: error.variant_name(),
+            message: error.to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_log_keeps_most_recent() {
+        let log = ErrorLog::new(2);
+        for (timestamp_ms, message) in [(1, "first"), (2, "second"), (3, "third")] {
+            log.record(timestamp_ms, None, &ErrorHandler::ProcessingError(message.to_string()));
+        }
+
+        let records = log.records();
+        assert_eq!(records.iter().map(|record| record.timestamp_ms).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(records[1].variant, "ProcessingError");
+        assert!(records[1].message.contains("third"));
+    }
+
+    #[test]
+    fn test_in_flight_guard() {
+        let metrics = Arc::new(SolverMetrics::new());
+
+        let first = metrics.begin_solve();
+        let second = metrics.begin_solve();
+        assert_eq!(metrics.in_flight_solves(), 2);
+
+        drop(first);
+        metrics.record_completed();
+        assert_eq!(metrics.in_flight_solves(), 1);
+        assert_eq!(metrics.total_solves_completed(), 1);
+
+        drop(second);
+        assert_eq!(metrics.in_flight_solves(), 0);
+    }
+
+    #[test]
+    fn test_aggregate_hashrate_never_negative() {
+        let metrics = SolverMetrics::new();
+
+        metrics.adjust_hash_rate(-5);
+        assert_eq!(metrics.aggregate_hashrate(), 0);
+
+        metrics.adjust_hash_rate(15);
+        assert_eq!(metrics.aggregate_hashrate(), 10);
+    }
+
+    #[test]
+    fn test_render_openmetrics() {
+        let metrics = SolverMetrics::new();
+        metrics.record_submission();
+        metrics.record_error(&ErrorHandler::ProcessingError("boom".to_string()));
+        metrics.record_error(&ErrorHandler::ProcessingError("boom".to_string()));
+        metrics.adjust_hash_rate(1_500);
+
+        let text = metrics.render_openmetrics();
+        assert!(text.contains("ironshield_submissions_total 1\n"));
+        assert!(text.contains("ironshield_errors_total{variant=\"ProcessingError\"} 2\n"));
+        assert!(text.contains("ironshield_hash_rate 1500\n"));
+        assert!(text.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn test_cpu_budget_enforces_ceiling() {
+        let budget = CpuBudget::new(Duration::from_secs(2));
+        assert!(budget.check().is_ok());
+
+        budget.record(4, Duration::from_millis(500));
+        assert_eq!(budget.cpu_seconds_used(), 2.0);
+        assert!(budget.check().is_err());
+    }
+
+    #[test]
+    fn test_unlimited_cpu_budget() {
+        let budget = CpuBudget::unlimited();
+        budget.record(64, Duration::from_secs(3600));
+        assert!(budget.check().is_ok());
+    }
+}
@@ -0,0 +1,543 @@
+//! # `ProgressTracker` implementations.
+
+use ironshield_types::chrono;
+use tokio::sync::mpsc;
+
+use crate::client::solve::ProgressTracker;
+use crate::client::stats::format_number_with_commas;
+use crate::handler::result::ResultHandler;
+
+use std::collections::HashMap;
+use std::fs::{
+    File,
+    OpenOptions
+};
+use std::io::{
+    BufWriter,
+    Write
+};
+use std::path::{
+    Path,
+    PathBuf
+};
+use std::fmt;
+use std::sync::{
+    Arc,
+    Mutex,
+    PoisonError
+};
+use std::time::{
+    Duration,
+    Instant
+};
+
+/// A single progress update from one worker thread of a
+/// running solve. Sum the latest event of each thread for
+/// the solve's total. Only the multithreaded solver reports
+/// progress; single-threaded solves emit no events.
+///
+/// * `thread_id`:      The reporting worker thread.
+/// * `total_attempts`: Attempts made by this thread.
+/// * `hash_rate`:      This thread's attempts per second.
+/// * `elapsed`:        Time since this thread started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressEvent {
+    pub thread_id:      usize,
+    pub total_attempts: u64,
+    pub hash_rate:      u64,
+    pub elapsed:        Duration,
+}
+
+/// Turns a worker's progress batches into the hash rate
+/// passed to `ProgressTracker::on_progress`.
+///
+/// Each worker thread gets its own smoother from the factory
+/// set with `ClientConfig::set_hash_rate_smoother`, so
+/// implementations only ever see one thread's samples.
+pub trait HashRateSmoother: Send {
+    /// # Arguments
+    /// * `attempts_delta`: Attempts made since the last sample.
+    /// * `dt`:             Time since the last sample.
+    ///
+    /// # Returns
+    /// * `u64`: The hash rate to report, in attempts per second.
+    fn sample(&mut self, attempts_delta: u64, dt: Duration) -> u64;
+}
+
+/// Creates a `HashRateSmoother` for each worker thread.
+pub type HashRateSmootherFactory = dyn Fn() -> Box<dyn HashRateSmoother> + Send + Sync;
+
+/// The default smoother: cumulative attempts divided by
+/// total elapsed time.
+///
+/// * `attempts`: Attempts sampled so far.
+/// * `elapsed`:  Time covered by the samples.
+#[derive(Debug, Default)]
+pub struct CumulativeSmoother {
+    attempts: u64,
+    elapsed:  Duration,
+}
+
+impl HashRateSmoother for CumulativeSmoother {
+    fn sample(&mut self, attempts_delta: u64, dt: Duration) -> u64 {
+        self.attempts += attempts_delta;
+        self.elapsed += dt;
+
+        let elapsed_millis: u64 = self.elapsed.as_millis() as u64;
+        if elapsed_millis > 0 {
+            (self.attempts * 1000) / elapsed_millis
+        } else {
+            self.attempts // If solved instantly, assume 1ms.
+        }
+    }
+}
+
+/// Forwards progress updates into a caller-owned channel.
+///
+/// Uses `try_send` so solving never blocks on the
+/// receiver: events are dropped while the channel is full
+/// or closed.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use tokio::sync::mpsc;
+/// use ironshield::client::progress::ChannelProgressTracker;
+///
+/// let (tx, _rx) = mpsc::channel(64);
+/// let tracker = Arc::new(ChannelProgressTracker::new(tx));
+/// ```
+pub struct ChannelProgressTracker {
+    tx: mpsc::Sender<ProgressEvent>,
+}
+
+impl ChannelProgressTracker {
+    /// # Arguments
+    /// * `tx`: The channel to send progress events into.
+    ///
+    /// # Returns
+    /// * `Self`: A tracker forwarding into `tx`.
+    pub fn new(tx: mpsc::Sender<ProgressEvent>) -> Self {
+        Self { tx }
+    }
+}
+
+impl ProgressTracker for ChannelProgressTracker {
+    fn on_progress(
+        &self,
+        thread_id:      usize,
+        total_attempts: u64,
+        hash_rate:      u64,
+        elapsed:        Duration
+    ) {
+        // A full or closed channel must never interrupt solving.
+        let _ = self.tx.try_send(ProgressEvent {
+            thread_id,
+            total_attempts,
+            hash_rate,
+            elapsed,
+        });
+    }
+}
+
+/// A combined progress readout across a batch of solves.
+///
+/// * `challenges`:     Solves registered with the batch.
+/// * `completed`:      Solves that have finished, whether
+///                     or not they succeeded.
+/// * `total_attempts`: Attempts made across all solves.
+/// * `hash_rate`:      Combined hash rate of the solves
+///                     still running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BatchProgress {
+    pub challenges:     usize,
+    pub completed:      usize,
+    pub total_attempts: u64,
+    pub hash_rate:      u64,
+}
+
+impl fmt::Display for BatchProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "solving {} challenges, {} total attempts @ {} h/s, {} done",
+            self.challenges,
+            format_number_with_commas(self.total_attempts),
+            format_number_with_commas(self.hash_rate),
+            self.completed,
+        )
+    }
+}
+
+/// Per-thread attempts and hash rate of one solve in a batch,
+/// and the solve's attempt count once it has finished.
+#[derive(Default)]
+struct BatchSolve {
+    threads:        HashMap<usize, (u64, u64)>,
+    final_attempts: Option<u64>,
+    finished:       bool,
+}
+
+/// Aggregates progress across many concurrent solves into a
+/// single `BatchProgress`, for one UI element or dashboard
+/// gauge rather than a readout per challenge.
+///
+/// Pass it to `solve_challenges` or `solve_pipeline`, or
+/// register solves by hand with `start` and pass each
+/// returned `BatchMember` as that solve's `ProgressTracker`.
+///
+/// # Example
+/// ```no_run
+/// # async fn example(challenges: Vec<ironshield_types::IronShieldChallenge>) {
+/// use std::sync::Arc;
+/// use ironshield::{solve_challenges, ClientConfig};
+/// use ironshield::client::progress::BatchProgressTracker;
+///
+/// let batch = Arc::new(BatchProgressTracker::new());
+/// let config = ClientConfig::default();
+/// let results = solve_challenges(challenges, &config, false, None, Some(batch.clone())).await;
+/// println!("{}", batch.progress());
+/// # }
+/// ```
+#[derive(Default)]
+pub struct BatchProgressTracker {
+    solves: Mutex<Vec<BatchSolve>>,
+}
+
+impl BatchProgressTracker {
+    /// # Returns
+    /// * `Self`: An empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a new solve with the batch.
+    ///
+    /// # Returns
+    /// * `Arc<BatchMember>`: The progress tracker for the solve.
+    ///                       Call `BatchMember::finish` once
+    ///                       the solve ends.
+    pub fn start(self: &Arc<Self>) -> Arc<BatchMember> {
+        let mut solves = self.solves.lock().unwrap_or_else(PoisonError::into_inner);
+        solves.push(BatchSolve::default());
+
+        Arc::new(BatchMember {
+            batch: Arc::clone(self),
+            index: solves.len() - 1,
+        })
+    }
+
+    /// # Returns
+    /// * `BatchProgress`: The current aggregate across all
+    ///                    registered solves.
+    pub fn progress(&self) -> BatchProgress {
+        let solves = self.solves.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut progress = BatchProgress {
+            challenges: solves.len(),
+            ..BatchProgress::default()
+        };
+
+        for solve in solves.iter() {
+            progress.total_attempts += solve.final_attempts
+                .unwrap_or_else(|| solve.threads.values().map(|(attempts, _)| attempts).sum::<u64>());
+            if solve.finished {
+                progress.completed += 1;
+            } else {
+                progress.hash_rate += solve.threads.values().map(|(_, rate)| rate).sum::<u64>();
+            }
+        }
+
+        progress
+    }
+}
+
+/// One solve's handle into a `BatchProgressTracker`.
+///
+/// * `batch`: The batch the solve reports into.
+/// * `index`: The solve's slot in the batch.
+pub struct BatchMember {
+    batch: Arc<BatchProgressTracker>,
+    index: usize,
+}
+
+impl BatchMember {
+    /// Marks the solve as done. Its attempts stay in the
+    /// batch total, but it no longer counts toward the hash
+    /// rate.
+    pub fn finish(&self) {
+        let mut solves = self.batch.solves.lock().unwrap_or_else(PoisonError::into_inner);
+        solves[self.index].finished = true;
+    }
+
+    /// Marks the solve as done like `finish`, counting its
+    /// final attempts in the batch total instead of the last
+    /// progress reports, which lag behind or are missing for
+    /// single-threaded solves.
+    ///
+    /// # Arguments
+    /// * `attempts`: Total attempts the solve made.
+    pub fn finish_with_attempts(&self, attempts: u64) {
+        let mut solves = self.batch.solves.lock().unwrap_or_else(PoisonError::into_inner);
+        let solve: &mut BatchSolve = &mut solves[self.index];
+
+        solve.final_attempts = Some(attempts);
+        solve.finished = true;
+    }
+}
+
+impl ProgressTracker for BatchMember {
+    fn on_progress(
+        &self,
+        thread_id:      usize,
+        total_attempts: u64,
+        hash_rate:      u64,
+        _elapsed:       Duration
+    ) {
+        let mut solves = self.batch.solves.lock().unwrap_or_else(PoisonError::into_inner);
+        let solve: &mut BatchSolve = &mut solves[self.index];
+
+        if !solve.finished {
+            solve.threads.insert(thread_id, (total_attempts, hash_rate));
+        }
+    }
+}
+
+/// How often buffered progress lines are flushed to disk.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Appends timestamped progress lines to a log file.
+///
+/// Lines are buffered and flushed at most once per
+/// `FLUSH_INTERVAL`, and on drop. With a maximum size
+/// set, the file is rotated to `<path>.1` once it grows
+/// past that size, replacing any previous rotation.
+///
+/// # Example
+/// ```no_run
+/// use std::sync::Arc;
+/// use ironshield::client::progress::FileProgressTracker;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let tracker = FileProgressTracker::new("/var/log/ironshield/progress.log")?
+///     .with_max_size(10 * 1024 * 1024);
+/// let tracker = Arc::new(tracker);
+/// # Ok(())
+/// # }
+/// ```
+pub struct FileProgressTracker {
+    path:     PathBuf,
+    max_size: Option<u64>,
+    log:      Mutex<LogFile>,
+}
+
+/// The open log file and its bookkeeping.
+struct LogFile {
+    writer:     BufWriter<File>,
+    size:       u64,
+    last_flush: Instant,
+}
+
+impl LogFile {
+    /// # Arguments
+    /// * `path`: The log file to append to.
+    ///
+    /// # Returns
+    /// * `ResultHandler<Self>`: The opened file or an IO error.
+    fn open(path: &Path) -> ResultHandler<Self> {
+        let file: File = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        let size: u64 = file.metadata()?.len();
+
+        Ok(Self {
+            writer:     BufWriter::new(file),
+            size,
+            last_flush: Instant::now(),
+        })
+    }
+}
+
+impl FileProgressTracker {
+    /// # Arguments
+    /// * `path`: The log file to append progress lines to.
+    ///           Created if it does not exist.
+    ///
+    /// # Returns
+    /// * `ResultHandler<Self>`: The tracker, or an IO error if
+    ///                          the file could not be opened.
+    pub fn new(path: impl AsRef<Path>) -> ResultHandler<Self> {
+        let path: PathBuf = path.as_ref().to_path_buf();
+        let log: LogFile = LogFile::open(&path)?;
+
+        Ok(Self {
+            path,
+            max_size: None,
+            log:      Mutex::new(log),
+        })
+    }
+
+    /// # Arguments
+    /// * `bytes`: Size after which the log file is rotated.
+    ///
+    /// # Returns
+    /// * `Self`: The tracker for method chaining.
+    pub fn with_max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Moves the current log to `<path>.1` and starts a new one.
+    ///
+    /// # Arguments
+    /// * `log`: The currently open log file.
+    fn rotate(&self, log: &mut LogFile) -> ResultHandler<()> {
+        log.writer.flush()?;
+
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(".1");
+        std::fs::rename(&self.path, rotated)?;
+
+        *log = LogFile::open(&self.path)?;
+        Ok(())
+    }
+
+    /// Writes a single progress line, rotating and flushing
+    /// as needed.
+    fn write_line(&self, line: &str) -> ResultHandler<()> {
+        let mut log = self.log.lock().unwrap_or_else(PoisonError::into_inner);
+
+        log.writer.write_all(line.as_bytes())?;
+        log.size += line.len() as u64;
+
+        if let Some(max_size) = self.max_size {
+            if log.size >= max_size {
+                return self.rotate(&mut log);
+            }
+        }
+
+        if log.last_flush.elapsed() >= FLUSH_INTERVAL {
+            log.writer.flush()?;
+            log.last_flush = Instant::now();
+        }
+
+        Ok(())
+    }
+}
+
+impl ProgressTracker for FileProgressTracker {
+    fn on_progress(
+        &self,
+        thread_id:      usize,
+        total_attempts: u64,
+        hash_rate:      u64,
+        elapsed:        Duration
+    ) {
+        let line: String = format!(
+            "{} thread={} attempts={} hash_rate={} h/s elapsed={:.2}s\n",
+            chrono::Utc::now().to_rfc3339(),
+            thread_id,
+            format_number_with_commas(total_attempts),
+            format_number_with_commas(hash_rate),
+            elapsed.as_secs_f64(),
+        );
+
+        // Progress logging must never interrupt solving.
+        let _ = self.write_line(&line);
+    }
+}
+
+impl Drop for FileProgressTracker {
+    fn drop(&mut self) {
+        let log = self.log.get_mut().unwrap_or_else(PoisonError::into_inner);
+        let _ = log.writer.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cumulative_smoother() {
+        let mut smoother = CumulativeSmoother::default();
+        assert_eq!(smoother.sample(500, Duration::ZERO), 500);
+        assert_eq!(smoother.sample(1_500, Duration::from_millis(500)), 4_000);
+        assert_eq!(smoother.sample(0, Duration::from_millis(500)), 2_000);
+    }
+
+    #[test]
+    fn test_channel_progress_tracker_drops_when_full() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let tracker = ChannelProgressTracker::new(tx);
+
+        tracker.on_progress(0, 100, 50, Duration::from_secs(2));
+        tracker.on_progress(1, 200, 100, Duration::from_secs(2));
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.total_attempts, 100);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_batch_progress_tracker_aggregates() {
+        let batch = Arc::new(BatchProgressTracker::new());
+        let first = batch.start();
+        let second = batch.start();
+
+        first.on_progress(0, 1_000, 500, Duration::from_secs(2));
+        first.on_progress(1, 2_000, 1_000, Duration::from_secs(2));
+        second.on_progress(0, 3_000, 1_500, Duration::from_secs(2));
+        first.on_progress(0, 1_500, 600, Duration::from_secs(3));
+
+        assert_eq!(batch.progress(), BatchProgress {
+            challenges:     2,
+            completed:      0,
+            total_attempts: 6_500,
+            hash_rate:      3_100,
+        });
+
+        second.finish();
+        let progress = batch.progress();
+        assert_eq!(progress.completed, 1);
+        assert_eq!(progress.total_attempts, 6_500);
+        assert_eq!(progress.hash_rate, 1_600);
+        assert_eq!(
+            progress.to_string(),
+            "solving 2 challenges, 6,500 total attempts @ 1,600 h/s, 1 done"
+        );
+
+        first.finish_with_attempts(4_000);
+        assert_eq!(batch.progress().total_attempts, 7_000);
+    }
+
+    #[test]
+    fn test_file_progress_tracker_writes_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("progress.log");
+
+        let tracker = FileProgressTracker::new(&path).unwrap();
+        tracker.on_progress(0, 1500, 1000, Duration::from_millis(1500));
+        tracker.on_progress(1, 2500, 2000, Duration::from_millis(1250));
+        drop(tracker);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("thread=0 attempts=1,500 hash_rate=1,000 h/s elapsed=1.50s"));
+    }
+
+    #[test]
+    fn test_file_progress_tracker_rotates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("progress.log");
+
+        let tracker = FileProgressTracker::new(&path).unwrap().with_max_size(1);
+        tracker.on_progress(0, 1, 1, Duration::from_secs(1));
+        tracker.on_progress(0, 2, 1, Duration::from_secs(2));
+        drop(tracker);
+
+        assert!(dir.path().join("progress.log.1").exists());
+        assert!(std::fs::read_to_string(&path).unwrap().is_empty());
+    }
+}
@@ -6,22 +6,92 @@ use ironshield_types::{
     IronShieldToken,
 };
 
+#[cfg(feature = "gzip")]
+use crate::client::compression;
+use crate::client::challenge::{
+    DifficultyInfo,
+    PreparedChallenge
+};
+use crate::client::circuit::CircuitState;
 use crate::client::config::ClientConfig;
 use crate::client::http::HttpClientBuilder;
+use crate::client::metrics::ErrorRecord;
 use crate::client::response::ApiResponse;
+use crate::client::retry::{
+    RetryBudget,
+    RetryPolicy
+};
+use crate::client::stats::SolveStats;
+#[cfg(feature = "hmac")]
+use crate::client::signing::{
+    self,
+    FINGERPRINT_HEADER,
+    FINGERPRINT_TIMESTAMP_HEADER,
+    SIGNATURE_HEADER,
+    TIMESTAMP_HEADER
+};
+use crate::constant::{
+    client_version_string,
+    CLIENT_VERSION_HEADER
+};
 use crate::handler::{
     error::{
         ErrorHandler, 
-        INVALID_ENDPOINT
+        INVALID_ENDPOINT,
+        SIGNATURE_FAIL
     },
     result::ResultHandler
 };
 
-use reqwest::Client;
+use futures::future::{
+    BoxFuture,
+    FutureExt,
+    Shared
+};
+use reqwest::{
+    Client,
+    RequestBuilder
+};
+
+use std::collections::HashMap;
+use std::sync::{
+    Arc,
+    Mutex,
+    PoisonError,
+    atomic::{
+        AtomicU64,
+        AtomicUsize,
+        Ordering
+    }
+};
+
+/// Media type advertised when `ClientConfig::accept_header`
+/// is unset.
+const DEFAULT_ACCEPT: &str = "application/json";
+
+/// Request body for a single challenge covering several
+/// endpoints.
+///
+/// * `endpoints`: The protected endpoint URLs.
+/// * `timestamp`: The request time as Unix epoch milliseconds.
+#[derive(serde::Serialize)]
+struct MultiEndpointRequest<'a> {
+    endpoints: &'a [&'a str],
+    timestamp: i64,
+}
+
+/// An in-flight challenge fetch that concurrent callers
+/// for the same endpoint can await together.
+type SharedFetch = Shared<BoxFuture<'static, Result<IronShieldChallenge, String>>>;
 
 pub struct IronShieldClient {
-    config:      ClientConfig,
-    http_client: Client,
+    config:            ClientConfig,
+    http_client:       Client,
+    insecure:          bool,
+    in_flight_fetches: Arc<Mutex<HashMap<String, SharedFetch>>>,
+    active_api_key:    Arc<AtomicUsize>,
+    bytes_sent:        Arc<AtomicU64>,
+    bytes_received:    Arc<AtomicU64>,
 }
 
 impl IronShieldClient {
@@ -51,18 +121,213 @@ impl IronShieldClient {
             ));
         }
 
-        let http_client = HttpClientBuilder::new()
+        Self::build(config, false)
+    }
+
+    /// Creates a client that may talk to the API over plain HTTP,
+    /// e.g. through a trusted local reverse proxy that terminates
+    /// TLS upstream. A warning is logged on every request sent
+    /// without TLS.
+    ///
+    /// # Arguments
+    /// * `config`: The client configuration, which must have
+    ///             `allow_insecure` set.
+    ///
+    /// # Return
+    /// * `ResultHandler<Self>`: The initialized client or an error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use ironshield::client::config::ClientConfig;
+    /// use ironshield::client::request::IronShieldClient;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut config = ClientConfig::testing();
+    ///     config.allow_insecure = true;
+    ///     let client = IronShieldClient::new_insecure(config)?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn new_insecure(config: ClientConfig) -> ResultHandler<Self> {
+        if !config.allow_insecure {
+            return Err(ErrorHandler::config_error(
+                "Insecure clients require `allow_insecure` to be set in the configuration"
+            ));
+        }
+
+        if !config.api_base_url.starts_with("http://") && !config.api_base_url.starts_with("https://") {
+            return Err(ErrorHandler::config_error(
+                "API base URL must start with http:// or https://"
+            ));
+        }
+
+        let insecure: bool = !config.api_base_url.starts_with("https://");
+        Self::build(config, insecure)
+    }
+
+    /// # Arguments
+    /// * `config`:   The validated client configuration.
+    /// * `insecure`: Whether requests are sent without TLS.
+    ///
+    /// # Return
+    /// * `ResultHandler<Self>`: The initialized client or an error.
+    fn build(config: ClientConfig, insecure: bool) -> ResultHandler<Self> {
+        if let Some(accept) = &config.accept_header {
+            validate_accept_header(accept)?;
+        }
+
+        let http_client = if config.network_from_env {
+            HttpClientBuilder::from_env()?
+        } else {
+            HttpClientBuilder::new()
+        };
+        let mut http_client = http_client
             .timeout(config.timeout)
-            .build()?;
+            .user_agent(&config.user_agent)
+            .ip_family(config.ip_family)
+            .pool_idle_timeout(config.pool_idle_timeout)
+            .http2_keep_alive_interval(config.http2_keep_alive_interval)
+            .http2_keep_alive_while_idle(config.http2_keep_alive_while_idle);
+
+        if let Some(connect_timeout) = config.connect_timeout {
+            http_client = http_client.connect_timeout(connect_timeout);
+        }
+
+        for (host, addr) in &config.dns_overrides {
+            http_client = http_client.resolve(host, *addr);
+        }
+
+        let http_client = http_client.build()?;
 
         Ok(Self {
             config,
-            http_client
+            http_client,
+            insecure,
+            in_flight_fetches: Arc::new(Mutex::new(HashMap::new())),
+            active_api_key:    Arc::new(AtomicUsize::new(0)),
+            bytes_sent:        Arc::new(AtomicU64::new(0)),
+            bytes_received:    Arc::new(AtomicU64::new(0)),
         })
     }
 
+    /// # Returns
+    /// * `Vec<ErrorRecord>`: The most recent failed API requests
+    ///                       and solves from the attached
+    ///                       `ClientConfig::error_log`, oldest
+    ///                       first, or none if no log is attached.
+    pub fn recent_errors(&self) -> Vec<ErrorRecord> {
+        self.config.error_log.as_ref().map(|log| log.records()).unwrap_or_default()
+    }
+
+    /// # Returns
+    /// * `u64`: Request body bytes sent to the API so far,
+    ///          after compression.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    /// # Returns
+    /// * `u64`: Response body bytes received from the API
+    ///          so far.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received.load(Ordering::Relaxed)
+    }
+
+    /// # Returns
+    /// * `Option<CircuitState>`: The state of the attached
+    ///                           circuit breaker, if any.
+    pub fn circuit_state(&self) -> Option<CircuitState> {
+        self.config.circuit_breaker.as_ref().map(|breaker| breaker.state())
+    }
+
+    /// Logs a warning if this client sends requests without TLS.
+    fn warn_if_insecure(&self) {
+        if self.insecure {
+            eprintln!(
+                "WARNING: sending IronShield API request to '{}' without TLS.",
+                self.config.api_base_url
+            );
+        }
+    }
+
+    /// Fetches a challenge and derives the metadata a UI needs
+    /// before solving it. Pass the result to `solve_prepared`.
+    ///
+    /// # Arguments
+    /// * `endpoint`: The protected endpoint URL to access.
+    ///
+    /// # Returns
+    /// * `ResultHandler<PreparedChallenge>`: The challenge and
+    ///                                       its metadata.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use ironshield::client::config::ClientConfig;
+    /// use ironshield::client::request::IronShieldClient;
+    /// use ironshield::client::solve::{solve_prepared, SolveStrategy};
+    ///
+    /// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = ClientConfig::default();
+    /// # let client = IronShieldClient::new(config.clone())?;
+    /// let prepared = client.prepare_challenge("https://example.com/protected").await?;
+    /// println!("Solving a {} challenge...", prepared.difficulty_label);
+    ///
+    /// let solution = solve_prepared(prepared, &config, SolveStrategy::MultiThreaded).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn prepare_challenge(
+        &self,
+        endpoint: &str
+    ) -> ResultHandler<PreparedChallenge> {
+        self.fetch_challenge(endpoint).await.map(PreparedChallenge::new)
+    }
+
+    /// Fetches a challenge for `endpoint` only to report how
+    /// hard it is, without solving or submitting it. The
+    /// challenge is discarded and expires unused.
+    ///
+    /// # Arguments
+    /// * `endpoint`: The protected endpoint URL to probe.
+    ///
+    /// # Returns
+    /// * `ResultHandler<DifficultyInfo>`: The current difficulty,
+    ///                                    with a solve time estimate
+    ///                                    if `ClientConfig::expected_hash_rate`
+    ///                                    is set.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use ironshield::client::config::ClientConfig;
+    /// use ironshield::client::request::IronShieldClient;
+    ///
+    /// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = IronShieldClient::new(ClientConfig::default())?;
+    /// let info = client.probe_difficulty("https://example.com/protected").await?;
+    /// println!("{} bits ({})", info.bits, info.label);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn probe_difficulty(
+        &self,
+        endpoint: &str
+    ) -> ResultHandler<DifficultyInfo> {
+        let challenge: IronShieldChallenge = self.fetch_challenge(endpoint).await?;
+
+        Ok(DifficultyInfo::new(&challenge, self.config.expected_hash_rate))
+    }
+
     /// Fetches a challenge from the IronShield API.
     ///
+    /// The endpoint is canonicalized with `normalize_endpoint`
+    /// first, so the challenge is issued for the URL that function
+    /// returns.
+    ///
+    /// With `ClientConfig::coalesce_fetches` enabled, concurrent
+    /// calls for the same endpoint share a single network request
+    /// and all receive a clone of its challenge. Errors are then
+    /// reported to every waiter as `ErrorHandler::ProcessingError`.
+    ///
     /// # Arguments
     /// * `endpoint`: The protected endpoint URL to access.
     ///
@@ -85,30 +350,491 @@ impl IronShieldClient {
     pub async fn fetch_challenge(
         &self,
         endpoint: &str
+    ) -> ResultHandler<IronShieldChallenge> {
+        let endpoint: String = normalize_endpoint(endpoint)?;
+        let endpoint: &str = &endpoint;
+
+        if !self.config.coalesce_fetches {
+            return self.request_challenge(endpoint, None, None).await;
+        }
+
+        let fetch: SharedFetch = {
+            let mut in_flight = self.in_flight_fetches
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+
+            in_flight
+                .entry(endpoint.to_string())
+                .or_insert_with(|| self.start_shared_fetch(endpoint))
+                .clone()
+        };
+
+        fetch.await.map_err(ErrorHandler::ProcessingError)
+    }
+
+    /// Starts a fetch that removes itself from the in-flight
+    /// map once it completes.
+    ///
+    /// # Arguments
+    /// * `endpoint`: The protected endpoint URL to access.
+    ///
+    /// # Returns
+    /// * `SharedFetch`: The shareable fetch future.
+    fn start_shared_fetch(&self, endpoint: &str) -> SharedFetch {
+        let client = Self {
+            config:            self.config.clone(),
+            http_client:       self.http_client.clone(),
+            insecure:          self.insecure,
+            in_flight_fetches: Arc::new(Mutex::new(HashMap::new())),
+            active_api_key:    Arc::clone(&self.active_api_key),
+            bytes_sent:        Arc::clone(&self.bytes_sent),
+            bytes_received:    Arc::clone(&self.bytes_received),
+        };
+        let in_flight_fetches = Arc::clone(&self.in_flight_fetches);
+        let endpoint: String = endpoint.to_string();
+
+        async move {
+            let result = client
+                .request_challenge(&endpoint, None, None)
+                .await
+                .map_err(|e: ErrorHandler| e.to_string());
+
+            in_flight_fetches
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&endpoint);
+
+            result
+        }
+        .boxed()
+        .shared()
+    }
+
+    /// Fetches a challenge like `fetch_challenge`, using a
+    /// caller-supplied request timestamp instead of reading the
+    /// clock, e.g. when the application uses the `time` crate or
+    /// corrects for clock skew. Fetches are never coalesced.
+    ///
+    /// # Arguments
+    /// * `endpoint`:     The protected endpoint URL to access.
+    /// * `timestamp_ms`: The request time as Unix epoch
+    ///                   milliseconds.
+    ///
+    /// # Returns
+    /// * `ResultHandler<IronShieldChallenge>`: The challenge to solve.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use ironshield::client::config::ClientConfig;
+    /// use ironshield::client::request::IronShieldClient;
+    ///
+    /// async fn example(now_ms: i64) -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = IronShieldClient::new(ClientConfig::default())?;
+    /// let challenge = client.fetch_challenge_at("https://example.com/protected", now_ms).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_challenge_at(
+        &self,
+        endpoint:     &str,
+        timestamp_ms: i64,
+    ) -> ResultHandler<IronShieldChallenge> {
+        let endpoint: String = normalize_endpoint(endpoint)?;
+
+        self.request_challenge(&endpoint, Some(timestamp_ms), None).await
+    }
+
+    /// Fetches a challenge as one leg of a validation, with
+    /// retries also drawn from the validation's `budget`.
+    ///
+    /// A limited budget bypasses
+    /// `ClientConfig::coalesce_fetches`, as a shared fetch
+    /// cannot charge its retries to one validation.
+    ///
+    /// # Arguments
+    /// * `endpoint`: The protected endpoint URL to access.
+    /// * `budget`:   Retries left for the validation.
+    ///
+    /// # Returns
+    /// * `ResultHandler<IronShieldChallenge>`: The challenge to solve.
+    pub(crate) async fn fetch_challenge_within(
+        &self,
+        endpoint: &str,
+        budget:   &RetryBudget,
+    ) -> ResultHandler<IronShieldChallenge> {
+        if !budget.is_limited() {
+            return self.fetch_challenge(endpoint).await;
+        }
+
+        let endpoint: String = normalize_endpoint(endpoint)?;
+
+        self.request_challenge(&endpoint, None, Some(budget)).await
+    }
+
+    /// Requests a new challenge for `endpoint` from the API.
+    ///
+    /// Transient failures are retried according to
+    /// `ClientConfig::retry_policy`. With
+    /// `ClientConfig::refetch_on_malformed_challenge` enabled, a
+    /// response body that cannot be parsed is re-fetched once. API
+    /// errors are never retried.
+    ///
+    /// # Arguments
+    /// * `endpoint`:     The protected endpoint URL to access.
+    /// * `timestamp_ms`: The request time, or `None` for now.
+    /// * `budget`:       Retries left for the validation, if
+    ///                   the request is part of one.
+    ///
+    /// # Returns
+    /// * `ResultHandler<IronShieldChallenge>`: The challenge to solve.
+    async fn request_challenge(
+        &self,
+        endpoint:     &str,
+        timestamp_ms: Option<i64>,
+        budget:       Option<&RetryBudget>,
+    ) -> ResultHandler<IronShieldChallenge> {
+        match self.request_challenge_retrying(endpoint, timestamp_ms, budget).await {
+            Err(e) if self.config.refetch_on_malformed_challenge && is_malformed_body(&e) => {
+                if self.config.verbose {
+                    eprintln!("Malformed challenge response ({}), re-fetching once.", e);
+                }
+
+                self.request_challenge_retrying(endpoint, timestamp_ms, budget).await
+            },
+            result => result,
+        }
+    }
+
+    /// Sends challenge requests for `endpoint` until one succeeds,
+    /// fails permanently, or `ClientConfig::retry_policy` or
+    /// `budget` runs out of retries.
+    ///
+    /// # Arguments
+    /// * `endpoint`:     The protected endpoint URL to access.
+    /// * `timestamp_ms`: The request time, or `None` for now.
+    /// * `budget`:       Retries left for the validation, if
+    ///                   the request is part of one.
+    ///
+    /// # Returns
+    /// * `ResultHandler<IronShieldChallenge>`: The challenge to solve.
+    async fn request_challenge_retrying(
+        &self,
+        endpoint:     &str,
+        timestamp_ms: Option<i64>,
+        budget:       Option<&RetryBudget>,
+    ) -> ResultHandler<IronShieldChallenge> {
+        let policy: RetryPolicy = self.config.retry_policy;
+        let mut retry: u32 = 0;
+
+        loop {
+            match self.request_challenge_once(endpoint, timestamp_ms).await {
+                Err(e) if retry < policy.max_retries
+                    && RetryPolicy::is_retryable(&e)
+                    && budget.is_none_or(RetryBudget::try_spend) => {
+                    if self.config.verbose {
+                        eprintln!("Challenge request failed ({}), retry {} of {}.", e, retry + 1, policy.max_retries);
+                    }
+
+                    tokio::time::sleep(policy.backoff(retry)).await;
+                    retry += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends a single challenge request for `endpoint`.
+    ///
+    /// # Arguments
+    /// * `endpoint`:     The protected endpoint URL to access.
+    /// * `timestamp_ms`: The request time, or `None` for now.
+    ///
+    /// # Returns
+    /// * `ResultHandler<IronShieldChallenge>`: The challenge to solve.
+    async fn request_challenge_once(
+        &self,
+        endpoint:     &str,
+        timestamp_ms: Option<i64>,
     ) -> ResultHandler<IronShieldChallenge> {
         let request = IronShieldRequest::new(
             endpoint.to_string(),
-            chrono::Utc::now().timestamp_millis(),
+            timestamp_ms.unwrap_or_else(|| self.config.clock().now_millis()),
         );
 
         let response = self.make_api_request("/request", &request).await?;
         let api_response = ApiResponse::from_json(response)?;
 
-        api_response.extract_challenge()
+        self.check_signature(api_response.extract_challenge()?)
+    }
+
+    /// Rejects a fetched challenge whose signature does not
+    /// verify, when `ClientConfig::verify_challenge_signature`
+    /// is set, so a forged challenge costs no CPU.
+    ///
+    /// # Arguments
+    /// * `challenge`: The fetched challenge.
+    ///
+    /// # Returns
+    /// * `ResultHandler<IronShieldChallenge>`: The challenge, or
+    ///                                         `ErrorHandler::ChallengeVerificationError`
+    ///                                         if its signature does not verify.
+    fn check_signature(&self, challenge: IronShieldChallenge) -> ResultHandler<IronShieldChallenge> {
+        if !self.config.verify_challenge_signature {
+            return Ok(challenge);
+        }
+
+        let public_key: &[u8; 32] = self.config.challenge_public_key.as_ref().ok_or_else(|| {
+            ErrorHandler::config_error("Challenge signature verification requires a public key")
+        })?;
+
+        ironshield_types::verify_challenge_signature_with_key(&challenge, public_key)
+            .map_err(|_| ErrorHandler::challenge_verification_error(SIGNATURE_FAIL.message))?;
+
+        Ok(challenge)
+    }
+
+    /// Fetches a single challenge scoped to several endpoints,
+    /// for APIs that issue one session challenge per page. Submit
+    /// its solution with `submit_solution_multi`. Fetches are never
+    /// coalesced.
+    ///
+    /// # Arguments
+    /// * `endpoints`: The protected endpoint URLs to access.
+    ///
+    /// # Returns
+    /// * `ResultHandler<IronShieldChallenge>`: The challenge to solve.
+    pub async fn fetch_challenge_multi(
+        &self,
+        endpoints: &[&str]
+    ) -> ResultHandler<IronShieldChallenge> {
+        if endpoints.is_empty() {
+            return Err(ErrorHandler::InvalidRequest(
+                "At least one endpoint is required".to_string()
+            ));
+        }
+
+        let endpoints: Vec<String> = endpoints
+            .iter()
+            .map(|endpoint| normalize_endpoint(endpoint))
+            .collect::<ResultHandler<Vec<String>>>()?;
+        let endpoints: Vec<&str> = endpoints.iter().map(String::as_str).collect();
+
+        let request = MultiEndpointRequest {
+            endpoints: &endpoints,
+            timestamp: self.config.clock().now_millis(),
+        };
+
+        let response = self.make_api_request("/request", &request).await?;
+        let api_response = ApiResponse::from_json(response)?;
+
+        self.check_signature(api_response.extract_challenge()?)
+    }
+
+    /// Reads the server's current time from the `Date` header
+    /// of a lightweight `HEAD` request to the API base URL.
+    ///
+    /// The difference from the local clock can be applied to
+    /// request timestamps to correct for local clock skew.
+    /// The `Date` header only has second precision.
+    ///
+    /// # Returns
+    /// * `ResultHandler<i64>`: The server time as Unix epoch
+    ///                         milliseconds.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use ironshield::client::config::ClientConfig;
+    /// use ironshield::client::request::IronShieldClient;
+    ///
+    /// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = IronShieldClient::new(ClientConfig::default())?;
+    /// let server_time = client.server_time().await?;
+    /// let offset = server_time - ironshield_types::chrono::Utc::now().timestamp_millis();
+    /// println!("Local clock is off by {}ms", offset);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn server_time(&self) -> ResultHandler<i64> {
+        self.warn_if_insecure();
+
+        let response = self
+            .http_client
+            .head(&self.config.api_base_url)
+            .send()
+            .await
+            .map_err(ErrorHandler::from_network_error)?;
+
+        let date = response
+            .headers()
+            .get(reqwest::header::DATE)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| ErrorHandler::ProcessingError(
+                "API response has no valid 'Date' header".to_string()
+            ))?;
+
+        parse_http_date(date)
     }
 
+    /// Primes the client before its first real request, so
+    /// `fetch_challenge` does not pay for a cold start.
+    ///
+    /// Sends a `HEAD` request to the API base URL, through any
+    /// configured proxy, leaving an established connection in
+    /// the pool. The response status is ignored. Like other API
+    /// requests, it is subject to `ClientConfig::circuit_breaker`
+    /// and `max_transfer_bytes`.
+    ///
+    /// # Returns
+    /// * `ResultHandler<()>`: An error if the API cannot be
+    ///                        reached.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use ironshield::client::config::ClientConfig;
+    /// use ironshield::client::request::IronShieldClient;
+    ///
+    /// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = IronShieldClient::new(ClientConfig::default())?;
+    /// client.warm().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn warm(&self) -> ResultHandler<()> {
+        let url = reqwest::Url::parse(&self.config.api_base_url).map_err(|e| ErrorHandler::config_error(format!(
+            "Invalid API base URL '{}': {}", self.config.api_base_url, e
+        )))?;
+
+        self.warn_if_insecure();
+        self.check_transfer_limit()?;
+
+        let breaker = self.config.circuit_breaker.as_deref();
+        if breaker.is_some_and(|breaker| !breaker.try_acquire()) {
+            return Err(ErrorHandler::ProcessingError("circuit open".to_string()));
+        }
+
+        let response = self.http_client
+            .head(url)
+            .send()
+            .await
+            .map_err(ErrorHandler::from_network_error);
+
+        if let Some(breaker) = breaker {
+            match &response {
+                Ok(response) if !response.status().is_server_error() => breaker.record_success(),
+                _                                                     => breaker.record_failure(),
+            }
+        }
+
+        response?;
+
+        Ok(())
+    }
+
+    /// Submits a solved challenge for validation.
+    ///
+    /// Known rejections are reported as specific errors: clock
+    /// skew as `ErrorHandler::InvalidRequest`, expiry as
+    /// `ErrorHandler::Challenge`, and a wrong solution or failed
+    /// signature check as `ErrorHandler::InvalidSolution` and
+    /// `ErrorHandler::SignatureVerificationError` respectively.
+    ///
+    /// # Arguments
+    /// * `solution`: The solved challenge.
+    ///
+    /// # Returns
+    /// * `ResultHandler<IronShieldToken>`: The token granting access.
     pub async fn submit_solution(
         &self,
         solution: &IronShieldChallengeResponse,
     ) -> ResultHandler<IronShieldToken> {
-        let response = self.make_api_request("/response", solution).await?;
+        self.submit_response(solution_body(solution, None)?).await
+    }
+
+    /// Submits a solved challenge like `submit_solution`, also
+    /// sending the solve's thread count, hash rate, and elapsed
+    /// time as `client_metrics` when
+    /// `ClientConfig::report_client_metrics` is set. Stats of a
+    /// solution that took no proof of work, from the solve
+    /// cache or a `ClientConfig::solver`, are not sent.
+    ///
+    /// # Arguments
+    /// * `solution`: The solved challenge.
+    /// * `stats`:    Statistics of the solve that produced it.
+    ///
+    /// # Returns
+    /// * `ResultHandler<IronShieldToken>`: The token granting access.
+    pub async fn submit_solution_with_stats(
+        &self,
+        solution: &IronShieldChallengeResponse,
+        stats:    &SolveStats,
+    ) -> ResultHandler<IronShieldToken> {
+        let reported: Option<&SolveStats> = Some(stats)
+            .filter(|stats| self.config.report_client_metrics && stats.thread_count > 0);
+
+        self.submit_response(solution_body(solution, reported)?).await
+    }
+
+    /// Sends a solution body built by `solution_body`.
+    ///
+    /// # Arguments
+    /// * `body`: The request payload.
+    ///
+    /// # Returns
+    /// * `ResultHandler<IronShieldToken>`: The token granting access.
+    async fn submit_response(&self, body: serde_json::Value) -> ResultHandler<IronShieldToken> {
+        if let Some(metrics) = &self.config.metrics {
+            metrics.record_submission();
+        }
+
+        let response = self.make_api_request("/response", &body).await?;
         let api_response = ApiResponse::from_json(response)?;
 
         api_response.extract_token()
     }
 
+    /// Submits the solution to a challenge from
+    /// `fetch_challenge_multi`.
+    ///
+    /// # Arguments
+    /// * `solution`:       The solved multi-endpoint challenge.
+    /// * `endpoint_count`: Number of endpoints passed to
+    ///                     `fetch_challenge_multi`.
+    ///
+    /// # Returns
+    /// * `ResultHandler<Vec<IronShieldToken>>`: One token per endpoint,
+    ///                                          in request order, or an
+    ///                                          error if the API returned
+    ///                                          a different number.
+    pub async fn submit_solution_multi(
+        &self,
+        solution:       &IronShieldChallengeResponse,
+        endpoint_count: usize,
+    ) -> ResultHandler<Vec<IronShieldToken>> {
+        if let Some(metrics) = &self.config.metrics {
+            metrics.record_submission();
+        }
+
+        let response = self.make_api_request("/response", solution).await?;
+        let api_response = ApiResponse::from_json(response)?;
+
+        api_response.extract_tokens(endpoint_count)
+    }
+
     /// Makes a standardized API request to the IronShield API service.
     ///
+    /// The `Accept` header is taken from `ClientConfig::accept_header`.
+    /// While `ClientConfig::circuit_breaker` is open, requests fail
+    /// immediately without a network call.
+    /// Bodies of at least `ClientConfig::compression_threshold` bytes
+    /// are gzip-compressed. When `ClientConfig::signing_key` is set the
+    /// body as sent is signed and the signature and timestamp headers
+    /// are attached. Responses are read subject to
+    /// `ClientConfig::max_response_size` and `max_response_depth`.
+    /// Failures are counted in `ClientConfig::metrics`.
+    /// If the API rejects the active `ClientConfig::api_keys` entry
+    /// with a `401`, the request is retried once with the next key,
+    /// which becomes the active key if it is accepted.
+    ///
     /// # Arguments
     /// * `path`: The API endpoint path (e.g., "/request" or "/response").
     /// * `body`: The request payload to send to the API.
@@ -122,24 +848,733 @@ impl IronShieldClient {
         path: &str,
         body: &T,
     ) -> ResultHandler<serde_json::Value> {
-        let response = self
+        let keys: &[String] = &self.config.api_keys;
+        let active: usize = self.active_api_key.load(Ordering::Relaxed);
+
+        let mut result = self.send_api_request(path, body, keys.get(active).map(String::as_str)).await;
+
+        if keys.len() > 1 && matches!(result, Err(ErrorHandler::AuthenticationError(_))) {
+            let next: usize = (active + 1) % keys.len();
+
+            if self.config.verbose {
+                eprintln!("API key {} was rejected, retrying with key {}", active, next);
+            }
+
+            result = self.send_api_request(path, body, Some(&keys[next])).await;
+
+            if result.is_ok() {
+                let _ = self.active_api_key.compare_exchange(active, next, Ordering::Relaxed, Ordering::Relaxed);
+            }
+        }
+
+        if let (Err(e), Some(metrics)) = (&result, &self.config.metrics) {
+            metrics.record_error(e);
+        }
+
+        if let (Err(e), Some(log)) = (&result, &self.config.error_log) {
+            log.record(self.config.clock().now_millis(), Some(format!("{}{}", self.config.api_base_url, path)), e);
+        }
+
+        result
+    }
+
+    /// Sends an API request for `make_api_request`.
+    ///
+    /// # Arguments
+    /// * `path`:    The API endpoint path.
+    /// * `body`:    The request payload to send to the API.
+    /// * `api_key`: Bearer token to authenticate with, if any.
+    ///
+    /// # Returns
+    /// * `ResultHandler<serde_json::Value>`: The parsed JSON response,
+    ///                                       or `ErrorHandler::AuthenticationError`
+    ///                                       on a `401`.
+    async fn send_api_request<T: serde::Serialize>(
+        &self,
+        path:    &str,
+        body:    &T,
+        api_key: Option<&str>,
+    ) -> ResultHandler<serde_json::Value> {
+        self.warn_if_insecure();
+        self.check_transfer_limit()?;
+
+        let mut body: Vec<u8> = serde_json::to_vec(body)?;
+        let fingerprint_timestamp: Option<i64> = self.config.request_fingerprinting.then(|| {
+            body_timestamp(&body).unwrap_or_else(|| self.config.clock().now_millis())
+        });
+
+        let mut request = self
             .http_client
             .post(&format!("{}{}", self.config.api_base_url, path))
             .header("Content-Type", "application/json")
-            .json(body)
+            .header("Accept", self.config.accept_header.as_deref().unwrap_or(DEFAULT_ACCEPT))
+            .header(CLIENT_VERSION_HEADER, client_version_string());
+
+        if let Some(key) = api_key {
+            request = request.bearer_auth(key);
+        }
+
+        if let Some(threshold) = self.config.compression_threshold {
+            if body.len() >= threshold {
+                body = Self::compress_body(&body)?;
+                request = request.header("Content-Encoding", "gzip");
+            }
+        }
+
+        if let Some(key) = &self.config.signing_key {
+            request = Self::sign_request(request, key, &body)?;
+        }
+
+        if let Some(timestamp) = fingerprint_timestamp {
+            request = Self::fingerprint_request(request, path, timestamp, &body)?;
+        }
+
+        let breaker = self.config.circuit_breaker.as_deref();
+        if breaker.is_some_and(|breaker| !breaker.try_acquire()) {
+            return Err(ErrorHandler::ProcessingError("circuit open".to_string()));
+        }
+
+        self.bytes_sent.fetch_add(body.len() as u64, Ordering::Relaxed);
+
+        let response = request
+            .body(body)
             .send()
             .await
-            .map_err(ErrorHandler::from_network_error)?;
+            .map_err(ErrorHandler::from_network_error);
 
-        if !response.status().is_success() {
-            return Err(ErrorHandler::ProcessingError(format!(
+        if let Some(breaker) = breaker {
+            match &response {
+                Ok(response) if !response.status().is_server_error() => breaker.record_success(),
+                _                                                     => breaker.record_failure(),
+            }
+        }
+
+        let response = response?;
+
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(ErrorHandler::authentication_error(format!(
                 "API request failed with status: {}",
                 response.status()
             )))
         }
 
-        let json_response = response.json().await.map_err(ErrorHandler::from_network_error)?;
+        if !response.status().is_success() {
+            let status: reqwest::StatusCode = response.status();
+            let body: Vec<u8> = self.read_limited_body(response).await.unwrap_or_default();
+
+            return Err(rejection_error(status.as_u16(), &body).unwrap_or_else(|| {
+                ErrorHandler::ProcessingError(format!("API request failed with status: {}", status))
+            }))
+        }
+
+        let body: Vec<u8> = self.read_limited_body(response).await?;
+
+        if let Some(max_depth) = self.config.max_response_depth {
+            check_json_depth(&body, max_depth)?;
+        }
+
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// # Returns
+    /// * `ResultHandler<()>`: A `ProcessingError` once the bytes
+    ///                        sent and received reach
+    ///                        `ClientConfig::max_transfer_bytes`.
+    fn check_transfer_limit(&self) -> ResultHandler<()> {
+        let Some(max_bytes) = self.config.max_transfer_bytes else {
+            return Ok(());
+        };
+        let transferred: u64 = self.bytes_sent().saturating_add(self.bytes_received());
+
+        if transferred >= max_bytes {
+            return Err(ErrorHandler::ProcessingError(format!(
+                "Transfer limit of {} bytes reached ({} bytes sent and received)",
+                max_bytes, transferred
+            )));
+        }
 
-        Ok(json_response)
+        Ok(())
     }
-}
\ No newline at end of file
+
+    /// Reads a response body, failing as soon as it exceeds
+    /// `ClientConfig::max_response_size`.
+    ///
+    /// # Arguments
+    /// * `response`: The API response.
+    ///
+    /// # Returns
+    /// * `ResultHandler<Vec<u8>>`: The complete body.
+    async fn read_limited_body(&self, mut response: reqwest::Response) -> ResultHandler<Vec<u8>> {
+        let max_size: usize = self.config.max_response_size.unwrap_or(usize::MAX);
+        let too_large = || ErrorHandler::ProcessingError(format!(
+            "API response exceeds the maximum size of {} bytes", max_size
+        ));
+
+        if response.content_length().is_some_and(|length| length > max_size as u64) {
+            return Err(too_large());
+        }
+
+        let mut body: Vec<u8> = Vec::new();
+
+        while let Some(chunk) = response.chunk().await.map_err(ErrorHandler::from_network_error)? {
+            self.bytes_received.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+
+            if body.len() + chunk.len() > max_size {
+                return Err(too_large());
+            }
+
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(body)
+    }
+
+    /// # Arguments
+    /// * `body`: The serialized request body.
+    ///
+    /// # Returns
+    /// * `ResultHandler<Vec<u8>>`: The gzip-compressed body.
+    #[cfg(feature = "gzip")]
+    fn compress_body(body: &[u8]) -> ResultHandler<Vec<u8>> {
+        compression::gzip(body)
+    }
+
+    /// Request compression is unavailable without the `gzip` feature,
+    /// so a configured threshold is rejected rather than silently ignored.
+    #[cfg(not(feature = "gzip"))]
+    fn compress_body(_body: &[u8]) -> ResultHandler<Vec<u8>> {
+        Err(ErrorHandler::config_error(
+            "Request compression requires the `gzip` feature"
+        ))
+    }
+
+    /// Attaches the HMAC signature and timestamp headers to a request.
+    ///
+    /// # Arguments
+    /// * `request`: The request being built.
+    /// * `key`:     The configured signing key.
+    /// * `body`:    The exact serialized body that will be sent.
+    ///
+    /// # Returns
+    /// * `ResultHandler<RequestBuilder>`: The request with the
+    ///                                    signature headers set.
+    #[cfg(feature = "hmac")]
+    fn sign_request(
+        request: RequestBuilder,
+        key:     &[u8],
+        body:    &[u8],
+    ) -> ResultHandler<RequestBuilder> {
+        let timestamp: i64 = chrono::Utc::now().timestamp_millis();
+        let signature: String = signing::sign_request(key, timestamp, body)?;
+
+        Ok(request
+            .header(SIGNATURE_HEADER, signature)
+            .header(TIMESTAMP_HEADER, timestamp))
+    }
+
+    /// Attaches the fingerprint and its timestamp headers to a
+    /// request.
+    ///
+    /// # Arguments
+    /// * `request`:   The request being built.
+    /// * `path`:      The API path of the request.
+    /// * `timestamp`: The timestamp to fingerprint.
+    /// * `body`:      The exact serialized body that will be sent.
+    ///
+    /// # Returns
+    /// * `ResultHandler<RequestBuilder>`: The request with the
+    ///                                    fingerprint headers set.
+    #[cfg(feature = "hmac")]
+    fn fingerprint_request(
+        request:   RequestBuilder,
+        path:      &str,
+        timestamp: i64,
+        body:      &[u8],
+    ) -> ResultHandler<RequestBuilder> {
+        let fingerprint: String = signing::request_fingerprint("POST", path, timestamp, body);
+
+        Ok(request
+            .header(FINGERPRINT_HEADER, fingerprint)
+            .header(FINGERPRINT_TIMESTAMP_HEADER, timestamp))
+    }
+
+    /// Fingerprinting is unavailable without the `hmac` feature,
+    /// so enabling it is rejected rather than silently ignored.
+    #[cfg(not(feature = "hmac"))]
+    fn fingerprint_request(
+        _request:   RequestBuilder,
+        _path:      &str,
+        _timestamp: i64,
+        _body:      &[u8],
+    ) -> ResultHandler<RequestBuilder> {
+        Err(ErrorHandler::config_error(
+            "Request fingerprinting requires the `hmac` feature"
+        ))
+    }
+
+    /// Request signing is unavailable without the `hmac` feature,
+    /// so a configured key is rejected rather than silently ignored.
+    #[cfg(not(feature = "hmac"))]
+    fn sign_request(
+        _request: RequestBuilder,
+        _key:     &[u8],
+        _body:    &[u8],
+    ) -> ResultHandler<RequestBuilder> {
+        Err(ErrorHandler::config_error(
+            "Request signing requires the `hmac` feature"
+        ))
+    }
+}
+
+/// # Arguments
+/// * `solution`: The solved challenge.
+/// * `stats`:    Statistics to send as `client_metrics`, if any.
+///
+/// # Returns
+/// * `ResultHandler<serde_json::Value>`: The body of a solution
+///                                       submission.
+fn solution_body(
+    solution: &IronShieldChallengeResponse,
+    stats:    Option<&SolveStats>,
+) -> ResultHandler<serde_json::Value> {
+    let mut body: serde_json::Value = serde_json::to_value(solution)?;
+
+    if let (Some(stats), Some(fields)) = (stats, body.as_object_mut()) {
+        fields.insert("client_metrics".to_string(), serde_json::to_value(stats.client_metrics())?);
+    }
+
+    Ok(body)
+}
+
+/// # Arguments
+/// * `body`: A serialized JSON request body.
+///
+/// # Returns
+/// * `Option<i64>`: Its top-level `timestamp`, as sent in
+///                  challenge requests, if any.
+fn body_timestamp(body: &[u8]) -> Option<i64> {
+    serde_json::from_slice::<serde_json::Value>(body)
+        .ok()?
+        .get("timestamp")?
+        .as_i64()
+}
+
+/// # Arguments
+/// * `error`: The error from a challenge request.
+///
+/// # Returns
+/// * `bool`: `true` if the response body could not be parsed,
+///           as opposed to the API reporting an error.
+fn is_malformed_body(error: &ErrorHandler) -> bool {
+    match error {
+        ErrorHandler::SerializationError(_)  => true,
+        ErrorHandler::Deserialization { .. } => true,
+        ErrorHandler::NetworkError(e)        => e.is_decode(),
+        _                                    => false,
+    }
+}
+
+/// Checks that a configured `Accept` header is a non-empty,
+/// valid header value of the form `type/subtype`.
+///
+/// # Arguments
+/// * `accept`: The configured media type.
+///
+/// # Returns
+/// * `ResultHandler<()>`: An error if the value cannot be sent.
+fn validate_accept_header(accept: &str) -> ResultHandler<()> {
+    let valid: bool = accept.contains('/')
+        && reqwest::header::HeaderValue::from_str(accept).is_ok();
+
+    if !valid {
+        return Err(ErrorHandler::config_error(format!(
+            "Invalid Accept header '{}'", accept
+        )));
+    }
+
+    Ok(())
+}
+
+/// Canonicalizes a protected endpoint URL the way it is
+/// sent to the API.
+///
+/// The endpoint must be an absolute `http://` or `https://`
+/// URL with a host. The scheme and host are lowercased,
+/// default ports (`:80` for HTTP, `:443` for HTTPS) are
+/// removed, and a single trailing slash is trimmed from the
+/// path unless a query or fragment follows it, so
+/// `HTTPS://Example.com:443/protected/` becomes
+/// `https://example.com/protected`.
+///
+/// # Arguments
+/// * `endpoint`: The endpoint as given by the caller.
+///
+/// # Returns
+/// * `ResultHandler<String>`: The canonical endpoint, or
+///                            `ErrorHandler::InvalidRequest`
+///                            if it is not a valid absolute
+///                            URL.
+///
+/// # Example
+/// ```
+/// use ironshield::client::request::normalize_endpoint;
+///
+/// assert_eq!(normalize_endpoint("https://Example.com:443/a/")?, "https://example.com/a");
+/// assert!(normalize_endpoint("example.com/a").is_err());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn normalize_endpoint(endpoint: &str) -> ResultHandler<String> {
+    let url = reqwest::Url::parse(endpoint.trim()).map_err(|e| ErrorHandler::InvalidRequest(format!(
+        "Endpoint '{}' is not an absolute URL ({}); include the scheme, e.g. https://{}",
+        endpoint, e, endpoint.trim()
+    )))?;
+
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none_or(str::is_empty) {
+        return Err(ErrorHandler::InvalidRequest(format!(
+            "Endpoint '{}' must be an http:// or https:// URL with a host",
+            endpoint
+        )));
+    }
+
+    let mut normalized: String = url.to_string();
+
+    if url.query().is_none() && url.fragment().is_none() && normalized.ends_with('/') {
+        normalized.pop();
+    }
+
+    Ok(normalized)
+}
+
+/// Rejects JSON nested deeper than `max_depth` arrays and
+/// objects without deserializing it.
+///
+/// # Arguments
+/// * `json`:      The raw JSON document.
+/// * `max_depth`: Deepest nesting allowed.
+///
+/// # Returns
+/// * `ResultHandler<()>`: An error if the limit is exceeded.
+fn check_json_depth(json: &[u8], max_depth: usize) -> ResultHandler<()> {
+    let mut depth: usize = 0;
+    let mut in_string: bool = false;
+    let mut escaped: bool = false;
+
+    for byte in json {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\'       => escaped = true,
+                b'"'        => in_string = false,
+                _           => {},
+            }
+            continue;
+        }
+
+        match byte {
+            b'"'        => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > max_depth {
+                    return Err(ErrorHandler::ProcessingError(format!(
+                        "API response is nested deeper than {} levels", max_depth
+                    )));
+                }
+            },
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _           => {},
+        }
+    }
+
+    Ok(())
+}
+
+/// Classifies a rejected API request from its error body,
+/// e.g. `{"error": "Challenge has expired", "success": false}`.
+///
+/// # Arguments
+/// * `status`: The HTTP status code.
+/// * `body`:   The raw response body.
+///
+/// # Returns
+/// * `Option<ErrorHandler>`: See `ErrorHandler::from_rejection`.
+fn rejection_error(status: u16, body: &[u8]) -> Option<ErrorHandler> {
+    let value: Option<serde_json::Value> = serde_json::from_slice(body).ok();
+    let message: &str = value
+        .as_ref()
+        .and_then(|value| value.get("error").or_else(|| value.get("message")))
+        .and_then(|message| message.as_str())
+        .unwrap_or_default();
+
+    ErrorHandler::from_rejection(status, message)
+}
+
+/// Parses an HTTP `Date` header value.
+///
+/// # Arguments
+/// * `date`: The header value, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+///
+/// # Returns
+/// * `ResultHandler<i64>`: The date as Unix epoch milliseconds.
+fn parse_http_date(date: &str) -> ResultHandler<i64> {
+    chrono::DateTime::parse_from_rfc2822(date)
+        .map(|date| date.timestamp_millis())
+        .map_err(|e| ErrorHandler::ProcessingError(format!(
+            "Invalid 'Date' header '{}': {}", date, e
+        )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_transfer_limit() {
+        let mut config = ClientConfig::default();
+        assert!(IronShieldClient::new(config.clone()).unwrap().check_transfer_limit().is_ok());
+
+        config.max_transfer_bytes = Some(0);
+        let client = IronShieldClient::new(config).unwrap();
+        assert!(client.check_transfer_limit().is_err());
+        assert_eq!(client.bytes_sent() + client.bytes_received(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_sends_configured_user_agent() {
+        use axum::{
+            http::HeaderMap,
+            routing::post,
+            Router
+        };
+
+        let agents = Arc::new(Mutex::new(Vec::new()));
+        let agents_clone = Arc::clone(&agents);
+        let app = Router::new().route("/request", post(move |headers: HeaderMap| async move {
+            let agent = headers
+                .get(http::header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            agents_clone.lock().unwrap().push(agent);
+            "{}"
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let config = ClientConfig {
+            api_base_url:   format!("http://{}", addr),
+            user_agent:     "custom-agent/1.0".to_string(),
+            allow_insecure: true,
+            ..ClientConfig::default()
+        };
+        let client = IronShieldClient::new_insecure(config).unwrap();
+
+        // The empty response is not a challenge, but the request was sent.
+        assert!(client.fetch_challenge("https://example.com/protected").await.is_err());
+        let agents = agents.lock().unwrap();
+        assert!(!agents.is_empty());
+        assert!(agents.iter().all(|agent| agent == "custom-agent/1.0"));
+    }
+
+    /// Serves `/request`, accepting only the bearer token `new`.
+    ///
+    /// # Returns
+    /// * `(String, Arc<AtomicUsize>)`: The server's base URL and
+    ///                                 the number of requests it
+    ///                                 has received.
+    async fn key_rotation_server() -> (String, Arc<AtomicUsize>) {
+        use axum::{
+            http::{HeaderMap, StatusCode},
+            routing::post,
+            Router
+        };
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let hits_clone = Arc::clone(&hits);
+        let app = Router::new().route("/request", post(move |headers: HeaderMap| async move {
+            hits_clone.fetch_add(1, Ordering::Relaxed);
+
+            match headers.get(http::header::AUTHORIZATION).and_then(|value| value.to_str().ok()) {
+                Some("Bearer new") => (StatusCode::OK, "{}"),
+                _                  => (StatusCode::UNAUTHORIZED, ""),
+            }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        (format!("http://{}", addr), hits)
+    }
+
+    #[tokio::test]
+    async fn test_api_key_fallback_promotes_working_key() {
+        let (base_url, hits) = key_rotation_server().await;
+        let config = ClientConfig {
+            api_base_url:   base_url,
+            api_keys:       vec!["old".to_string(), "new".to_string()],
+            allow_insecure: true,
+            ..ClientConfig::default()
+        };
+        let client = IronShieldClient::new_insecure(config).unwrap();
+
+        // The rejected key is retried once with the next key.
+        assert!(client.make_api_request("/request", &serde_json::json!({})).await.is_ok());
+        assert_eq!(hits.load(Ordering::Relaxed), 2);
+        assert_eq!(client.active_api_key.load(Ordering::Relaxed), 1);
+
+        // The working key is used first from then on.
+        assert!(client.make_api_request("/request", &serde_json::json!({})).await.is_ok());
+        assert_eq!(hits.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_coalesced_fetch_promotes_api_key() {
+        let (base_url, _) = key_rotation_server().await;
+        let config = ClientConfig {
+            api_base_url:     base_url,
+            api_keys:         vec!["old".to_string(), "new".to_string()],
+            allow_insecure:   true,
+            coalesce_fetches: true,
+            ..ClientConfig::default()
+        };
+        let client = IronShieldClient::new_insecure(config).unwrap();
+
+        // The empty response is not a challenge, but the key was accepted.
+        assert!(client.fetch_challenge("https://example.com/protected").await.is_err());
+        assert_eq!(client.active_api_key.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn test_solution_body_reports_client_metrics() {
+        let challenge = crate::client::challenge::generate_challenge(0, "https://example.com");
+        let solution = crate::client::solve::solve_blocking(
+            challenge,
+            crate::client::solve::SolveConfig::new(&ClientConfig::default(), false),
+        ).unwrap();
+
+        let plain = solution_body(&solution, None).unwrap();
+        assert_eq!(plain, serde_json::to_value(&solution).unwrap());
+
+        let stats = SolveStats::new(4, 10_000, 500, Vec::new());
+        let reported = solution_body(&solution, Some(&stats)).unwrap();
+        assert_eq!(reported["client_metrics"]["thread_count"], 4);
+        assert_eq!(reported["client_metrics"]["hash_rate"], stats.hash_rate);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn test_cached_solutions_send_no_client_metrics() {
+        use axum::{
+            routing::post,
+            Json,
+            Router
+        };
+
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let bodies_clone = Arc::clone(&bodies);
+        let app = Router::new().route("/response", post(move |Json(body): Json<serde_json::Value>| async move {
+            bodies_clone.lock().unwrap().push(body);
+            Json(serde_json::json!({}))
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let config = ClientConfig {
+            api_base_url:          format!("http://{}", addr),
+            allow_insecure:        true,
+            report_client_metrics: true,
+            ..ClientConfig::default()
+        };
+        let client = IronShieldClient::new_insecure(config).unwrap();
+        let challenge = crate::client::challenge::generate_challenge(0, "https://example.com");
+        let solution = crate::client::solve::solve_blocking(
+            challenge,
+            crate::client::solve::SolveConfig::new(&ClientConfig::default(), false),
+        ).unwrap();
+
+        // The empty response carries no token; only the request matters.
+        let _ = client.submit_solution_with_stats(&solution, &SolveStats::new(0, 0, 0, Vec::new())).await;
+        assert!(bodies.lock().unwrap()[0].get("client_metrics").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_warm_respects_circuit_breaker() {
+        use axum::{
+            routing::get,
+            Router
+        };
+
+        let app = Router::new().route("/", get(|| async { "" }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let breaker = Arc::new(crate::client::circuit::CircuitBreaker::new(1, std::time::Duration::from_secs(60)));
+        let config = ClientConfig {
+            api_base_url:    format!("http://{}", addr),
+            allow_insecure:  true,
+            circuit_breaker: Some(Arc::clone(&breaker)),
+            ..ClientConfig::default()
+        };
+        let client = IronShieldClient::new_insecure(config).unwrap();
+        assert!(client.warm().await.is_ok());
+
+        // An open circuit fails without a network call.
+        breaker.record_failure();
+        assert!(client.warm().await.is_err());
+    }
+
+    #[test]
+    fn test_is_malformed_body() {
+        let parse_error = serde_json::from_str::<serde_json::Value>("{\"chall").unwrap_err();
+        assert!(is_malformed_body(&ErrorHandler::SerializationError(parse_error)));
+        assert!(!is_malformed_body(&ErrorHandler::ProcessingError("Challenge expired".to_string())));
+    }
+
+    #[test]
+    fn test_validate_accept_header() {
+        assert!(validate_accept_header("application/vnd.ironshield.v2+json").is_ok());
+        assert!(validate_accept_header("").is_err());
+        assert!(validate_accept_header("json").is_err());
+        assert!(validate_accept_header("application/json\r\nX-Injected: 1").is_err());
+    }
+
+    #[test]
+    fn test_normalize_endpoint() {
+        assert_eq!(normalize_endpoint("HTTPS://Example.COM:443/Protected/").unwrap(), "https://example.com/Protected");
+        assert_eq!(normalize_endpoint("http://example.com:80").unwrap(), "http://example.com");
+        assert_eq!(normalize_endpoint("https://example.com:8443/a?b=c").unwrap(), "https://example.com:8443/a?b=c");
+        assert!(normalize_endpoint("example.com/protected").is_err());
+        assert!(normalize_endpoint("ftp://example.com/file").is_err());
+    }
+
+    #[test]
+    fn test_check_json_depth() {
+        assert!(check_json_depth(br#"{"a": [1, {"b": 2}]}"#, 3).is_ok());
+        assert!(check_json_depth(br#"{"a": [1, {"b": 2}]}"#, 2).is_err());
+        assert!(check_json_depth(br#"{"a": "[[[[\"{{{{"}"#, 1).is_ok());
+    }
+
+    #[test]
+    fn test_body_timestamp() {
+        assert_eq!(body_timestamp(br#"{"endpoint":"https://example.com","timestamp":1700000000000}"#), Some(1_700_000_000_000));
+        assert_eq!(body_timestamp(b"{}"), None);
+        assert_eq!(body_timestamp(b"not json"), None);
+    }
+
+    #[test]
+    fn test_rejection_error() {
+        let body = br#"{"error": "Invalid solution provided for the challenge", "success": false}"#;
+        assert!(matches!(rejection_error(422, body), Some(ErrorHandler::InvalidSolution(_))));
+        assert!(matches!(rejection_error(410, b"<html>"), Some(ErrorHandler::Challenge(_))));
+        assert!(rejection_error(500, b"{}").is_none());
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap(), 784_111_777_000);
+        assert!(parse_http_date("yesterday").is_err());
+    }
+}
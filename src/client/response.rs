@@ -6,6 +6,7 @@ use ironshield_types::{
 use crate::handler::error::ErrorHandler;
 use crate::handler::result::ResultHandler;
 
+use serde::de::DeserializeOwned;
 use serde_json::Value;
 
 /// Represents a structured IronShield API response.
@@ -25,11 +26,19 @@ pub struct ApiResponse {
 impl ApiResponse {
     /// Parses a raw JSON response into a structured `ApiResponse`.
     ///
+    /// The response must be an object with a `status` that is
+    /// a number or a numeric string in the `u16` range. A
+    /// missing `message` becomes `"No message"` and a `null`
+    /// one an empty string; any other non-string `message` is
+    /// rejected.
+    ///
     /// # Arguments
     /// * `response`: The raw JSON value from the API response.
     ///
     /// # Returns
-    /// * `ResultHandler<Self>`: Parsed response or an error.
+    /// * `ResultHandler<Self>`: Parsed response, or
+    ///                          `ErrorHandler::ProcessingError`
+    ///                          naming the invalid field.
     ///
     /// # Example
     /// ```ignore
@@ -41,14 +50,22 @@ impl ApiResponse {
     /// let api_response = ApiResponse::from_json(json_response)?;
     /// ```
     pub fn from_json(response: Value) -> ResultHandler<Self> {
-        let status = response.get("status")
-            .and_then(|s: &Value| s.as_u64())
-            .unwrap_or(0) as u16;
+        if !response.is_object() {
+            return Err(invalid_response("expected a JSON object"));
+        }
+
+        let status: u16 = match response.get("status") {
+            None              => return Err(invalid_response("missing 'status' field")),
+            Some(Value::Null) => return Err(invalid_response("'status' is null")),
+            Some(status)      => parse_status(status)?,
+        };
 
-        let message = response.get("message")
-            .and_then(|m: &Value| m.as_str())
-            .unwrap_or("No message")
-            .to_string();
+        let message: String = match response.get("message") {
+            None                     => "No message".to_string(),
+            Some(Value::Null)        => String::new(),
+            Some(Value::String(msg)) => msg.clone(),
+            Some(_)                  => return Err(invalid_response("'message' is not a string")),
+        };
 
         Ok(Self {
             status,
@@ -83,7 +100,7 @@ impl ApiResponse {
             ErrorHandler::ProcessingError("No 'challenge' field in API response".to_string())
         })?;
 
-        serde_json::from_value(challenge_data.clone()).map_err(ErrorHandler::from)
+        deserialize_field(challenge_data, "challenge")
     }
 
     /// Extracts the `IronShieldToken` from the API response data.
@@ -101,6 +118,143 @@ impl ApiResponse {
             ErrorHandler::ProcessingError("No 'token' field in API response".to_string())
         })?;
 
-        serde_json::from_value(token_data.clone()).map_err(ErrorHandler::from)
+        deserialize_field(token_data, "token")
+    }
+
+    /// Extracts one `IronShieldToken` per endpoint from the
+    /// response to a multi-endpoint challenge submission.
+    ///
+    /// # Arguments
+    /// * `endpoint_count`: Number of endpoints the challenge
+    ///                     was requested for.
+    ///
+    /// # Returns
+    /// * `ResultHandler<Vec<IronShieldToken>>`: The tokens in the order
+    ///                                          the endpoints were
+    ///                                          requested, or an error
+    ///                                          if there is not exactly
+    ///                                          one per endpoint.
+    pub fn extract_tokens(&self, endpoint_count: usize) -> ResultHandler<Vec<IronShieldToken>> {
+        if !self.is_success() {
+            return Err(ErrorHandler::ProcessingError(self.message.clone()));
+        }
+
+        let tokens_data = self.data.get("tokens").ok_or_else(|| {
+            ErrorHandler::ProcessingError("No 'tokens' field in API response".to_string())
+        })?;
+
+        let tokens: Vec<IronShieldToken> = deserialize_field(tokens_data, "tokens")?;
+
+        // Tokens are paired with endpoints by position, so a short list would mis-pair them.
+        if tokens.len() != endpoint_count {
+            return Err(ErrorHandler::ProcessingError(format!(
+                "Expected {} tokens, one per endpoint, but the API returned {}",
+                endpoint_count, tokens.len()
+            )));
+        }
+
+        Ok(tokens)
+    }
+}
+
+/// Reads a response's `status`, accepting a number or a
+/// numeric string such as `"200"`.
+///
+/// # Arguments
+/// * `status`: The `status` field's JSON value.
+///
+/// # Returns
+/// * `ResultHandler<u16>`: The status code, or an error if
+///                         it is not a whole number in the
+///                         `u16` range.
+fn parse_status(status: &Value) -> ResultHandler<u16> {
+    let code: Option<u64> = match status {
+        Value::Number(number) => number.as_u64(),
+        Value::String(text)   => text.trim().parse::<u64>().ok(),
+        _                     => None,
+    };
+
+    code.and_then(|code| u16::try_from(code).ok())
+        .ok_or_else(|| invalid_response(&format!("invalid 'status' value {}", status)))
+}
+
+/// # Arguments
+/// * `reason`: What is wrong with the response.
+///
+/// # Returns
+/// * `ErrorHandler`: A `ProcessingError` describing the
+///                   malformed response.
+fn invalid_response(reason: &str) -> ErrorHandler {
+    ErrorHandler::ProcessingError(format!("Invalid API response: {}", reason))
+}
+
+/// Deserializes one field of an API response, naming the
+/// field and the keys it contained on failure.
+///
+/// # Arguments
+/// * `value`: The field's JSON value.
+/// * `field`: The field name, e.g. `"challenge"`.
+///
+/// # Returns
+/// * `ResultHandler<T>`: The parsed value or an
+///                       `ErrorHandler::Deserialization`.
+fn deserialize_field<T: DeserializeOwned>(value: &Value, field: &str) -> ResultHandler<T> {
+    serde_json::from_value(value.clone()).map_err(|e| ErrorHandler::deserialization_error(
+        format!("{} from API response", field),
+        value,
+        e,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_json_status() {
+        let response = ApiResponse::from_json(serde_json::json!({ "status": "404" })).unwrap();
+        assert_eq!(response.status, 404);
+        assert_eq!(response.message, "No message");
+
+        let response = ApiResponse::from_json(serde_json::json!({ "status": 200, "message": null })).unwrap();
+        assert_eq!(response.message, "");
+
+        for invalid in [
+            serde_json::json!({}),
+            serde_json::json!({ "status": null }),
+            serde_json::json!({ "status": 70000 }),
+            serde_json::json!({ "status": -1 }),
+            serde_json::json!({ "status": 200.5 }),
+            serde_json::json!({ "status": "ok" }),
+            serde_json::json!({ "status": 200, "message": 5 }),
+            serde_json::json!([200]),
+        ] {
+            assert!(ApiResponse::from_json(invalid).is_err());
+        }
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_extract_tokens() {
+        let response = ApiResponse::from_json(serde_json::json!({
+            "status": 200,
+            "tokens": []
+        })).unwrap();
+        assert!(response.extract_tokens(0).unwrap().is_empty());
+        assert!(response.extract_tokens(2).is_err());
+
+        let response = ApiResponse::from_json(serde_json::json!({ "status": 200 })).unwrap();
+        assert!(response.extract_tokens(0).is_err());
+    }
+
+    #[test]
+    fn test_extract_challenge_error_names_field() {
+        let response = ApiResponse::from_json(serde_json::json!({
+            "status": 200,
+            "challenge": { "random_nonce": "abc" }
+        })).unwrap();
+
+        let message = response.extract_challenge().unwrap_err().to_string();
+        assert!(message.starts_with("Failed to deserialize challenge from API response: missing field"));
+        assert!(message.ends_with("(fields present: [random_nonce])"));
+    }
+}
@@ -0,0 +1,283 @@
+//! # Retry policy for transient API failures.
+
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::handler::error::{
+    ErrorHandler,
+    CHALLENGE_EXPIRED_MSG
+};
+
+use std::sync::atomic::{
+    AtomicU32,
+    Ordering
+};
+use std::time::Duration;
+
+/// How often and how patiently failed API requests are
+/// retried.
+///
+/// Only transient failures are retried, i.e. connection
+/// failures and timeouts. API rejections never are. The
+/// delay before retry `n` (counting from `0`) is
+/// `initial_backoff * 2^n`, capped at `max_backoff`.
+///
+/// * `max_retries`:           Retries after the first attempt.
+/// * `initial_backoff`:       Delay before the first retry.
+/// * `max_backoff`:           Longest delay between retries.
+/// * `per_validation_budget`: Retries allowed across every
+///                            leg of one `validate_challenge`
+///                            call, on top of the per-request
+///                            `max_retries` and
+///                            `SubmitRetryPolicy::max_attempts`.
+///                            `None` leaves only the
+///                            per-request limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    pub max_retries:     u32,
+    #[serde(with = "millis_serde")]
+    pub initial_backoff: Duration,
+    #[serde(with = "millis_serde")]
+    pub max_backoff:     Duration,
+    #[serde(default)]
+    pub per_validation_budget: Option<u32>,
+}
+
+impl Default for RetryPolicy {
+    /// No retries.
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl RetryPolicy {
+    /// # Returns
+    /// * `Self`: A policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_retries:     0,
+            initial_backoff: Duration::ZERO,
+            max_backoff:     Duration::ZERO,
+            per_validation_budget: None,
+        }
+    }
+
+    /// # Arguments
+    /// * `retry`: Zero-based index of the retry.
+    ///
+    /// # Returns
+    /// * `Duration`: How long to wait before that retry.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor: u32 = 1u32.checked_shl(retry).unwrap_or(u32::MAX);
+
+        std::cmp::min(self.initial_backoff.saturating_mul(factor), self.max_backoff)
+    }
+
+    /// # Arguments
+    /// * `error`: The failure of an attempt.
+    ///
+    /// # Returns
+    /// * `bool`: Whether the failure is transient and worth
+    ///           retrying.
+    pub fn is_retryable(error: &ErrorHandler) -> bool {
+        match error {
+            ErrorHandler::NetworkError(e)     => e.is_connect() || e.is_timeout(),
+            ErrorHandler::TimeoutError { .. } => true,
+            _                                 => false,
+        }
+    }
+}
+
+/// Retries left for one validation, shared by the fetch
+/// and submit legs, from `RetryPolicy::per_validation_budget`.
+#[derive(Debug)]
+pub(crate) struct RetryBudget {
+    remaining: Option<AtomicU32>,
+}
+
+impl RetryBudget {
+    /// # Arguments
+    /// * `policy`: The policy to take the budget from.
+    ///
+    /// # Returns
+    /// * `Self`: A budget of `policy.per_validation_budget`
+    ///           retries, or an unlimited one.
+    pub(crate) fn new(policy: &RetryPolicy) -> Self {
+        Self { remaining: policy.per_validation_budget.map(AtomicU32::new) }
+    }
+
+    /// # Returns
+    /// * `bool`: Whether the budget limits retries at all.
+    pub(crate) fn is_limited(&self) -> bool {
+        self.remaining.is_some()
+    }
+
+    /// Takes one retry from the budget.
+    ///
+    /// # Returns
+    /// * `bool`: `true` if the retry may go ahead, `false`
+    ///           once the budget is spent.
+    pub(crate) fn try_spend(&self) -> bool {
+        match &self.remaining {
+            Some(remaining) => remaining
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                .is_ok(),
+            None => true,
+        }
+    }
+}
+
+/// What to do after a failed solution submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmitAction {
+    /// Submit the same solution again; it is still valid.
+    Resubmit,
+    /// Fetch and solve a new challenge, as the old one
+    /// expired.
+    Resolve,
+    /// Give up.
+    Fail,
+}
+
+/// How failed solution submissions are retried. Distinct
+/// from `RetryPolicy`, as a submission can fail for reasons
+/// that need different handling:
+///
+/// * A transient network failure resubmits the same
+///   solution after `backoff`.
+/// * An expired challenge is fetched and solved again.
+/// * A wrong solution, or any other rejection, fails
+///   immediately.
+///
+/// * `max_attempts`: Submissions allowed in total,
+///                   including the first. `1` never
+///                   retries.
+/// * `backoff`:      Delay before resubmitting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmitRetryPolicy {
+    pub max_attempts: u32,
+    #[serde(with = "millis_serde")]
+    pub backoff:      Duration,
+}
+
+impl Default for SubmitRetryPolicy {
+    /// No retries.
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl SubmitRetryPolicy {
+    /// # Returns
+    /// * `Self`: A policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff:      Duration::ZERO,
+        }
+    }
+
+    /// # Arguments
+    /// * `error`:   Why the submission failed.
+    /// * `attempt`: One-based number of the failed
+    ///              submission.
+    ///
+    /// # Returns
+    /// * `SubmitAction`: How to proceed.
+    pub fn action_for(&self, error: &ErrorHandler, attempt: u32) -> SubmitAction {
+        if attempt >= self.max_attempts {
+            return SubmitAction::Fail;
+        }
+
+        match error {
+            ErrorHandler::Challenge(message) if message == CHALLENGE_EXPIRED_MSG => SubmitAction::Resolve,
+            e if RetryPolicy::is_retryable(e)                                  => SubmitAction::Resubmit,
+            _                                                                  => SubmitAction::Fail,
+        }
+    }
+}
+
+/// Serializes `Duration` fields as whole milliseconds, as
+/// backoffs are usually well under a second.
+mod millis_serde {
+    use serde::{
+        Deserialize,
+        Deserializer,
+        Serializer
+    };
+    use std::time::Duration;
+
+    pub fn serialize<S>(
+        duration: &Duration,
+        serializer: S
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u64(duration.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D>(
+        deserializer: D
+    ) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Duration::from_millis(u64::deserialize(deserializer)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        let policy = RetryPolicy {
+            max_retries:     5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff:     Duration::from_millis(350),
+            per_validation_budget: None,
+        };
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(350));
+        assert_eq!(policy.backoff(40), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn test_retry_budget_is_shared() {
+        let policy = RetryPolicy { per_validation_budget: Some(2), ..RetryPolicy::none() };
+        let budget = RetryBudget::new(&policy);
+
+        assert!(budget.try_spend());
+        assert!(budget.try_spend());
+        assert!(!budget.try_spend());
+
+        let unlimited = RetryBudget::new(&RetryPolicy::none());
+        assert!(!unlimited.is_limited());
+        assert!((0..100).all(|_| unlimited.try_spend()));
+    }
+
+    #[test]
+    fn test_submit_action_for() {
+        let policy = SubmitRetryPolicy { max_attempts: 3, backoff: Duration::ZERO };
+        let expired = ErrorHandler::challenge_error(CHALLENGE_EXPIRED_MSG);
+        let timeout = ErrorHandler::timeout(Duration::from_secs(1));
+        let invalid = ErrorHandler::InvalidSolution("Invalid solution".to_string());
+
+        assert_eq!(policy.action_for(&expired, 1), SubmitAction::Resolve);
+        assert_eq!(policy.action_for(&timeout, 2), SubmitAction::Resubmit);
+        assert_eq!(policy.action_for(&invalid, 1), SubmitAction::Fail);
+        assert_eq!(policy.action_for(&timeout, 3), SubmitAction::Fail);
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(RetryPolicy::is_retryable(&ErrorHandler::timeout(Duration::from_secs(1))));
+        assert!(!RetryPolicy::is_retryable(&ErrorHandler::ProcessingError("circuit open".to_string())));
+    }
+}
@@ -0,0 +1,131 @@
+//! # HMAC-SHA256 request signing and request fingerprints.
+
+use hmac::{
+    Hmac,
+    Mac
+};
+use sha2::{
+    Digest,
+    Sha256
+};
+
+use crate::handler::error::ErrorHandler;
+use crate::handler::result::ResultHandler;
+
+/// Header carrying the hex-encoded request signature.
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+/// Header carrying the millisecond timestamp covered
+/// by the signature.
+pub const TIMESTAMP_HEADER: &str = "X-Timestamp";
+/// Header carrying the hex-encoded request fingerprint.
+pub const FINGERPRINT_HEADER: &str = "X-Request-Fingerprint";
+/// Header carrying the millisecond timestamp covered
+/// by the fingerprint.
+pub const FINGERPRINT_TIMESTAMP_HEADER: &str = "X-Fingerprint-Timestamp";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signs a request body for the gateway.
+///
+/// The signed message is `timestamp + "\n" + body`.
+///
+/// # Arguments
+/// * `key`:       The shared signing key.
+/// * `timestamp`: Unix timestamp in milliseconds, sent
+///                alongside the signature.
+/// * `body`:      The exact serialized request body.
+///
+/// # Returns
+/// * `ResultHandler<String>`: The lowercase hex-encoded
+///                            HMAC-SHA256 signature.
+pub fn sign_request(
+    key:       &[u8],
+    timestamp: i64,
+    body:      &[u8],
+) -> ResultHandler<String> {
+    let mut mac = HmacSha256::new_from_slice(key)
+        .map_err(|_| ErrorHandler::config_error("Invalid request signing key"))?;
+
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b"\n");
+    mac.update(body);
+
+    Ok(mac.finalize()
+          .into_bytes()
+          .iter()
+          .map(|byte| format!("{:02x}", byte))
+          .collect())
+}
+
+/// Fingerprints a request for the API's replay protection.
+///
+/// The hashed message is
+/// `method + "\n" + path + "\n" + timestamp + "\n" + body`.
+///
+/// # Arguments
+/// * `method`:    The HTTP method, e.g. `POST`.
+/// * `path`:      The API path, e.g. `/request`.
+/// * `timestamp`: Unix timestamp in milliseconds. For
+///                challenge requests, the one in the body.
+/// * `body`:      The exact serialized request body.
+///
+/// # Returns
+/// * `String`: The lowercase hex-encoded SHA-256 digest.
+pub fn request_fingerprint(
+    method:    &str,
+    path:      &str,
+    timestamp: i64,
+    body:      &[u8],
+) -> String {
+    let mut hasher = Sha256::new();
+
+    hasher.update(method.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(timestamp.to_string().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+
+    hasher.finalize()
+          .iter()
+          .map(|byte| format!("{:02x}", byte))
+          .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_is_hex_sha256() {
+        let signature = sign_request(b"secret", 1_700_000_000_000, b"{}").unwrap();
+        assert_eq!(signature.len(), 64);
+        assert!(signature.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_signature_covers_timestamp_and_body() {
+        let base      = sign_request(b"secret", 1, b"{}").unwrap();
+        let timestamp = sign_request(b"secret", 2, b"{}").unwrap();
+        let body      = sign_request(b"secret", 1, b"[]").unwrap();
+        let key       = sign_request(b"other",  1, b"{}").unwrap();
+
+        assert_eq!(base, sign_request(b"secret", 1, b"{}").unwrap());
+        assert_ne!(base, timestamp);
+        assert_ne!(base, body);
+        assert_ne!(base, key);
+    }
+
+    #[test]
+    fn test_fingerprint_covers_every_part() {
+        let base = request_fingerprint("POST", "/request", 1, b"{}");
+
+        assert_eq!(base.len(), 64);
+        assert_eq!(base, request_fingerprint("POST", "/request", 1, b"{}"));
+        assert_ne!(base, request_fingerprint("GET", "/request", 1, b"{}"));
+        assert_ne!(base, request_fingerprint("POST", "/response", 1, b"{}"));
+        assert_ne!(base, request_fingerprint("POST", "/request", 2, b"{}"));
+        assert_ne!(base, request_fingerprint("POST", "/request", 1, b"[]"));
+    }
+}
@@ -1,24 +1,99 @@
+//! # Proof-of-work solving.
+//!
+//! A core search cannot be interrupted once it has started.
+//! When a solve is cancelled, times out, or is suspended,
+//! its result is returned at once, but worker threads
+//! already searching keep using CPU until they find a
+//! solution of their own. So do the remaining
+//! workers of a multithreaded solve once one has found a
+//! solution. Multithreaded workers therefore run on
+//! dedicated OS threads rather than Tokio's blocking pool,
+//! so an abandoned search never delays runtime shutdown and
+//! ends when the process exits.
+
+use tokio::sync::Notify;
 use tokio::task::JoinHandle;
 use tokio::time::Duration;
 use futures::future;
+use futures::stream::{
+    FuturesUnordered,
+    Stream,
+    StreamExt
+};
+use serde::{
+    Deserialize,
+    Serialize
+};
 
+use ironshield_core::PoWConfig;
 use ironshield_types::{
     IronShieldChallenge, 
     IronShieldChallengeResponse
 };
 
+use crate::client::challenge::{
+    difficulty_bits,
+    PreparedChallenge
+};
 use crate::client::config::ClientConfig;
-use crate::handler::error::ErrorHandler;
+use crate::client::progress::{
+    BatchMember,
+    BatchProgressTracker,
+    ChannelProgressTracker,
+    CumulativeSmoother,
+    HashRateSmoother,
+    ProgressEvent
+};
+use crate::client::metrics::{
+    CpuBudget,
+    InFlightSolve,
+    SolverMetrics
+};
+use crate::client::stats::{
+    format_number_with_commas,
+    hash_rate,
+    SolveStats,
+    ThreadStats
+};
+use crate::handler::error::{
+    ErrorHandler,
+    SolveError
+};
 use crate::handler::result::ResultHandler;
 
+use std::any::Any;
+use std::io::Write;
 use std::sync::{
-    Arc, atomic::{
-        AtomicBool, 
+    Arc,
+    Mutex,
+    PoisonError,
+    atomic::{
+        AtomicBool,
+        AtomicU64,
         Ordering
     }
 };
 use std::time::Instant;
 
+/// How to solve challenges for a protected endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SolveStrategy {
+    /// Solve on a single thread.
+    SingleThreaded,
+    /// Solve on `recommended_thread_count` threads.
+    MultiThreaded,
+}
+
+impl SolveStrategy {
+    /// # Returns
+    /// * `bool`: Whether the strategy uses multithreaded
+    ///           solving.
+    pub fn is_multithreaded(self) -> bool {
+        self == Self::MultiThreaded
+    }
+}
+
 /// Configuration for proof-of-work challenge
 /// solving.
 ///
@@ -26,10 +101,25 @@ use std::time::Instant;
 ///                        for solving.
 /// * `use_multithreaded`: Whether to use
 ///                        multithreaded solving
+/// * `difficulty_override`: Number of leading zero bits
+///                          to solve for instead of the
+///                          challenge's own target. For
+///                          tests and benchmarks only: the
+///                          response will not validate
+///                          server-side unless it matches
+///                          the real target.
+/// * `stack_size`:        Stack size of solver threads in
+///                        bytes. When set, single-threaded
+///                        solves also run on a dedicated OS
+///                        thread instead of Tokio's blocking
+///                        pool.
 #[derive(Debug, Clone)]
 pub struct SolveConfig {
     pub thread_count:      usize,
     pub use_multithreaded: bool,
+    #[cfg(feature = "test-util")]
+    pub difficulty_override: Option<u32>,
+    pub stack_size:        Option<usize>,
 }
 
 impl SolveConfig {
@@ -45,23 +135,250 @@ impl SolveConfig {
     /// # Returns
     /// * `Self`: A new instance of the solving config.
     pub fn new(config: &ClientConfig, use_multithreaded: bool) -> Self {
-        let available_cores: usize = num_cpus::get();
-
-        // Use 80% of available cores, minimum 1, respect config override.
-        let thread_count: usize = if use_multithreaded {
-            config.num_threads
-                .unwrap_or_else(|| std::cmp::max(1, (available_cores * 4) / 5))
-        } else {
-            1
-        };
-
         Self {
-            thread_count,
+            thread_count: recommended_thread_count(config, use_multithreaded),
             use_multithreaded,
+            #[cfg(feature = "test-util")]
+            difficulty_override: None,
+            stack_size: config.solver_stack_size,
         }
     }
 }
 
+/// Number of threads a solve with this configuration
+/// would use.
+///
+/// # Arguments
+/// * `config`:            Client configuration containing
+///                        optional thread count override.
+/// * `use_multithreaded`: Whether multithreaded solving
+///                        would be enabled.
+///
+/// # Returns
+/// * `usize`: `ClientConfig::num_threads` if set, clamped to
+///            the available cores unless
+///            `ClientConfig::allow_oversubscription` is set,
+///            otherwise 80% of the available cores (minimum 1),
+///            or 1 when not multithreading. A clamped request
+///            is reported on stderr when `ClientConfig::verbose`
+///            is set.
+///
+/// # Example
+/// ```
+/// use ironshield::client::config::ClientConfig;
+/// use ironshield::client::solve::recommended_thread_count;
+///
+/// let config = ClientConfig::default();
+/// println!("will use {} threads", recommended_thread_count(&config, true));
+/// ```
+pub fn recommended_thread_count(config: &ClientConfig, use_multithreaded: bool) -> usize {
+    if !use_multithreaded {
+        return 1;
+    }
+
+    let available: usize = num_cpus::get();
+
+    let Some(requested) = config.num_threads else {
+        // Use 80% of available cores, minimum 1.
+        return std::cmp::max(1, (available * 4) / 5);
+    };
+
+    let (thread_count, warning) = clamp_thread_count(requested, available, config.allow_oversubscription);
+    if let Some(warning) = warning.filter(|_| config.verbose) {
+        eprintln!("WARNING: {}", warning);
+    }
+
+    thread_count
+}
+
+/// Default stack size of a Tokio blocking thread.
+const DEFAULT_SOLVE_STACK_BYTES: usize = 2 * 1024 * 1024;
+
+/// Conservative memory cost of one solver thread, as
+/// `ironshield_core` does not report its own: the default
+/// 2 MiB stack of a Tokio blocking thread plus 64 KiB for
+/// hashing buffers and progress bookkeeping. The SHA-256
+/// search itself is not memory-hard.
+pub const SOLVE_THREAD_MEMORY_BYTES: usize = DEFAULT_SOLVE_STACK_BYTES + 64 * 1024;
+
+/// Fixed memory cost of a solve regardless of its thread
+/// count, covering shared solve state and the challenge.
+pub const SOLVE_BASE_MEMORY_BYTES: usize = 64 * 1024;
+
+/// Estimates the memory a solve needs, e.g. to cap
+/// `ClientConfig::num_threads` on a memory-constrained
+/// host.
+///
+/// # Arguments
+/// * `config`:    The solve configuration.
+/// * `challenge`: The challenge to solve.
+///
+/// # Returns
+/// * `usize`: `SOLVE_THREAD_MEMORY_BYTES` per thread, with
+///            `SolveConfig::stack_size` in place of the
+///            default stack if set, plus
+///            `SOLVE_BASE_MEMORY_BYTES` and the size of the
+///            challenge, in bytes.
+///
+/// # Example
+/// ```
+/// use ironshield::{estimate_solve_memory, ClientConfig, SolveConfig};
+/// # fn example(challenge: ironshield_types::IronShieldChallenge) {
+/// let solve_config = SolveConfig::new(&ClientConfig::default(), true);
+/// let bytes = estimate_solve_memory(&solve_config, &challenge);
+/// println!("Solve needs about {} MiB", bytes / (1024 * 1024));
+/// # }
+/// ```
+pub fn estimate_solve_memory(config: &SolveConfig, challenge: &IronShieldChallenge) -> usize {
+    let thread_count: usize = if config.use_multithreaded { config.thread_count.max(1) } else { 1 };
+    let challenge_bytes: usize = std::mem::size_of::<IronShieldChallenge>()
+        + challenge.random_nonce.len()
+        + challenge.website_id.len();
+
+    let thread_bytes: usize = config.stack_size.map_or(SOLVE_THREAD_MEMORY_BYTES, |stack_size| {
+        SOLVE_THREAD_MEMORY_BYTES - DEFAULT_SOLVE_STACK_BYTES + stack_size
+    });
+
+    thread_count * thread_bytes + SOLVE_BASE_MEMORY_BYTES + challenge_bytes
+}
+
+/// Limits a configured thread count to the available cores.
+///
+/// # Arguments
+/// * `requested`: The configured thread count.
+/// * `available`: The number of available cores.
+/// * `allow`:     Whether oversubscription is allowed.
+///
+/// # Returns
+/// * `(usize, Option<String>)`: The thread count to use, and
+///                              a warning if it was clamped.
+fn clamp_thread_count(requested: usize, available: usize, allow: bool) -> (usize, Option<String>) {
+    if allow || requested <= available {
+        return (requested, None);
+    }
+
+    (available, Some(format!(
+        "num_threads = {} exceeds the {} available cores; using {}. \
+         Set allow_oversubscription to use {} threads.",
+        requested, available, available, requested
+    )))
+}
+
+/// Opaque caller data attached to a solve with
+/// `solve_challenge_with_context`, handed back to its
+/// callbacks for correlation, e.g. a request ID.
+pub type SolveContext = Arc<dyn Any + Send + Sync>;
+
+/// Maps a challenge and solve configuration to the core
+/// solver's parameters, attached to a `ClientConfig` with
+/// `set_pow_config`.
+pub type PowConfigMapper = dyn Fn(&IronShieldChallenge, &SolveConfig) -> PoWConfig + Send + Sync;
+
+/// The core solver parameters used for a challenge unless
+/// `ClientConfig::pow_config` is set.
+///
+/// The built-in mapping depends only on the strategy; the
+/// challenge is passed for mappings that need it.
+///
+/// # Arguments
+/// * `challenge`:    The challenge to solve.
+/// * `solve_config`: How it will be solved.
+///
+/// # Returns
+/// * `PoWConfig`: The core's multithreaded configuration
+///                when solving on more than one thread,
+///                otherwise its single-threaded one.
+pub fn challenge_to_pow_config(_challenge: &IronShieldChallenge, solve_config: &SolveConfig) -> PoWConfig {
+    if solve_config.use_multithreaded && solve_config.thread_count > 1 {
+        PoWConfig::multi_threaded()
+    } else {
+        PoWConfig::single_threaded()
+    }
+}
+
+/// # Arguments
+/// * `challenge`:    The challenge to solve.
+/// * `solve_config`: How it will be solved.
+/// * `config`:       Client configuration. `ClientConfig`
+///
+/// # Returns
+/// * `PoWConfig`: The parameters from `ClientConfig::pow_config`
+///                if set, otherwise `challenge_to_pow_config`.
+fn pow_config_for(
+    challenge:    &IronShieldChallenge,
+    solve_config: &SolveConfig,
+    config:       &ClientConfig,
+) -> PoWConfig {
+    match &config.pow_config {
+        Some(mapper) => mapper(challenge, solve_config),
+        None         => challenge_to_pow_config(challenge, solve_config),
+    }
+}
+
+/// Solves challenges in place of the built-in
+/// proof-of-work solver when attached to a `ClientConfig`
+/// with `set_solver`.
+///
+/// Every solve goes through the attached solver after the
+/// difficulty check and solve cache, whatever strategy or
+/// limit was requested. `solve` may block; it is run on
+/// Tokio's blocking pool, never on the async executor, except
+/// by `solve_challenge_local`, which runs it on the calling
+/// thread.
+pub trait Solver: Send + Sync {
+    /// # Arguments
+    /// * `challenge`: The challenge to solve.
+    ///
+    /// # Returns
+    /// * `ResultHandler<IronShieldChallengeResponse>`: A solution
+    ///                                                 or an error.
+    fn solve(&self, challenge: &IronShieldChallenge) -> ResultHandler<IronShieldChallengeResponse>;
+}
+
+/// A `Solver` that ignores the challenge and returns a
+/// fixed response instantly, for testing the code around
+/// solving without spending CPU on proof-of-work.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use ironshield::client::challenge::generate_challenge;
+/// use ironshield::client::config::ClientConfig;
+/// use ironshield::client::solve::{solve_blocking, FixedSolver, SolveConfig};
+///
+/// let config = ClientConfig::default();
+/// let response = solve_blocking(generate_challenge(0, "https://example.com"), SolveConfig::new(&config, false))?;
+///
+/// let mut config = config;
+/// config.set_solver(Arc::new(FixedSolver::new(response)));
+/// # Ok::<(), ironshield::handler::error::ErrorHandler>(())
+/// ```
+#[cfg(feature = "test-util")]
+#[derive(Debug, Clone)]
+pub struct FixedSolver {
+    response: IronShieldChallengeResponse,
+}
+
+#[cfg(feature = "test-util")]
+impl FixedSolver {
+    /// # Arguments
+    /// * `response`: The response to return for every
+    ///               challenge.
+    ///
+    /// # Returns
+    /// * `Self`: The solver.
+    pub fn new(response: IronShieldChallengeResponse) -> Self {
+        Self { response }
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl Solver for FixedSolver {
+    fn solve(&self, _challenge: &IronShieldChallenge) -> ResultHandler<IronShieldChallengeResponse> {
+        Ok(self.response.clone())
+    }
+}
+
 /// Trait for progress callbacks during solving
 pub trait ProgressTracker: Send + Sync {
     fn on_progress(
@@ -71,6 +388,85 @@ pub trait ProgressTracker: Send + Sync {
         hash_rate:      u64, 
         elapsed:        Duration
     );
+
+    /// Called instead of `on_progress` with the context of
+    /// the solve, if any. Forwards to `on_progress` unless
+    /// overridden.
+    fn on_progress_with_context(
+        &self,
+        context:        Option<&SolveContext>,
+        thread_id:      usize,
+        total_attempts: u64,
+        hash_rate:      u64,
+        elapsed:        Duration
+    ) {
+        let _ = context;
+        self.on_progress(thread_id, total_attempts, hash_rate, elapsed);
+    }
+}
+
+/// Why a solver thread stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    /// This thread found the solution.
+    Found,
+    /// Another thread found the solution first.
+    AbortedBecauseOtherFound,
+    /// The solve was aborted, timed out, or dropped.
+    Cancelled,
+    /// The thread failed or panicked.
+    Error(String),
+}
+
+/// Trait for observing when multithreaded solver threads
+/// start and stop. Attach with
+/// `ClientConfig::set_thread_observer`.
+///
+/// `on_stop` with `StopReason::AbortedBecauseOtherFound` or
+/// `StopReason::Cancelled` is called once the thread is
+/// abandoned, not once it exits: the core search cannot be
+/// interrupted, so the thread may keep running until it
+/// finishes its search. See the module documentation.
+pub trait ThreadLifecycleObserver: Send + Sync {
+    fn on_start(
+        &self,
+        thread_id: usize,
+        offset:    u64,
+        stride:    u64
+    );
+
+    fn on_stop(
+        &self,
+        thread_id: usize,
+        reason:    StopReason
+    );
+
+    /// Called instead of `on_start` with the context of the
+    /// solve, if any. Forwards to `on_start` unless
+    /// overridden.
+    fn on_start_with_context(
+        &self,
+        context:   Option<&SolveContext>,
+        thread_id: usize,
+        offset:    u64,
+        stride:    u64
+    ) {
+        let _ = context;
+        self.on_start(thread_id, offset, stride);
+    }
+
+    /// Called instead of `on_stop` with the context of the
+    /// solve, if any. Forwards to `on_stop` unless
+    /// overridden.
+    fn on_stop_with_context(
+        &self,
+        context:   Option<&SolveContext>,
+        thread_id: usize,
+        reason:    StopReason
+    ) {
+        let _ = context;
+        self.on_stop(thread_id, reason);
+    }
 }
 
 /// Primary entry point for solving proof-of-work challenges.
@@ -92,173 +488,1950 @@ pub async fn solve_challenge(
     use_multithreaded: bool,
     progress_tracker:  Option<Arc<dyn ProgressTracker>>,
 ) -> ResultHandler<IronShieldChallengeResponse> {
-    let solve_config: SolveConfig = SolveConfig::new(config, use_multithreaded);
-
-    let _start_time: Instant = Instant::now();
+    solve_challenge_with_stats(challenge, config, use_multithreaded, progress_tracker)
+        .await
+        .map(|(solution, _stats)| solution)
+}
 
-    // Choose a solving strategy based on configuration.
-    let result = if solve_config.use_multithreaded && solve_config.thread_count > 1 {
-        solve_multithreaded(challenge, &solve_config, config, progress_tracker).await
-    } else {
-        solve_single_threaded(challenge, config).await
-    };
+/// Solves a proof-of-work challenge and reports how the
+/// solve went.
+///
+/// # Arguments
+/// * `challenge`:          The challenge to solve.
+/// * `config`:             Client configuration. `ClientConfig`
+/// * `use_multithreading`: Whether to attempt multithreaded solving.
+/// * `progress_tracker`:   Optional progress tracker for detailed logging
+///
+/// # Returns
+/// `ResultHandler<(IronShieldChallengeResponse, SolveStats)>`: A valid
+///                                                             solution and
+///                                                             the statistics
+///                                                             of the solve,
+///                                                             or an error.
+pub async fn solve_challenge_with_stats(
+    challenge:         IronShieldChallenge,
+    config:            &ClientConfig,
+    use_multithreaded: bool,
+    progress_tracker:  Option<Arc<dyn ProgressTracker>>,
+) -> ResultHandler<(IronShieldChallengeResponse, SolveStats)> {
+    let solve_config: SolveConfig = SolveConfig::new(config, use_multithreaded);
 
-    // Return result without logging
-    result
+    run_solve(challenge, &solve_config, config, progress_tracker, None, None, None).await
 }
 
-/// Solve using multiple threads with early termination when a solution is found.
-async fn solve_multithreaded(
-    challenge: IronShieldChallenge,
-    solve_config: &SolveConfig,
-    config: &ClientConfig,
-    progress_tracker: Option<Arc<dyn ProgressTracker>>,
+/// Solves a proof-of-work challenge, handing `context` back
+/// to every progress and thread lifecycle callback so they
+/// can be correlated with the caller's own state.
+///
+/// Single-threaded solves run no worker threads and emit no
+/// progress events, so no callback ever sees the context.
+///
+/// # Arguments
+/// * `challenge`:          The challenge to solve.
+/// * `config`:             Client configuration. `ClientConfig`
+/// * `use_multithreading`: Whether to attempt multithreaded solving.
+/// * `progress_tracker`:   Optional progress tracker for detailed logging
+/// * `context`:            Passed to `on_progress_with_context`,
+///                         `on_start_with_context` and
+///                         `on_stop_with_context`.
+///
+/// # Returns
+/// `ResultHandler<IronShieldChallengeResponse>`: A valid solution
+///                                               or an error.
+pub async fn solve_challenge_with_context(
+    challenge:         IronShieldChallenge,
+    config:            &ClientConfig,
+    use_multithreaded: bool,
+    progress_tracker:  Option<Arc<dyn ProgressTracker>>,
+    context:           SolveContext,
 ) -> ResultHandler<IronShieldChallengeResponse> {
-    let challenge: Arc<IronShieldChallenge> = Arc::new(challenge);
-    let solution_found: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
-    let mut handles: Vec<JoinHandle<Result<IronShieldChallengeResponse, ErrorHandler>>> = Vec::new();
+    let solve_config: SolveConfig = SolveConfig::new(config, use_multithreaded);
 
-    // Spawn worker threads with proper stride and offset.
-    for thread_id in 0..solve_config.thread_count {
-        let      challenge_clone: Arc<IronShieldChallenge> = Arc::clone(&challenge);
-        let        thread_stride: u64 = solve_config.thread_count as u64;
-        let        thread_offset: u64 = thread_id as u64;
-        let         config_clone: ClientConfig = config.clone();
-        let solution_found_clone: Arc<AtomicBool> = Arc::clone(&solution_found);
-        let progress_tracker_clone = progress_tracker.clone();
+    run_solve(challenge, &solve_config, config, progress_tracker, None, Some(context), None)
+        .await
+        .map(|(solution, _stats)| solution)
+}
 
-        let handle = tokio::task::spawn_blocking(move || {
-            // Create progress callback for status updates.
-            let core_progress_callback = create_progress_callback(
-                thread_id,
-                config_clone.clone(),
-                solution_found_clone,
-                progress_tracker_clone,
-            );
+/// Solves many challenges, at most `concurrency` at a time.
+///
+/// Solves are started lazily: a new one starts only when a
+/// running one finishes, so a batch of any size holds at
+/// most `concurrency` solves, and their worker threads, in
+/// memory at once. A failed solve does not stop the batch.
+/// With `use_multithreaded` set, each solve also uses
+/// `recommended_thread_count` threads, so the default
+/// concurrency drops to `1` to avoid oversubscribing the CPU.
+///
+/// # Arguments
+/// * `challenges`:         The challenges to solve.
+/// * `config`:             Client configuration. `ClientConfig`
+/// * `use_multithreading`: Whether each solve is multithreaded.
+/// * `concurrency`:        Most solves to run at once, or `None`
+///                         for the number of available cores
+///                         (`1` when multithreaded).
+/// * `batch_progress`:     Optional tracker aggregating progress
+///                         across the whole batch. Every
+///                         challenge is registered up front.
+///
+/// # Returns
+/// * `Vec<ResultHandler<IronShieldChallengeResponse>>`: One result
+///                                                      per challenge,
+///                                                      in input order.
+pub async fn solve_challenges(
+    challenges:        Vec<IronShieldChallenge>,
+    config:            &ClientConfig,
+    use_multithreaded: bool,
+    concurrency:       Option<usize>,
+    batch_progress:    Option<Arc<BatchProgressTracker>>,
+) -> Vec<ResultHandler<IronShieldChallengeResponse>> {
+    let concurrency: usize = concurrency.unwrap_or_else(|| {
+        if use_multithreaded { 1 } else { num_cpus::get() }
+    });
+    let items: Vec<_> = challenges
+        .into_iter()
+        .map(|challenge| (challenge, batch_progress.as_ref().map(BatchProgressTracker::start)))
+        .collect();
 
-            // Call ironshield-core's find_solution_multi_threaded function.
-            ironshield_core::find_solution(
-                &*challenge_clone,
-                Some(ironshield_core::PoWConfig::multi_threaded()), // Use optimized multithreaded config
-                Some(thread_offset as usize),                       // start_offset for this thread.
-                Some(thread_stride as usize),                       // stride for optimal thread-stride pattern.
-                Some(&core_progress_callback),                      // Progress callback for status updates.
-            ).map_err(|e: String| ErrorHandler::ProcessingError(format!(
-                "Thread {} failed: {}", thread_id, e
-            )))
-        });
+    run_bounded(items, concurrency, |(challenge, member)| {
+        solve_batch_member(challenge, config, use_multithreaded, member)
+    }).await
+}
 
-        handles.push(handle);
-    }
+/// Solves one challenge of a batch, marking its batch
+/// member finished with the solve's final attempts once it
+/// ends, or without them if it fails or is dropped.
+///
+/// # Arguments
+/// * `challenge`:          The challenge to solve.
+/// * `config`:             Client configuration. `ClientConfig`
+/// * `use_multithreading`: Whether to attempt multithreaded solving.
+/// * `member`:             The solve's handle into the batch
+///                         tracker, if any.
+///
+/// # Returns
+/// * `ResultHandler<IronShieldChallengeResponse>`: The solve result.
+async fn solve_batch_member(
+    challenge:         IronShieldChallenge,
+    config:            &ClientConfig,
+    use_multithreaded: bool,
+    member:            Option<Arc<BatchMember>>,
+) -> ResultHandler<IronShieldChallengeResponse> {
+    let tracker: Option<Arc<dyn ProgressTracker>> = member
+        .clone()
+        .map(|member| member as Arc<dyn ProgressTracker>);
+    let mut guard: FinishOnDrop = FinishOnDrop(member);
+    let result = solve_challenge_with_stats(challenge, config, use_multithreaded, tracker).await;
 
-    // Wait for ANY thread to find a solution and immediately signal others to stop.
-    wait_for_solution(handles, solution_found, config).await
+    if let (Ok((_, stats)), Some(member)) = (&result, guard.0.take()) {
+        member.finish_with_attempts(stats.total_attempts);
+    }
+    result.map(|(solution, _)| solution)
 }
 
-/// Create a progress callback for a worker thread.
-fn create_progress_callback(
-    thread_id: usize,
-    _config: ClientConfig,
-    solution_found: Arc<AtomicBool>,
-    progress_tracker: Option<Arc<dyn ProgressTracker>>,
-) -> impl Fn(u64) {
-    let thread_start_time: Instant = Instant::now();
-    let cumulative_attempts: Arc<std::sync::atomic::AtomicU64> = Arc::new(std::sync::atomic::AtomicU64::new(0));
+/// Finishes a batch member when dropped, so a solve whose
+/// future is dropped stops counting toward the batch's
+/// hash rate.
+struct FinishOnDrop(Option<Arc<BatchMember>>);
 
-    move |batch_attempts: u64| {
-        // Stop reporting progress if a solution already found by another thread.
-        if solution_found.load(Ordering::Relaxed) {
-            return;
+impl Drop for FinishOnDrop {
+    fn drop(&mut self) {
+        if let Some(member) = &self.0 {
+            member.finish();
         }
+    }
+}
 
-        // Accumulate attempts (core callback provides batch size, not cumulative).
-        let total_attempts: u64 = cumulative_attempts.fetch_add(batch_attempts, Ordering::Relaxed) + batch_attempts;
-
-        // Progress tracking
-        let _elapsed: Duration = thread_start_time.elapsed();
-        let _elapsed_millis: u64 = _elapsed.as_millis() as u64;
-
-        // Calculate hash rate based on cumulative attempts.
-        let _hash_rate: u64 = if _elapsed_millis > 0 {
-            (total_attempts * 1000) / _elapsed_millis
-        } else {
-            total_attempts  // If solved instantly, assume 1ms.
-        };
+/// Solves a stream of challenges as they arrive, the
+/// streaming analog of `solve_challenges`.
+///
+/// Results are yielded in input order, even when a later
+/// solve finishes first. At most `concurrency` solves run
+/// at once, and the next challenge is only pulled from
+/// `input` once a slot is free, so a slow consumer of the
+/// output applies backpressure all the way to `input`.
+/// Solves are single-threaded unless
+/// `ClientConfig::heartbeat_interval` is set, which selects
+/// the multithreaded solver; keep `concurrency` low then.
+///
+/// # Arguments
+/// * `input`:          The challenges to solve.
+/// * `config`:         Client configuration. `ClientConfig`
+/// * `concurrency`:    Most solves to run at once. `0` is
+///                     treated as `1`.
+/// * `batch_progress`: Optional tracker aggregating progress
+///                     across the pipeline. Challenges are
+///                     registered as they are pulled from
+///                     `input`.
+///
+/// # Returns
+/// * `impl Stream<Item = ResultHandler<IronShieldChallengeResponse>>`:
+///   One result per challenge, in input order.
+///
+/// # Example
+/// ```no_run
+/// # async fn example(challenges: Vec<ironshield_types::IronShieldChallenge>) {
+/// use futures::StreamExt;
+/// use ironshield::{solve_pipeline, ClientConfig};
+///
+/// let config = ClientConfig::default();
+/// let results: Vec<_> = solve_pipeline(futures::stream::iter(challenges), &config, 4, None)
+///     .collect()
+///     .await;
+/// # }
+/// ```
+pub fn solve_pipeline<'a>(
+    input:          impl Stream<Item = IronShieldChallenge> + 'a,
+    config:         &'a ClientConfig,
+    concurrency:    usize,
+    batch_progress: Option<Arc<BatchProgressTracker>>,
+) -> impl Stream<Item = ResultHandler<IronShieldChallengeResponse>> + 'a {
+    input
+        .map(move |challenge| {
+            let member: Option<Arc<BatchMember>> = batch_progress.as_ref().map(BatchProgressTracker::start);
+            solve_batch_member(challenge, config, false, member)
+        })
+        .buffered(concurrency.max(1))
+}
 
-        // Progress information is available here but not currently logged
-        // The CLI wrapper will handle progress display through animations
+/// Runs `task` on every item with at most `limit` tasks in
+/// flight, starting the next only as one finishes.
+///
+/// # Arguments
+/// * `items`: The inputs.
+/// * `limit`: Most tasks in flight. `0` is treated as `1`.
+/// * `task`:  Creates the task for an item.
+///
+/// # Returns
+/// * `Vec<T>`: The task outputs in input order.
+async fn run_bounded<I, T, F, Fut>(
+    items: Vec<I>,
+    limit: usize,
+    task:  F,
+) -> Vec<T>
+where
+    F:   Fn(I) -> Fut,
+    Fut: Future<Output = T>,
+{
+    let limit: usize = limit.max(1);
+    let mut outputs: Vec<Option<T>> = (0..items.len()).map(|_| None).collect();
+    let mut pending = items.into_iter().enumerate();
+    let mut in_flight = FuturesUnordered::new();
 
-        // Call the provided progress callback if it exists
-        if let Some(tracker) = &progress_tracker {
-            tracker.on_progress(thread_id, total_attempts, _hash_rate, _elapsed);
-        }
+    for (index, item) in pending.by_ref().take(limit) {
+        let task = task(item);
+        in_flight.push(async move { (index, task.await) });
     }
-}
 
-/// Wait for any thread to find a solution and abort remaining threads.
-async fn wait_for_solution(
-    mut handles:    Vec<JoinHandle<ResultHandler<IronShieldChallengeResponse>>>,
-    solution_found: Arc<AtomicBool>,
-    _config:        &ClientConfig,
-) -> ResultHandler<IronShieldChallengeResponse> {
-    while !handles.is_empty() {
-        // Wait for the first handle to complete.
-        let (result, _thread_index, other_handles) = future::select_all(handles).await;
-
-        match result {
-            Ok(Ok(found_solution)) => {
-                // Signal all threads to stop progress reporting.
-                solution_found.store(true, Ordering::Relaxed);
-                
-                for handle in other_handles {
-                    handle.abort();
-                }
+    while let Some((index, output)) = in_flight.next().await {
+        outputs[index] = Some(output);
 
-                return Ok(found_solution);
-            },
-            Ok(Err(_e)) => {
-                handles = other_handles;
-            },
-            Err(_e) => {
-                handles = other_handles;
-            }
+        if let Some((index, item)) = pending.next() {
+            let task = task(item);
+            in_flight.push(async move { (index, task.await) });
         }
     }
 
-    Err(ErrorHandler::ProcessingError(
-        "No solution found by any thread".to_string()
-    ))
+    outputs.into_iter().map(|output| output.expect("every task completes")).collect()
 }
 
-/// Solve using a single thread.
-async fn solve_single_threaded(
-    challenge: IronShieldChallenge,
-    _config: &ClientConfig,
-) -> ResultHandler<IronShieldChallengeResponse> {
-    // Use tokio::task::spawn_blocking to avoid blocking the async runtime.
-    let handle = tokio::task::spawn_blocking(move || {
-        // Use single-threaded function (progress callbacks not supported in single-threaded core).
-        ironshield_core::find_solution(&challenge, Some(ironshield_core::PoWConfig::single_threaded()), None, None, None)
-    });
+/// Solves a challenge given and returned as JSON, for FFI
+/// and RPC boundaries.
+///
+/// # Arguments
+/// * `challenge_json`:     A serialized `IronShieldChallenge`.
+/// * `config`:             Client configuration. `ClientConfig`
+/// * `use_multithreading`: Whether to attempt multithreaded solving.
+///
+/// # Returns
+/// `ResultHandler<String>`: The serialized `IronShieldChallengeResponse`,
+///                          `ErrorHandler::SerializationError` or
+///                          `ErrorHandler::Deserialization` if the input
+///                          cannot be parsed, or the solve error.
+pub async fn solve_challenge_json(
+    challenge_json:    &str,
+    config:            &ClientConfig,
+    use_multithreaded: bool,
+) -> ResultHandler<String> {
+    let value: serde_json::Value = serde_json::from_str(challenge_json)?;
+    let challenge: IronShieldChallenge = serde_json::from_value(value.clone())
+        .map_err(|e| ErrorHandler::deserialization_error("challenge from JSON input", &value, e))?;
 
-    match handle.await {
-        Ok(Ok(solution)) => {
-            Ok(solution)
-        },
-        Ok(Err(e)) => {
-            Err(ErrorHandler::ProcessingError(format!(
-                "Single-threaded solve failed: {}", e
-            )))
-        },
-        Err(e) => {
-            Err(ErrorHandler::ProcessingError(format!(
-                "Single-threaded solve task failed: {}", e
-            )))
-        }
-    }
+    let solution = solve_challenge(challenge, config, use_multithreaded, None).await?;
+
+    Ok(serde_json::to_string(&solution)?)
 }
 
-#[cfg(test)]
+/// Solves a proof-of-work challenge, pushing progress
+/// updates into a channel the caller already owns.
+///
+/// Events are sent with `try_send` and dropped while the
+/// channel is full, so a slow receiver never stalls the
+/// solver. Each event covers one worker thread, and a
+/// single-threaded solve sends none.
+///
+/// # Arguments
+/// * `challenge`:          The challenge to solve.
+/// * `config`:             Client configuration. `ClientConfig`
+/// * `use_multithreading`: Whether to attempt multithreaded solving.
+/// * `tx`:                 Sender to push `ProgressEvent`s into.
+///
+/// # Returns
+/// `ResultHandler<IronShieldChallengeResponse>`: A valid solution or an error.
+pub async fn solve_challenge_into(
+    challenge:         IronShieldChallenge,
+    config:            &ClientConfig,
+    use_multithreaded: bool,
+    tx:                tokio::sync::mpsc::Sender<ProgressEvent>,
+) -> ResultHandler<IronShieldChallengeResponse> {
+    let tracker: Arc<dyn ProgressTracker> = Arc::new(ChannelProgressTracker::new(tx));
+
+    solve_challenge(challenge, config, use_multithreaded, Some(tracker)).await
+}
+
+/// Solves a proof-of-work challenge with an explicit
+/// solve configuration instead of one derived from
+/// `config`.
+///
+/// # Arguments
+/// * `challenge`:        The challenge to solve.
+/// * `solve_config`:     Thread and strategy settings.
+/// * `config`:           Client configuration. `ClientConfig`
+/// * `progress_tracker`: Optional progress tracker for detailed logging
+///
+/// # Returns
+/// `ResultHandler<IronShieldChallengeResponse>`: A valid solution or an error.
+pub async fn solve_with_config(
+    challenge:        IronShieldChallenge,
+    solve_config:     &SolveConfig,
+    config:           &ClientConfig,
+    progress_tracker: Option<Arc<dyn ProgressTracker>>,
+) -> ResultHandler<IronShieldChallengeResponse> {
+    run_solve(challenge, solve_config, config, progress_tracker, None, None, None)
+        .await
+        .map(|(solution, _stats)| solution)
+}
+
+/// Searches for a solution until `deadline`.
+///
+/// # Arguments
+/// * `challenge`:          The challenge to solve.
+/// * `config`:             Client configuration. `ClientConfig`
+/// * `use_multithreading`: Whether to use more than one thread.
+/// * `deadline`:           When to give up.
+///
+/// # Returns
+/// `ResultHandler<IronShieldChallengeResponse>`: A valid solution, or
+///                                               `SolveError::TimedOut`
+///                                               if the deadline passed.
+pub async fn solve_with_deadline(
+    challenge:         IronShieldChallenge,
+    config:            &ClientConfig,
+    use_multithreaded: bool,
+    deadline:          Instant,
+) -> ResultHandler<IronShieldChallengeResponse> {
+    solve_with_deadline_and_stats(challenge, config, use_multithreaded, deadline)
+        .await
+        .map(|(solution, _stats)| solution)
+}
+
+/// Like `solve_with_deadline`, also reporting how the solve
+/// went.
+///
+/// # Arguments
+/// * `challenge`:          The challenge to solve.
+/// * `config`:             Client configuration. `ClientConfig`
+/// * `use_multithreading`: Whether to use more than one thread.
+/// * `deadline`:           When to give up.
+///
+/// # Returns
+/// `ResultHandler<(IronShieldChallengeResponse, SolveStats)>`: The
+///                                                             solution
+///                                                             and its
+///                                                             statistics,
+///                                                             or an error.
+pub(crate) async fn solve_with_deadline_and_stats(
+    challenge:         IronShieldChallenge,
+    config:            &ClientConfig,
+    use_multithreaded: bool,
+    deadline:          Instant,
+) -> ResultHandler<(IronShieldChallengeResponse, SolveStats)> {
+    let solve_config: SolveConfig = SolveConfig::new(config, use_multithreaded);
+
+    run_solve(challenge, &solve_config, config, None, Some(SolveLimit::Deadline(deadline)), None, None).await
+}
+
+/// Outcome of `solve_resumable`.
+#[derive(Debug, Clone)]
+pub enum ResumableSolve {
+    /// A solution was found.
+    Solved(IronShieldChallengeResponse, SolveStats),
+    /// The deadline passed first. Every nonce below
+    /// `next_nonce` has been searched.
+    Suspended { next_nonce: u64 },
+}
+
+/// Searches for a solution from `start_nonce` until
+/// `deadline`, with the multithreaded solver, reporting
+/// where the search stopped if the deadline passes so a
+/// later call can continue from there.
+///
+/// The checkpoint is conservative: nonces a thread reached
+/// after the slowest one may be searched again. Suspending
+/// does not stop the workers already searching; see the
+/// module documentation.
+///
+/// # Arguments
+/// * `challenge`:          The challenge to solve.
+/// * `config`:             Client configuration. `ClientConfig`
+/// * `use_multithreading`: Whether to use more than one thread.
+/// * `start_nonce`:        First nonce to search, `0` for a
+///                         new search.
+/// * `deadline`:           When to suspend the search.
+///
+/// # Returns
+/// `ResultHandler<ResumableSolve>`: The solution, the nonce to
+///                                  resume from, or an error.
+pub async fn solve_resumable(
+    challenge:         IronShieldChallenge,
+    config:            &ClientConfig,
+    use_multithreaded: bool,
+    start_nonce:       u64,
+    deadline:          Instant,
+) -> ResultHandler<ResumableSolve> {
+    let solve_config: SolveConfig = SolveConfig::new(config, use_multithreaded);
+    let checkpoint: AtomicU64 = AtomicU64::new(start_nonce);
+
+    match run_solve(challenge, &solve_config, config, None, Some(SolveLimit::Deadline(deadline)), None, Some(&checkpoint)).await {
+        Ok((solution, stats))                                 => Ok(ResumableSolve::Solved(solution, stats)),
+        Err(ErrorHandler::Solve(SolveError::TimedOut { .. })) => Ok(ResumableSolve::Suspended {
+            next_nonce: checkpoint.load(Ordering::Relaxed),
+        }),
+        Err(e)                                                => Err(e),
+    }
+}
+
+/// Solves a challenge returned by
+/// `IronShieldClient::prepare_challenge`.
+///
+/// # Arguments
+/// * `prepared`: The prepared challenge.
+/// * `config`:   Client configuration. `ClientConfig`
+/// * `strategy`: Whether to solve on one or many threads.
+///
+/// # Returns
+/// `ResultHandler<IronShieldChallengeResponse>`: A valid solution or an error.
+pub async fn solve_prepared(
+    prepared: PreparedChallenge,
+    config:   &ClientConfig,
+    strategy: SolveStrategy,
+) -> ResultHandler<IronShieldChallengeResponse> {
+    solve_challenge(prepared.challenge, config, strategy.is_multithreaded(), None).await
+}
+
+/// Least time each phase of `calibrate` spends solving.
+pub const CALIBRATION_DURATION: Duration = Duration::from_secs(1);
+
+/// Difficulty of the challenges `calibrate` solves, low
+/// enough that no single solve takes much longer than
+/// `CALIBRATION_DURATION`.
+const CALIBRATION_DIFFICULTY_BITS: u32 = 20;
+
+/// Smallest multithreaded speedup for which `calibrate`
+/// recommends `SolveStrategy::MultiThreaded`.
+pub const CALIBRATION_MIN_SPEEDUP: f64 = 1.5;
+
+/// Measured single and multithreaded solving performance
+/// of this machine.
+///
+/// * `single_hashrate`:      Attempts per second on one
+///                           thread.
+/// * `multi_hashrate`:       Attempts per second on
+///                           `thread_count` threads.
+/// * `thread_count`:         Threads used for the
+///                           multithreaded measurement.
+/// * `speedup`:              `multi_hashrate` relative to
+///                           `single_hashrate`.
+/// * `recommended_strategy`: Multithreaded if `speedup` is
+///                           at least `CALIBRATION_MIN_SPEEDUP`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationReport {
+    pub single_hashrate:      u64,
+    pub multi_hashrate:       u64,
+    pub thread_count:         usize,
+    pub speedup:              f64,
+    pub recommended_strategy: SolveStrategy,
+}
+
+/// Measures whether multithreaded solving pays off on this
+/// machine, e.g. to choose a default `SolveStrategy` rather
+/// than trusting `recommended_thread_count` to scale.
+///
+/// Solves local challenges for at least
+/// `CALIBRATION_DURATION` on one thread, then on
+/// `recommended_thread_count` threads, so it takes a little
+/// over twice that long and keeps every solver thread busy.
+/// Every search has finished by the time it returns.
+/// Attached metrics, caches, solvers, budgets, timeouts, and
+/// difficulty limits are ignored.
+///
+/// # Arguments
+/// * `config`: Client configuration. `ClientConfig`
+///
+/// # Returns
+/// * `ResultHandler<CalibrationReport>`: Both hash rates and
+///                                       the recommendation,
+///                                       or an error if the
+///                                       solver failed.
+pub async fn calibrate(config: &ClientConfig) -> ResultHandler<CalibrationReport> {
+    let thread_count: usize = recommended_thread_count(config, true);
+
+    let single_hashrate: u64 = measure_hash_rate(1, config).await?;
+    let multi_hashrate: u64 = measure_hash_rate(thread_count, config).await?;
+    let speedup: f64 = multi_hashrate as f64 / std::cmp::max(1, single_hashrate) as f64;
+
+    Ok(CalibrationReport {
+        single_hashrate,
+        multi_hashrate,
+        thread_count,
+        speedup,
+        recommended_strategy: if speedup >= CALIBRATION_MIN_SPEEDUP {
+            SolveStrategy::MultiThreaded
+        } else {
+            SolveStrategy::SingleThreaded
+        },
+    })
+}
+
+/// Solves local challenges on `thread_count` threads until
+/// `CALIBRATION_DURATION` has passed. Each thread solves
+/// challenges of its own to completion and every thread is
+/// joined before returning, so no search is left running
+/// and every attempt is counted.
+///
+/// # Arguments
+/// * `thread_count`: The threads to solve on.
+/// * `config`:       Client configuration. `ClientConfig`
+///
+/// # Returns
+/// * `ResultHandler<u64>`: The attempts per second reached
+///                         across all threads.
+async fn measure_hash_rate(thread_count: usize, config: &ClientConfig) -> ResultHandler<u64> {
+    let config: ClientConfig = config.clone();
+    let stack_size: usize = config.solver_stack_size.unwrap_or(DEFAULT_SOLVE_STACK_BYTES);
+
+    let measured = tokio::task::spawn_blocking(move || -> ResultHandler<u64> {
+        let start_time: Instant = Instant::now();
+
+        let attempts: u64 = std::thread::scope(|scope| {
+            let workers = (0..thread_count)
+                .map(|thread_id| {
+                    let config: &ClientConfig = &config;
+                    std::thread::Builder::new()
+                        .stack_size(stack_size)
+                        .spawn_scoped(scope, move || calibration_worker(thread_id, config, start_time))
+                        .map_err(|e| ErrorHandler::from(SolveError::CoreFailure(format!("Failed to spawn solver thread: {}", e))))
+                })
+                .collect::<ResultHandler<Vec<_>>>()?;
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap_or_else(|payload| std::panic::resume_unwind(payload)))
+                .sum::<ResultHandler<u64>>()
+        })?;
+
+        Ok(hash_rate(attempts, start_time.elapsed().as_millis() as u64))
+    }).await;
+
+    measured.map_err(|e| SolveError::CoreFailure(format!("Calibration task failed: {}", e)))?
+}
+
+/// Solves local challenges on the current thread until
+/// `CALIBRATION_DURATION` has passed since `start_time`.
+///
+/// # Arguments
+/// * `thread_id`:  Distinguishes this thread's challenges.
+/// * `config`:     Client configuration. `ClientConfig`
+/// * `start_time`: When the measurement started.
+///
+/// # Returns
+/// * `ResultHandler<u64>`: The attempts made.
+fn calibration_worker(thread_id: usize, config: &ClientConfig, start_time: Instant) -> ResultHandler<u64> {
+    let mut attempts: u64 = 0;
+
+    for round in 0u64.. {
+        if start_time.elapsed() >= CALIBRATION_DURATION {
+            break;
+        }
+
+        let now: i64 = config.clock().now_millis();
+        let challenge: IronShieldChallenge = IronShieldChallenge {
+            random_nonce:         format!("calibration-{}-{}", thread_id, round),
+            created_time:         now,
+            expiration_time:      now + 60_000,
+            website_id:           "calibration".to_string(),
+            challenge_param:      leading_zero_target(CALIBRATION_DIFFICULTY_BITS),
+            recommended_attempts: 1 << CALIBRATION_DIFFICULTY_BITS,
+            public_key:           [0; 32],
+            challenge_signature:  [0; 64],
+        };
+
+        let pow_config: PoWConfig = pow_config_for(&challenge, &SolveConfig::new(config, false), config);
+        let (_, stats) = solve_inline(&challenge, pow_config)?;
+        attempts += stats.total_attempts;
+    }
+
+    Ok(attempts)
+}
+
+/// Starts a solve that can be cancelled from elsewhere.
+///
+/// Cancelling through the returned `SolveHandle` resolves
+/// only this solve's future; other solves sharing the same
+/// `ClientConfig` are unaffected. The worker threads already
+/// searching are not stopped and keep using CPU; see the
+/// module documentation. The future owns a copy of `config`,
+/// so it can be spawned or stored freely.
+///
+/// # Arguments
+/// * `challenge`:          The challenge to solve.
+/// * `config`:             Client configuration. `ClientConfig`
+/// * `use_multithreading`: Whether to use more than one thread.
+/// * `progress_tracker`:   Optional progress tracker for detailed logging
+///
+/// # Returns
+/// * `(SolveHandle, impl Future)`: A handle to cancel the
+///                                 solve, and the solve itself,
+///                                 which resolves to
+///                                 `SolveError::Cancelled` once
+///                                 cancelled.
+///
+/// # Example
+/// ```no_run
+/// # async fn example(challenge: ironshield_types::IronShieldChallenge) {
+/// use ironshield::{solve_challenge_cancellable, ClientConfig};
+///
+/// let (handle, solve) = solve_challenge_cancellable(challenge, &ClientConfig::default(), true, None);
+/// let task = tokio::spawn(solve);
+///
+/// handle.cancel();
+/// assert!(task.await.unwrap().is_err());
+/// # }
+/// ```
+pub fn solve_challenge_cancellable(
+    challenge:         IronShieldChallenge,
+    config:            &ClientConfig,
+    use_multithreaded: bool,
+    progress_tracker:  Option<Arc<dyn ProgressTracker>>,
+) -> (SolveHandle, impl Future<Output = ResultHandler<IronShieldChallengeResponse>> + Send + 'static) {
+    let handle: SolveHandle = SolveHandle::new();
+    let limit: SolveLimit = SolveLimit::Cancel(handle.clone());
+    let config: ClientConfig = config.clone();
+
+    let solve = async move {
+        let solve_config: SolveConfig = SolveConfig::new(&config, use_multithreaded);
+
+        run_solve(challenge, &solve_config, &config, progress_tracker, Some(limit), None, None)
+            .await
+            .map(|(solution, _stats)| solution)
+    };
+
+    (handle, solve)
+}
+
+/// Solves a challenge on the calling thread without
+/// requiring a tokio runtime.
+///
+/// The solve is CPU-bound and runs to completion inside
+/// the first poll, so the returned future is ready as soon
+/// as it is polled. This makes it usable from `async-std`,
+/// `smol`, or `futures::executor::block_on`, at the cost of
+/// blocking the executor thread for the whole solve. An
+/// attached `Solver` is used like everywhere else, on the
+/// calling thread. Solve caches, metrics, and CPU budgets
+/// are not consulted.
+///
+/// # Arguments
+/// * `challenge`: The challenge to solve.
+/// * `config`:    Client configuration. `ClientConfig`
+///
+/// # Returns
+/// `ResultHandler<IronShieldChallengeResponse>`: A valid solution or an error.
+///
+/// # Example
+/// ```no_run
+/// use ironshield::client::config::ClientConfig;
+/// use ironshield::client::solve::solve_challenge_local;
+/// use ironshield::IronShieldChallenge;
+///
+/// fn example(challenge: IronShieldChallenge) -> Result<(), Box<dyn std::error::Error>> {
+/// let config = ClientConfig::default();
+/// let solution = futures::executor::block_on(solve_challenge_local(challenge, &config))?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn solve_challenge_local(
+    challenge: IronShieldChallenge,
+    config:    &ClientConfig,
+) -> ResultHandler<IronShieldChallengeResponse> {
+    check_difficulty(&challenge, config)?;
+
+    if let Some(solver) = &config.solver {
+        return solver.solve(&challenge);
+    }
+
+    let pow_config: PoWConfig = pow_config_for(&challenge, &SolveConfig::new(config, false), config);
+    let (solution, stats) = solve_inline(&challenge, pow_config)?;
+
+    if config.verbose {
+        eprintln!("Solved locally: {}", stats);
+    }
+
+    Ok(solution)
+}
+
+/// Solves a challenge synchronously, e.g. to produce genuine
+/// proofs for a server's verification tests without an async
+/// runtime.
+///
+/// With `use_multithreaded` set, the nonce space is split
+/// across `thread_count` OS threads and the first solution
+/// found is returned. The remaining threads cannot be
+/// interrupted and run in the background until they find a
+/// solution of their own.
+///
+/// # Arguments
+/// * `challenge`:    The challenge to solve, e.g. one issued
+///                   by the server under test.
+/// * `solve_config`: Thread settings and difficulty override.
+///
+/// # Returns
+/// `ResultHandler<IronShieldChallengeResponse>`: A response the
+///                                               server's verifier
+///                                               accepts, or an error.
+///
+/// # Example
+/// ```
+/// use ironshield::client::challenge::generate_challenge;
+/// use ironshield::client::config::ClientConfig;
+/// use ironshield::client::solve::{solve_blocking, SolveConfig};
+///
+/// let challenge = generate_challenge(4, "https://example.com");
+/// let solve_config = SolveConfig::new(&ClientConfig::default(), false);
+///
+/// let solution = solve_blocking(challenge, solve_config)?;
+/// # Ok::<(), ironshield::handler::error::ErrorHandler>(())
+/// ```
+#[cfg(feature = "test-util")]
+pub fn solve_blocking(
+    challenge:    IronShieldChallenge,
+    solve_config: SolveConfig,
+) -> ResultHandler<IronShieldChallengeResponse> {
+    let challenge: IronShieldChallenge = match solve_config.difficulty_override {
+        Some(bits) => IronShieldChallenge {
+            challenge_param: leading_zero_target(bits),
+            ..challenge
+        },
+        None => challenge,
+    };
+
+    if !solve_config.use_multithreaded || solve_config.thread_count <= 1 {
+        let pow_config: PoWConfig = challenge_to_pow_config(&challenge, &solve_config);
+        return solve_inline(&challenge, pow_config).map(|(solution, _stats)| solution);
+    }
+
+    let challenge: Arc<IronShieldChallenge> = Arc::new(challenge);
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    for offset in 0..solve_config.thread_count {
+        let challenge: Arc<IronShieldChallenge> = Arc::clone(&challenge);
+        let tx = tx.clone();
+        let stride: usize = solve_config.thread_count;
+        let pow_config: PoWConfig = challenge_to_pow_config(&challenge, &solve_config);
+
+        let worker = move || {
+            let result = ironshield_core::find_solution(
+                &challenge,
+                Some(pow_config),
+                Some(offset),
+                Some(stride),
+                None,
+            );
+
+            // The receiver is gone once a solution was returned.
+            let _ = tx.send(result);
+        };
+
+        match solve_config.stack_size {
+            Some(stack_size) => {
+                std::thread::Builder::new()
+                    .stack_size(stack_size)
+                    .spawn(worker)
+                    .map_err(|e| SolveError::CoreFailure(format!("Failed to spawn solver thread: {}", e)))?;
+            },
+            None => {
+                std::thread::spawn(worker);
+            },
+        }
+    }
+    drop(tx);
+
+    let mut failures: Vec<String> = Vec::new();
+
+    for result in rx {
+        match result {
+            Ok(solution) => return Ok(solution),
+            Err(e)       => failures.push(e),
+        }
+    }
+
+    Err(match most_common(&failures) {
+        Some((message, count)) => SolveError::CoreFailure(format!(
+            "No solution found by any thread; {} of {} failed with: {}",
+            count, solve_config.thread_count, message
+        )),
+        None => SolveError::Aborted,
+    }.into())
+}
+
+/// Solves a challenge with as many threads as an existing
+/// Rayon pool has, so the solve stays within the
+/// application's parallelism budget.
+///
+/// The search runs on the multithreaded solver's own
+/// workers rather than on `pool` itself: a core search
+/// cannot be interrupted, so the partitions that lose would
+/// hold the pool's threads until they found solutions of
+/// their own. Otherwise this solves like `solve_challenge`,
+/// including the solve cache, metrics, and an attached
+/// `Solver`.
+///
+/// # Arguments
+/// * `challenge`: The challenge to solve.
+/// * `config`:    Client configuration. `ClientConfig`
+/// * `pool`:      The pool whose size bounds the solve.
+///
+/// # Returns
+/// `ResultHandler<IronShieldChallengeResponse>`: A valid solution
+///                                               or an error.
+#[cfg(feature = "rayon")]
+pub async fn solve_on_pool(
+    challenge: IronShieldChallenge,
+    config:    &ClientConfig,
+    pool:      &rayon::ThreadPool,
+) -> ResultHandler<IronShieldChallengeResponse> {
+    let solve_config: SolveConfig = SolveConfig {
+        thread_count:      pool.current_num_threads().max(1),
+        use_multithreaded: true,
+        ..SolveConfig::new(config, true)
+    };
+
+    run_solve(challenge, &solve_config, config, None, None, None, None)
+        .await
+        .map(|(solution, _stats)| solution)
+}
+
+/// Builds a target that a hash meets when it has at least
+/// `bits` leading zero bits.
+///
+/// # Arguments
+/// * `bits`: Number of leading zero bits, capped at 256.
+///
+/// # Returns
+/// * `[u8; 32]`: The big-endian challenge target.
+pub(crate) fn leading_zero_target(bits: u32) -> [u8; 32] {
+    let bits: usize = std::cmp::min(bits, 256) as usize;
+    let mut target: [u8; 32] = [0xFF; 32];
+
+    for byte in target.iter_mut().take(bits / 8) {
+        *byte = 0;
+    }
+
+    if bits < 256 && bits % 8 != 0 {
+        target[bits / 8] = 0xFF >> (bits % 8);
+    }
+
+    target
+}
+
+/// Cancels a single solve started with
+/// `solve_challenge_cancellable`.
+///
+/// Cheap to clone; every clone cancels the same solve.
+#[derive(Debug, Clone, Default)]
+pub struct SolveHandle {
+    inner: Arc<SolveCancellation>,
+}
+
+/// Shared state behind a `SolveHandle`.
+///
+/// * `cancelled`: Set once `cancel` has been called.
+/// * `notify`:    Wakes the solve waiting on cancellation.
+#[derive(Debug, Default)]
+struct SolveCancellation {
+    cancelled: AtomicBool,
+    notify:    Notify,
+}
+
+impl SolveHandle {
+    /// # Returns
+    /// * `Self`: A handle that has not been cancelled.
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves the solve to `SolveError::Cancelled`. Its
+    /// worker threads are not stopped; calling this after it
+    /// has finished has no effect.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Relaxed);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// # Returns
+    /// * `bool`: Whether `cancel` has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Waits until `cancel` is called, including before this
+    /// future was first polled.
+    async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Limit applied to a multithreaded solve.
+enum SolveLimit {
+    /// Give up once the handle is cancelled.
+    Cancel(SolveHandle),
+    /// Give up once the instant has passed.
+    Deadline(Instant),
+}
+
+/// Runs a solve with the chosen strategy, answering from
+/// the attached solve cache when possible and keeping the
+/// attached metrics up to date.
+///
+/// The multithreaded solver is used when any of these is
+/// set, and the single-threaded one otherwise:
+///
+/// * `checkpoint`, which holds the nonce to start searching
+///   from and is updated to where an unsuccessful search
+///   left off.
+/// * `ClientConfig::heartbeat_interval` or
+///   `ClientConfig::min_hashrate_deadline`, as only it counts
+///   attempts.
+/// * `solve_config.use_multithreaded`, with more than one
+///   thread.
+///
+/// `limit`, `ClientConfig::solve_timeout` and the expiry bound
+/// of `ClientConfig::auto_deadline_from_expiry` are enforced by
+/// either solver and do not change the strategy.
+/// `context` is handed back to the callbacks of the
+/// multithreaded solver.
+async fn run_solve(
+    challenge:        IronShieldChallenge,
+    solve_config:     &SolveConfig,
+    config:           &ClientConfig,
+    progress_tracker: Option<Arc<dyn ProgressTracker>>,
+    limit:            Option<SolveLimit>,
+    context:          Option<SolveContext>,
+    checkpoint:       Option<&AtomicU64>,
+) -> ResultHandler<(IronShieldChallengeResponse, SolveStats)> {
+    check_difficulty(&challenge, config)?;
+
+    #[cfg(feature = "test-util")]
+    let challenge: IronShieldChallenge = match solve_config.difficulty_override {
+        Some(bits) => IronShieldChallenge {
+            challenge_param: leading_zero_target(bits),
+            ..challenge
+        },
+        None => challenge,
+    };
+
+    if let Some(solution) = config.solve_cache.as_ref().and_then(|cache| cache.get(&challenge)) {
+        return Ok((solution, SolveStats::new(0, 0, 0, Vec::new())));
+    }
+
+    if let Some(solver) = &config.solver {
+        let solver: Arc<dyn Solver> = Arc::clone(solver);
+        let solution = tokio::task::spawn_blocking(move || solver.solve(&challenge))
+            .await
+            .map_err(|e| SolveError::CoreFailure(format!("Custom solver task failed: {}", e)))??;
+
+        return Ok((solution, SolveStats::new(0, 0, 0, Vec::new())));
+    }
+
+    if let Some(budget) = &config.cpu_budget {
+        budget.check()?;
+    }
+
+    let cached_challenge: Option<IronShieldChallenge> = config.solve_cache.as_ref().map(|_| challenge.clone());
+    let _in_flight: Option<InFlightSolve> = config.metrics.as_ref().map(|metrics| metrics.begin_solve());
+    let start_time: Instant = Instant::now();
+
+    let limit: Option<SolveLimit> = limit.or_else(|| {
+        config.solve_timeout.map(|timeout| SolveLimit::Deadline(start_time + timeout))
+    });
+
+    // Choose a solving strategy based on configuration.
+    let multithreaded: bool = checkpoint.is_some()
+        || config.heartbeat_interval.is_some()
+        || config.min_hashrate_deadline.is_some()
+        || (solve_config.use_multithreaded && solve_config.thread_count > 1);
+
+    // Bound the solve by the challenge's expiry when that is sooner.
+    let expiry: Option<Instant> = expiry_budget(&challenge, config)?.map(|budget| start_time + budget);
+    let limit: Option<SolveLimit> = match (limit, expiry) {
+        (None, Some(expiry))                                 => Some(SolveLimit::Deadline(expiry)),
+        (Some(SolveLimit::Deadline(deadline)), Some(expiry)) => Some(SolveLimit::Deadline(deadline.min(expiry))),
+        (limit, _)                                           => limit,
+    };
+
+    let result = if multithreaded {
+        solve_multithreaded(challenge, solve_config, config, progress_tracker, limit, context, checkpoint).await
+    } else {
+        solve_single_threaded(challenge, solve_config, config, limit).await
+    };
+
+    match (&result, &config.metrics) {
+        (Ok(_), Some(metrics))  => metrics.record_completed(),
+        (Err(e), Some(metrics)) => metrics.record_error(e),
+        _                       => {},
+    }
+
+    if let (Err(e), Some(log)) = (&result, &config.error_log) {
+        log.record(config.clock().now_millis(), None, e);
+    }
+
+    if let (Ok((solution, _)), Some(cache), Some(challenge)) = (&result, &config.solve_cache, &cached_challenge) {
+        cache.insert(challenge, solution.clone());
+    }
+
+    result
+}
+
+/// Time left to solve a challenge before its expiry, less
+/// `ClientConfig::expiry_safety_margin`.
+///
+/// # Arguments
+/// * `challenge`: The challenge to solve.
+/// * `config`:    Client configuration. `ClientConfig`
+///
+/// # Returns
+/// * `ResultHandler<Option<Duration>>`: The time left, `None` with
+///                                      `auto_deadline_from_expiry`
+///                                      off, or `SolveError::TimedOut`
+///                                      if no time is left.
+fn expiry_budget(challenge: &IronShieldChallenge, config: &ClientConfig) -> ResultHandler<Option<Duration>> {
+    if !config.auto_deadline_from_expiry {
+        return Ok(None);
+    }
+
+    let margin_ms: i64 = i64::try_from(config.expiry_safety_margin.as_millis()).unwrap_or(i64::MAX);
+    let remaining_ms: i64 = challenge.expiration_time
+        .saturating_sub(config.clock().now_millis())
+        .saturating_sub(margin_ms);
+
+    if remaining_ms <= 0 {
+        return Err(SolveError::TimedOut { elapsed: Duration::ZERO }.into());
+    }
+
+    Ok(Some(Duration::from_millis(remaining_ms as u64)))
+}
+
+/// Rejects challenges harder than
+/// `ClientConfig::max_difficulty_bits`.
+///
+/// # Arguments
+/// * `challenge`: The challenge as issued.
+/// * `config`:    Client configuration. `ClientConfig`
+///
+/// # Returns
+/// * `ResultHandler<()>`: `ErrorHandler::ChallengeSolvingError`
+///                        if the challenge is too hard.
+fn check_difficulty(challenge: &IronShieldChallenge, config: &ClientConfig) -> ResultHandler<()> {
+    let bits: u32 = difficulty_bits(challenge);
+
+    match config.max_difficulty_bits {
+        Some(max_bits) if bits > max_bits => Err(ErrorHandler::challenge_solving_error(format!(
+            "Challenge requires {} leading zero bits, above the configured maximum of {}",
+            bits, max_bits
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// State shared between the worker threads of a single
+/// multithreaded solve.
+///
+/// * `start_time`:      When the solve started.
+/// * `total_attempts`:  Attempts made across all threads.
+/// * `thread_attempts`: Attempts made by each thread.
+/// * `finished`:        Set once a solution is found or the
+///                      solve is aborted; stops progress
+///                      reporting.
+/// * `abort_notify`:    Wakes `wait_for_solution` when the
+///                      solve is aborted.
+/// * `abort_reason`:    The error returned for an aborted
+///                      solve.
+/// * `metrics`:         Shared load counters, if attached.
+/// * `reported_rate`:   Hash rate last reported to `metrics`,
+///                      and whether it has been withdrawn for
+///                      good.
+/// * `context`:         Caller data handed to callbacks.
+/// * `started_workers`: Workers that began running.
+/// * `deadline`:        When the solve's limit runs out, if it
+///                      has a time limit.
+struct SolveState {
+    start_time:      Instant,
+    total_attempts:  AtomicU64,
+    thread_attempts: Vec<AtomicU64>,
+    finished:        AtomicBool,
+    abort_notify:    Notify,
+    abort_reason:    Mutex<Option<ErrorHandler>>,
+    metrics:         Option<Arc<SolverMetrics>>,
+    reported_rate:   Mutex<(u64, bool)>,
+    context:         Option<SolveContext>,
+    started_workers: AtomicU64,
+    deadline:        Option<Instant>,
+}
+
+impl SolveState {
+    /// # Arguments
+    /// * `start_time`:   When the solve started.
+    /// * `thread_count`: Number of worker threads.
+    /// * `metrics`:      Shared load counters to update.
+    ///
+    /// # Returns
+    /// * `Self`: Fresh state with no attempts recorded.
+    fn new(
+        start_time:   Instant,
+        thread_count: usize,
+        metrics:      Option<Arc<SolverMetrics>>,
+    ) -> Self {
+        Self {
+            start_time,
+            total_attempts:  AtomicU64::new(0),
+            thread_attempts: (0..thread_count).map(|_| AtomicU64::new(0)).collect(),
+            finished:        AtomicBool::new(false),
+            abort_notify:    Notify::new(),
+            abort_reason:    Mutex::new(None),
+            metrics,
+            reported_rate:   Mutex::new((0, false)),
+            context:         None,
+            started_workers: AtomicU64::new(0),
+            deadline:        None,
+        }
+    }
+
+    /// Replaces this solve's contribution to the aggregate
+    /// hash rate in the attached metrics.
+    ///
+    /// # Arguments
+    /// * `rate`: The solve's current attempts per second.
+    fn report_hash_rate(&self, rate: u64) {
+        if let Some(metrics) = &self.metrics {
+            let mut reported = self.reported_rate.lock().unwrap_or_else(PoisonError::into_inner);
+
+            // A report racing the final withdrawal must not re-add the rate.
+            if !reported.1 {
+                metrics.adjust_hash_rate(rate as i64 - reported.0 as i64);
+                reported.0 = rate;
+            }
+        }
+    }
+
+    /// Removes this solve's contribution to the aggregate
+    /// hash rate for good; later reports are ignored.
+    fn withdraw_hash_rate(&self) {
+        if let Some(metrics) = &self.metrics {
+            let mut reported = self.reported_rate.lock().unwrap_or_else(PoisonError::into_inner);
+
+            metrics.adjust_hash_rate(-(reported.0 as i64));
+            *reported = (0, true);
+        }
+    }
+
+    /// # Returns
+    /// * `SolveStats`: Statistics built from the attempts
+    ///                 recorded so far.
+    fn stats(&self) -> SolveStats {
+        let elapsed_ms: u64 = self.start_time.elapsed().as_millis() as u64;
+
+        let threads: Vec<ThreadStats> = self.thread_attempts
+            .iter()
+            .enumerate()
+            .map(|(thread_id, attempts)| {
+                let attempts: u64 = attempts.load(Ordering::Relaxed);
+                ThreadStats {
+                    thread_id,
+                    attempts,
+                    hash_rate: hash_rate(attempts, elapsed_ms),
+                }
+            })
+            .collect();
+
+        SolveStats::new(
+            self.thread_attempts.len(),
+            self.total_attempts.load(Ordering::Relaxed),
+            elapsed_ms,
+            threads,
+        )
+    }
+
+    /// Stops the solve with the provided error. Only the
+    /// first reason is kept.
+    ///
+    /// # Arguments
+    /// * `reason`: The error `wait_for_solution` returns.
+    fn abort(&self, reason: ErrorHandler) {
+        let mut slot = self.abort_reason.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if slot.is_none() {
+            *slot = Some(reason);
+            self.finished.store(true, Ordering::Relaxed);
+            self.abort_notify.notify_one();
+        }
+    }
+
+    /// # Returns
+    /// * `ErrorHandler`: The reason recorded by `abort`.
+    fn take_abort_reason(&self) -> ErrorHandler {
+        self.abort_reason
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .unwrap_or_else(|| SolveError::Aborted.into())
+    }
+}
+
+/// Solve using multiple threads with early termination when a solution is found.
+///
+/// Outside a Tokio runtime the challenge is solved on a
+/// single dedicated thread instead. When no worker thread
+/// can be started, the solve falls back to
+/// `solve_single_threaded`. Both keep enforcing `limit`.
+///
+/// With a `checkpoint`, the search starts at its nonce, and
+/// if no solution is found it is advanced past every nonce
+/// all threads have searched.
+async fn solve_multithreaded(
+    challenge: IronShieldChallenge,
+    solve_config: &SolveConfig,
+    config: &ClientConfig,
+    progress_tracker: Option<Arc<dyn ProgressTracker>>,
+    limit: Option<SolveLimit>,
+    context: Option<SolveContext>,
+    checkpoint: Option<&AtomicU64>,
+) -> ResultHandler<(IronShieldChallengeResponse, SolveStats)> {
+    if tokio::runtime::Handle::try_current().is_err() {
+        if config.verbose {
+            eprintln!("WARNING: Not running in a Tokio runtime; falling back to a single solver thread.");
+        }
+        return solve_outside_runtime(challenge, solve_config, config, limit).await;
+    }
+
+    let challenge: Arc<IronShieldChallenge> = Arc::new(challenge);
+    let mut state: SolveState = SolveState::new(
+        Instant::now(),
+        solve_config.thread_count,
+        config.metrics.clone(),
+    );
+    state.context = context;
+    state.deadline = match &limit {
+        Some(SolveLimit::Deadline(deadline)) => Some(*deadline),
+        _                                    => None,
+    };
+    let state: Arc<SolveState> = Arc::new(state);
+    let mut workers: WorkerHandles = WorkerHandles {
+        handles:  Vec::with_capacity(solve_config.thread_count),
+        state:    Arc::clone(&state),
+        observer: config.thread_observer.clone(),
+    };
+
+    // Thread `i` searches nonces `i, i + stride, i + 2 * stride, ...`.
+    let partitions: Vec<(u64, u64)> = (0..solve_config.thread_count)
+        .map(|thread_id| (thread_id as u64, solve_config.thread_count as u64))
+        .collect();
+
+    #[cfg(debug_assertions)]
+    assert_disjoint_partitions(&partitions);
+
+    // Resumed solves shift every partition past the nonces already searched.
+    let start_nonce: u64 = checkpoint.map_or(0, |checkpoint| checkpoint.load(Ordering::Relaxed));
+
+    // Spawn worker threads with proper stride and offset.
+    for (thread_id, &(thread_offset, thread_stride)) in partitions.iter().enumerate() {
+        let thread_offset: u64 = start_nonce.saturating_add(thread_offset);
+        let      challenge_clone: Arc<IronShieldChallenge> = Arc::clone(&challenge);
+        let         config_clone: ClientConfig = config.clone();
+        let          state_clone: Arc<SolveState> = Arc::clone(&state);
+        let progress_tracker_clone = progress_tracker.clone();
+        let       observer_clone = config.thread_observer.clone();
+        let           cpu_budget = config.cpu_budget.clone();
+        let           pow_config: PoWConfig = pow_config_for(&challenge, solve_config, config);
+
+        let worker = move || {
+            state_clone.started_workers.fetch_add(1, Ordering::Relaxed);
+
+            if let Some(observer) = &observer_clone {
+                observer.on_start_with_context(state_clone.context.as_ref(), thread_id, thread_offset, thread_stride);
+            }
+
+            // Create progress callback for status updates.
+            let core_progress_callback = create_progress_callback(
+                thread_id,
+                config_clone,
+                state_clone,
+                progress_tracker_clone,
+            );
+
+            // Call ironshield-core's find_solution_multi_threaded function.
+            charge_cpu_budget(cpu_budget, || ironshield_core::find_solution(
+                &*challenge_clone,
+                Some(pow_config),                                   // Core parameters for this challenge.
+                Some(thread_offset as usize),                       // start_offset for this thread.
+                Some(thread_stride as usize),                       // stride for optimal thread-stride pattern.
+                Some(&core_progress_callback),                      // Progress callback for status updates.
+            )).map_err(ErrorHandler::ProcessingError)
+        };
+
+        let stack_size: usize = solve_config.stack_size.unwrap_or(DEFAULT_SOLVE_STACK_BYTES);
+        workers.handles.push((thread_id, spawn_with_stack(stack_size, worker)));
+    }
+
+    // Wait for ANY thread to find a solution. The workers are aborted once
+    // `wait_for_solution` returns, or when this future is dropped.
+    let result = tokio::select! {
+        result = wait_for_solution(workers, config) => result,
+        _ = enforce_limit(&state, limit.as_ref()) => unreachable!("enforce_limit never completes"),
+        _ = heartbeat(&state, config, challenge.recommended_attempts) => unreachable!("heartbeat never completes"),
+    };
+
+    if let (Err(_), Some(checkpoint)) = (&result, checkpoint) {
+        let rounds: u64 = state.thread_attempts
+            .iter()
+            .map(|attempts| attempts.load(Ordering::Relaxed))
+            .min()
+            .unwrap_or(0);
+
+        checkpoint.store(start_nonce.saturating_add(rounds.saturating_mul(solve_config.thread_count as u64)), Ordering::Relaxed);
+    }
+
+    // No worker thread could be spawned: a slower solution beats none.
+    let spawn_failed: bool = matches!(result, Err(ErrorHandler::Solve(SolveError::Aborted | SolveError::CoreFailure(_))))
+        && state.started_workers.load(Ordering::Relaxed) == 0;
+
+    if spawn_failed {
+        if config.verbose {
+            eprintln!("WARNING: No solver threads could be started; falling back to a single-threaded solve.");
+        }
+        let challenge: IronShieldChallenge = Arc::unwrap_or_clone(challenge);
+        return solve_single_threaded(challenge, solve_config, config, limit).await;
+    }
+
+    Ok((result?, state.stats()))
+}
+
+/// Panics unless the `(offset, stride)` pairs split the
+/// nonce space into disjoint residue classes that together
+/// cover every nonce: each stride equals the thread count
+/// and the offsets are exactly `0..stride`. Debug builds
+/// only.
+///
+/// # Arguments
+/// * `partitions`: Each thread's `(offset, stride)`.
+#[cfg(debug_assertions)]
+fn assert_disjoint_partitions(partitions: &[(u64, u64)]) {
+    let thread_count: u64 = partitions.len() as u64;
+    let mut covered: Vec<bool> = vec![false; partitions.len()];
+
+    for (thread_id, &(offset, stride)) in partitions.iter().enumerate() {
+        assert_eq!(
+            stride, thread_count,
+            "Thread {} has stride {} but there are {} threads; nonces would be skipped or searched twice",
+            thread_id, stride, thread_count
+        );
+        assert!(
+            offset < stride && !covered[offset as usize],
+            "Thread {} has offset {} which is out of range or already taken; nonce coverage has gaps or overlaps",
+            thread_id, offset
+        );
+
+        covered[offset as usize] = true;
+    }
+}
+
+/// Worker tasks of a running multithreaded solve.
+///
+/// Dropping this, including when the solve future is
+/// cancelled by `tokio::select!` or `tokio::time::timeout`,
+/// stops progress reporting, withdraws the solve's hash
+/// rate from the attached metrics, and aborts every
+/// worker's task. The core searches themselves run on in
+/// the background; see the module documentation.
+///
+/// * `handles`:  Workers that have not completed yet, with
+///               their thread IDs.
+/// * `state`:    State shared with the workers.
+/// * `observer`: Notified when each worker stops.
+struct WorkerHandles {
+    handles:  Vec<(usize, JoinHandle<ResultHandler<IronShieldChallengeResponse>>)>,
+    state:    Arc<SolveState>,
+    observer: Option<Arc<dyn ThreadLifecycleObserver>>,
+}
+
+impl WorkerHandles {
+    /// Reports a worker as stopped.
+    ///
+    /// # Arguments
+    /// * `thread_id`: The stopped worker.
+    /// * `reason`:    Why it stopped.
+    fn notify_stop(&self, thread_id: usize, reason: StopReason) {
+        if let Some(observer) = &self.observer {
+            observer.on_stop_with_context(self.state.context.as_ref(), thread_id, reason);
+        }
+    }
+
+    /// Aborts every remaining worker.
+    ///
+    /// # Arguments
+    /// * `reason`: Reported to the observer for each worker.
+    fn stop_all(&mut self, reason: StopReason) {
+        for (thread_id, handle) in std::mem::take(&mut self.handles) {
+            handle.abort();
+            self.notify_stop(thread_id, reason.clone());
+        }
+    }
+}
+
+impl Drop for WorkerHandles {
+    fn drop(&mut self) {
+        // Stop progress reporting before withdrawing this solve's hash rate.
+        self.state.finished.store(true, Ordering::Relaxed);
+        self.state.withdraw_hash_rate();
+
+        self.stop_all(StopReason::Cancelled);
+    }
+}
+
+/// Aborts the solve once its limit is reached. Never
+/// completes, so `wait_for_solution` observes the abort.
+///
+/// # Arguments
+/// * `state`: State of the running solve.
+/// * `limit`: The limit to enforce, if any.
+async fn enforce_limit(
+    state: &SolveState,
+    limit: Option<&SolveLimit>,
+) {
+    match limit {
+        Some(&SolveLimit::Deadline(deadline)) => {
+            tokio::time::sleep_until(deadline.into()).await;
+            state.abort(SolveError::TimedOut { elapsed: state.start_time.elapsed() }.into());
+        },
+        Some(SolveLimit::Cancel(handle)) => {
+            handle.cancelled().await;
+            state.abort(SolveError::Cancelled.into());
+        },
+        None => {},
+    }
+
+    future::pending::<()>().await
+}
+
+/// Logs a one-line summary of the solve every
+/// `ClientConfig::heartbeat_interval`, to
+/// `ClientConfig::log_writer` or else stderr. Never completes.
+///
+/// # Arguments
+/// * `state`:                State of the running solve.
+/// * `config`:               Supplies the interval and writer.
+/// * `recommended_attempts`: Expected attempts, for the ETA.
+async fn heartbeat(
+    state:                &SolveState,
+    config:               &ClientConfig,
+    recommended_attempts: u64,
+) {
+    if let Some(interval) = config.heartbeat_interval.filter(|interval| !interval.is_zero()) {
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+
+        loop {
+            ticker.tick().await;
+
+            let attempts: u64 = state.total_attempts.load(Ordering::Relaxed);
+            let elapsed: Duration = state.start_time.elapsed();
+            let rate: u64 = hash_rate(attempts, elapsed.as_millis() as u64);
+            let eta: String = match recommended_attempts.checked_sub(attempts) {
+                Some(remaining) if rate > 0 => format!("{:.1}s", remaining as f64 / rate as f64),
+                Some(_)                     => "unknown".to_string(),
+                None                        => "overdue".to_string(),
+            };
+
+            let line: String = format!(
+                "Solve heartbeat: {} attempts, {:.1}s elapsed, {} attempts/s, ETA {}",
+                format_number_with_commas(attempts),
+                elapsed.as_secs_f64(),
+                format_number_with_commas(rate),
+                eta,
+            );
+
+            write_log_line(config, &line);
+        }
+    }
+
+    future::pending::<()>().await
+}
+
+/// Writes a line to `ClientConfig::log_writer`, or to stderr
+/// when none is set.
+///
+/// # Arguments
+/// * `config`: Client configuration. `ClientConfig`
+/// * `line`:   The line to write, without a newline.
+fn write_log_line(config: &ClientConfig, line: &str) {
+    match &config.log_writer {
+        Some(writer) => {
+            // A failing log sink must not fail the solve.
+            let _ = writeln!(writer.lock().unwrap_or_else(PoisonError::into_inner), "{}", line);
+        },
+        None => eprintln!("{}", line),
+    }
+}
+
+/// Create a progress callback for a worker thread.
+fn create_progress_callback(
+    thread_id: usize,
+    config: ClientConfig,
+    state: Arc<SolveState>,
+    progress_tracker: Option<Arc<dyn ProgressTracker>>,
+) -> impl Fn(u64) {
+    let thread_start_time: Instant = Instant::now();
+    let smoother: Box<dyn HashRateSmoother> = match &config.hash_rate_smoother {
+        Some(factory) => factory(),
+        None          => Box::new(CumulativeSmoother::default()),
+    };
+    let smoother: Mutex<(Box<dyn HashRateSmoother>, Instant)> = Mutex::new((smoother, thread_start_time));
+
+    move |batch_attempts: u64| {
+        // Stop reporting progress if a solution already found by another thread.
+        if state.finished.load(Ordering::Relaxed) {
+            return;
+        }
+
+        // Accumulate attempts (core callback provides batch size, not cumulative).
+        let total_attempts: u64 = state.thread_attempts[thread_id].fetch_add(batch_attempts, Ordering::Relaxed) + batch_attempts;
+        let solve_attempts: u64 = state.total_attempts.fetch_add(batch_attempts, Ordering::Relaxed) + batch_attempts;
+
+        // Progress tracking
+        let _elapsed: Duration = thread_start_time.elapsed();
+
+        // Calculate hash rate from this batch with the configured smoother.
+        let _hash_rate: u64 = {
+            let mut smoother = smoother.lock().unwrap_or_else(PoisonError::into_inner);
+            let (smoother, last_sample) = &mut *smoother;
+            let now: Instant = Instant::now();
+            let dt: Duration = now - *last_sample;
+            *last_sample = now;
+            smoother.sample(batch_attempts, dt)
+        };
+
+        // Progress information is available here but not currently logged
+        // The CLI wrapper will handle progress display through animations
+
+        // Call the provided progress callback if it exists
+        if let Some(tracker) = &progress_tracker {
+            tracker.on_progress_with_context(state.context.as_ref(), thread_id, total_attempts, _hash_rate, _elapsed);
+        }
+
+        state.report_hash_rate(hash_rate(solve_attempts, state.start_time.elapsed().as_millis() as u64));
+        check_hashrate_deadline(&state, &config, solve_attempts);
+    }
+}
+
+/// Aborts the solve with `SolveError::TimedOut` when the
+/// hash rate observed after the warm-up window cannot reach the
+/// needed attempts before the solve's deadline. Solves without
+/// a time limit are never aborted. An abort is always logged,
+/// with the projected and still needed attempts, to
+/// `ClientConfig::log_writer` or stderr. Aborting resolves the
+/// solve but does not stop the workers, which keep searching in
+/// the background.
+///
+/// # Arguments
+/// * `state`:          State of the running solve.
+/// * `config`:         Client configuration carrying the
+///                     `min_hashrate_deadline` policy.
+/// * `total_attempts`: Attempts made so far across all threads.
+fn check_hashrate_deadline(
+    state:          &SolveState,
+    config:         &ClientConfig,
+    total_attempts: u64,
+) {
+    let (Some((needed_attempts, warmup_window)), Some(deadline)) = (config.min_hashrate_deadline, state.deadline) else {
+        return;
+    };
+
+    let elapsed: Duration = state.start_time.elapsed();
+    if elapsed < warmup_window {
+        return;
+    }
+
+    let elapsed_millis: u128 = std::cmp::max(1, elapsed.as_millis());
+    let remaining: Duration = deadline.saturating_duration_since(state.start_time).saturating_sub(elapsed);
+    let remaining_millis: u128 = remaining.as_millis();
+    let projected_attempts: u128 = (total_attempts as u128 * remaining_millis) / elapsed_millis;
+    let still_needed: u128 = needed_attempts.saturating_sub(total_attempts) as u128;
+
+    if projected_attempts < still_needed {
+        write_log_line(config, &format!(
+            "Aborting solve: projected {} more attempts in the {:?} left, {} still needed.",
+            projected_attempts, remaining, still_needed
+        ));
+        state.abort(SolveError::TimedOut { elapsed }.into());
+    }
+}
+
+/// Wait for any thread to find a solution. The remaining
+/// threads are aborted when `workers` is dropped on return.
+async fn wait_for_solution(
+    mut workers: WorkerHandles,
+    _config:     &ClientConfig,
+) -> ResultHandler<IronShieldChallengeResponse> {
+    let thread_count: usize = workers.handles.len();
+    let mut failures: Vec<String> = Vec::new();
+
+    while !workers.handles.is_empty() {
+        // Wait for the first handle to complete, or for the solve to be aborted.
+        let completed = tokio::select! {
+            (result, thread_index, _) = future::select_all(workers.handles.iter_mut().map(|(_, handle)| handle)) => Some((result, thread_index)),
+            _ = workers.state.abort_notify.notified() => None,
+        };
+
+        let Some((result, thread_index)) = completed else {
+            workers.stop_all(StopReason::Cancelled);
+            return Err(workers.state.take_abort_reason());
+        };
+
+        let (thread_id, _) = workers.handles.swap_remove(thread_index);
+
+        match result {
+            Ok(Ok(found_solution)) => {
+                workers.notify_stop(thread_id, StopReason::Found);
+                workers.stop_all(StopReason::AbortedBecauseOtherFound);
+                return Ok(found_solution);
+            },
+            Ok(Err(e)) => {
+                let message: String = match e {
+                    ErrorHandler::ProcessingError(message) => message,
+                    e                                      => e.to_string(),
+                };
+                workers.notify_stop(thread_id, StopReason::Error(message.clone()));
+                failures.push(message);
+            },
+            Err(join_error) if join_error.is_cancelled() => {
+                workers.notify_stop(thread_id, StopReason::Cancelled);
+            },
+            Err(join_error) => {
+                // Keep the panic message so a crashing core call is diagnosable.
+                let message: String = panic_message(join_error.into_panic());
+                workers.notify_stop(thread_id, StopReason::Error(message.clone()));
+                failures.push(format!("worker panicked: {}", message));
+            }
+        }
+    }
+
+    let Some((message, count)) = most_common(&failures) else {
+        return Err(SolveError::Aborted.into());
+    };
+
+    Err(SolveError::CoreFailure(format!(
+        "No solution found by any thread; {} of {} failed with: {}",
+        count, thread_count, message
+    )).into())
+}
+
+/// Finds the most frequent of the failures reported by
+/// worker threads, preferring the earliest on a tie.
+///
+/// # Arguments
+/// * `failures`: Failure messages in the order reported.
+///
+/// # Returns
+/// * `Option<(&str, usize)>`: The message and how many
+///                            threads reported it, or
+///                            `None` if there were none.
+fn most_common(failures: &[String]) -> Option<(&str, usize)> {
+    let mut best: Option<(&str, usize)> = None;
+
+    for failure in failures {
+        let count: usize = failures.iter().filter(|other| *other == failure).count();
+
+        if best.is_none_or(|(_, best_count)| count > best_count) {
+            best = Some((failure, count));
+        }
+    }
+
+    best
+}
+
+/// Extracts the message from a worker thread's panic payload.
+///
+/// # Arguments
+/// * `payload`: The payload returned by `JoinError::into_panic`.
+///
+/// # Returns
+/// * `String`: The panic message, if it was a string.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Runs a search on the current thread, charging the time it
+/// took to `budget` once it ends, even if the solve it
+/// belongs to was abandoned long before.
+///
+/// # Arguments
+/// * `budget`: The CPU budget to charge, if any.
+/// * `search`: The search to run.
+///
+/// # Returns
+/// * `T`: The search's result.
+fn charge_cpu_budget<T>(budget: Option<Arc<CpuBudget>>, search: impl FnOnce() -> T) -> T {
+    let start_time: Instant = Instant::now();
+    let result: T = search();
+
+    if let Some(budget) = budget {
+        budget.record(1, start_time.elapsed());
+    }
+    result
+}
+
+/// Solve using a single thread.
+///
+/// With a `limit`, the solve fails once it is reached, and
+/// the search is left running on a dedicated thread.
+async fn solve_single_threaded(
+    challenge: IronShieldChallenge,
+    solve_config: &SolveConfig,
+    config: &ClientConfig,
+    limit: Option<SolveLimit>,
+) -> ResultHandler<(IronShieldChallengeResponse, SolveStats)> {
+    let pow_config: PoWConfig = pow_config_for(&challenge, &SolveConfig::new(config, false), config);
+    let cpu_budget: Option<Arc<CpuBudget>> = config.cpu_budget.clone();
+    let worker = move || charge_cpu_budget(cpu_budget, || solve_inline(&challenge, pow_config));
+
+    // Keep blocking work off the async runtime's threads.
+    let handle = match (solve_config.stack_size, &limit) {
+        (Some(stack_size), _) => spawn_with_stack(stack_size, worker),
+        (None, Some(_))       => spawn_with_stack(DEFAULT_SOLVE_STACK_BYTES, worker),
+        (None, None)          => tokio::task::spawn_blocking(worker),
+    };
+
+    let joined = match limit {
+        Some(limit) => {
+            let state: SolveState = SolveState::new(Instant::now(), 1, None);
+
+            tokio::select! {
+                joined = handle => joined,
+                _ = state.abort_notify.notified() => return Err(state.take_abort_reason()),
+                _ = enforce_limit(&state, Some(&limit)) => unreachable!("enforce_limit never completes"),
+            }
+        },
+        None => handle.await,
+    };
+
+    match joined {
+        Ok(result) => result,
+        Err(e) => {
+            Err(SolveError::CoreFailure(format!(
+                "Single-threaded solve task failed: {}", e
+            )).into())
+        }
+    }
+}
+
+/// Solves a challenge without a Tokio runtime, on a single
+/// dedicated thread so the executor polling this future is
+/// not blocked. A `limit` is enforced without Tokio's timers:
+/// deadlines are waited out on another thread.
+///
+/// # Arguments
+/// * `challenge`:    The challenge to solve.
+/// * `solve_config`: Supplies the solver thread's stack size.
+/// * `config`:       Client configuration.
+/// * `limit`:        The limit to enforce, if any.
+///
+/// # Returns
+/// * `ResultHandler<(IronShieldChallengeResponse, SolveStats)>`:
+///   The solution and statistics of the solve, or an error.
+async fn solve_outside_runtime(
+    challenge:    IronShieldChallenge,
+    solve_config: &SolveConfig,
+    config:       &ClientConfig,
+    limit:        Option<SolveLimit>,
+) -> ResultHandler<(IronShieldChallengeResponse, SolveStats)> {
+    let pow_config: PoWConfig = pow_config_for(&challenge, &SolveConfig::new(config, false), config);
+    let cpu_budget: Option<Arc<CpuBudget>> = config.cpu_budget.clone();
+    let start_time: Instant = Instant::now();
+    let (tx, rx) = futures::channel::oneshot::channel();
+
+    std::thread::Builder::new()
+        .name("ironshield-solver".to_string())
+        .stack_size(solve_config.stack_size.unwrap_or(DEFAULT_SOLVE_STACK_BYTES))
+        .spawn(move || {
+            // The receiver is gone if the solve was abandoned.
+            let _ = tx.send(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                charge_cpu_budget(cpu_budget, || solve_inline(&challenge, pow_config))
+            })));
+        })
+        .map_err(|e| SolveError::CoreFailure(format!("Failed to spawn solver thread: {}", e)))?;
+
+    let solved = async move {
+        match rx.await {
+            Ok(Ok(result))   => result,
+            Ok(Err(payload)) => std::panic::resume_unwind(payload),
+            Err(_)           => Err(SolveError::Aborted.into()),
+        }
+    };
+
+    let Some(limit) = limit else {
+        return solved.await;
+    };
+
+    let limit_reached = async move {
+        match limit {
+            SolveLimit::Deadline(deadline) => {
+                sleep_on_thread(deadline).await;
+                ErrorHandler::from(SolveError::TimedOut { elapsed: start_time.elapsed() })
+            },
+            SolveLimit::Cancel(handle) => {
+                handle.cancelled().await;
+                SolveError::Cancelled.into()
+            },
+        }
+    };
+
+    match future::select(Box::pin(solved), Box::pin(limit_reached)).await {
+        future::Either::Left((result, _)) => result,
+        future::Either::Right((error, _)) => Err(error),
+    }
+}
+
+/// Waits until `deadline` without a Tokio timer, by sleeping
+/// on a short-lived thread.
+///
+/// # Arguments
+/// * `deadline`: The instant to wait for.
+async fn sleep_on_thread(deadline: Instant) {
+    let (tx, rx) = futures::channel::oneshot::channel::<()>();
+
+    let spawned = std::thread::Builder::new()
+        .name("ironshield-timer".to_string())
+        .spawn(move || {
+            std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+            let _ = tx.send(());
+        });
+
+    // Without a timer thread, treat the deadline as reached rather than drop it.
+    if spawned.is_err() {
+        return;
+    }
+
+    let _ = rx.await;
+}
+
+/// Runs `task` on a dedicated OS thread with a
+/// `stack_size` byte stack, bridged to a Tokio task so it
+/// can be awaited like one from `spawn_blocking`. A panic in `task` resurfaces as a
+/// panic of the returned task.
+///
+/// # Arguments
+/// * `stack_size`: Stack size of the thread in bytes.
+/// * `task`:       The blocking work to run.
+///
+/// # Returns
+/// * `JoinHandle<ResultHandler<T>>`: The result of `task`, or
+///                                   an error if the thread
+///                                   could not be spawned.
+fn spawn_with_stack<T, F>(stack_size: usize, task: F) -> JoinHandle<ResultHandler<T>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> ResultHandler<T> + Send + 'static,
+{
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let spawned = std::thread::Builder::new()
+        .name("ironshield-solver".to_string())
+        .stack_size(stack_size)
+        .spawn(move || {
+            // The receiver is gone if the solve was abandoned.
+            let _ = tx.send(std::panic::catch_unwind(std::panic::AssertUnwindSafe(task)));
+        });
+
+    tokio::spawn(async move {
+        if let Err(e) = spawned {
+            return Err(SolveError::CoreFailure(format!("Failed to spawn solver thread: {}", e)).into());
+        }
+
+        match rx.await {
+            Ok(Ok(result))   => result,
+            Ok(Err(payload)) => std::panic::resume_unwind(payload),
+            Err(_)           => Err(SolveError::Aborted.into()),
+        }
+    })
+}
+
+/// Solves a challenge on the current thread.
+///
+/// # Arguments
+/// * `challenge`:  The challenge to solve.
+/// * `pow_config`: Core solver parameters.
+///
+/// # Returns
+/// * `ResultHandler<(IronShieldChallengeResponse, SolveStats)>`:
+///   The solution and statistics of the solve, or an error.
+fn solve_inline(
+    challenge:  &IronShieldChallenge,
+    pow_config: PoWConfig,
+) -> ResultHandler<(IronShieldChallengeResponse, SolveStats)> {
+    let start_time: Instant = Instant::now();
+
+    // Use single-threaded function (progress callbacks not supported in single-threaded core).
+    match ironshield_core::find_solution(challenge, Some(pow_config), None, None, None) {
+        Ok(solution) => {
+            // Single-threaded search starts at nonce 0 with stride 1, so
+            // this is exact only while the core keeps that nonce order.
+            let attempts: u64 = solution.solution as u64 + 1;
+            let stats: SolveStats = SolveStats::new(
+                1,
+                attempts,
+                start_time.elapsed().as_millis() as u64,
+                Vec::new(),
+            );
+
+            Ok((solution, stats))
+        },
+        Err(e) => {
+            Err(SolveError::CoreFailure(format!(
+                "Single-threaded solve failed: {}", e
+            )).into())
+        }
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
     use std::time::Duration;
@@ -271,6 +2444,7 @@ mod tests {
             timeout: Duration::from_secs(30),
             user_agent: crate::constant::USER_AGENT.to_string(),
             verbose: false,
+            ..ClientConfig::default()
         };
 
         let solve_config = SolveConfig::new(&config, false);
@@ -278,6 +2452,120 @@ mod tests {
         assert!(!solve_config.use_multithreaded);
     }
 
+    #[test]
+    fn test_withdrawn_hash_rate_stays_withdrawn() {
+        let metrics = Arc::new(SolverMetrics::new());
+        let state = SolveState::new(Instant::now(), 1, Some(Arc::clone(&metrics)));
+
+        state.report_hash_rate(1_000);
+        assert_eq!(metrics.aggregate_hashrate(), 1_000);
+
+        state.withdraw_hash_rate();
+        state.report_hash_rate(2_000);
+        assert_eq!(metrics.aggregate_hashrate(), 0);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn test_batch_counts_single_threaded_attempts() {
+        let challenges: Vec<_> = (0..3)
+            .map(|_| crate::client::challenge::generate_challenge(8, "https://example.com"))
+            .collect();
+        let batch = Arc::new(BatchProgressTracker::new());
+        let config = ClientConfig::default();
+
+        let results = solve_challenges(challenges, &config, false, None, Some(Arc::clone(&batch))).await;
+        assert!(results.iter().all(Result::is_ok));
+
+        let progress = batch.progress();
+        assert_eq!(progress.completed, 3);
+        assert!(progress.total_attempts >= 3);
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_writes_to_log_writer() {
+        let output: Arc<Mutex<Vec<u8>>> = Arc::new(Mutex::new(Vec::new()));
+        let mut config = ClientConfig::default();
+        config.heartbeat_interval = Some(Duration::from_millis(20));
+        config.set_log_writer(output.clone());
+
+        let state = SolveState::new(Instant::now(), 1, None);
+        state.total_attempts.store(1_500, Ordering::Relaxed);
+
+        tokio::select! {
+            _ = heartbeat(&state, &config, 3_000) => unreachable!(),
+            _ = tokio::time::sleep(Duration::from_millis(70)) => {},
+        }
+
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert!(output.lines().count() >= 2);
+        assert!(output.lines().all(|line| line.starts_with("Solve heartbeat: 1,500 attempts")));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_aborts_solve() {
+        let state = SolveState::new(Instant::now(), 1, None);
+        let handle = SolveHandle::new();
+        let other = SolveHandle::new();
+
+        handle.clone().cancel();
+        assert!(handle.is_cancelled());
+        assert!(!other.is_cancelled());
+
+        tokio::select! {
+            _ = enforce_limit(&state, Some(&SolveLimit::Cancel(handle))) => unreachable!(),
+            _ = tokio::time::sleep(Duration::from_millis(50)) => {},
+        }
+
+        assert!(state.finished.load(Ordering::Relaxed));
+        assert!(matches!(state.take_abort_reason(), ErrorHandler::Solve(SolveError::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn test_solve_challenge_json_rejects_bad_input() {
+        let config = ClientConfig::default();
+
+        let error = solve_challenge_json("{\"random_nonce\"", &config, false).await.unwrap_err();
+        assert!(matches!(error, ErrorHandler::SerializationError(_)));
+
+        let error = solve_challenge_json("{\"random_nonce\": \"abc\"}", &config, false).await.unwrap_err();
+        assert!(matches!(error, ErrorHandler::Deserialization { .. }));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_assert_disjoint_partitions_accepts_strided_split() {
+        assert_disjoint_partitions(&[(0, 3), (1, 3), (2, 3)]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "already taken")]
+    fn test_assert_disjoint_partitions_rejects_overlap() {
+        assert_disjoint_partitions(&[(0, 2), (0, 2)]);
+    }
+
+    #[test]
+    fn test_recommended_thread_count() {
+        let mut config = ClientConfig::default();
+        assert!(recommended_thread_count(&config, true) >= 1);
+
+        config.num_threads = Some(6);
+        config.allow_oversubscription = true;
+        assert_eq!(recommended_thread_count(&config, true), 6);
+        assert_eq!(recommended_thread_count(&config, false), 1);
+    }
+
+    #[test]
+    fn test_clamp_thread_count() {
+        let (threads, warning) = clamp_thread_count(64, 8, false);
+        assert_eq!(threads, 8);
+        assert!(warning.unwrap().starts_with("num_threads = 64 exceeds the 8 available cores"));
+
+        assert_eq!(clamp_thread_count(64, 8, true), (64, None));
+        assert_eq!(clamp_thread_count(4, 8, false), (4, None));
+    }
+
     #[test]
     fn test_solve_config_multithreaded() {
         let config = ClientConfig {
@@ -286,6 +2574,8 @@ mod tests {
             timeout: Duration::from_secs(30),
             user_agent: crate::constant::USER_AGENT.to_string(),
             verbose: false,
+            allow_oversubscription: true,
+            ..ClientConfig::default()
         };
 
         let solve_config = SolveConfig::new(&config, true);
@@ -301,10 +2591,492 @@ mod tests {
             timeout: Duration::from_secs(30),
             user_agent: crate::constant::USER_AGENT.to_string(),
             verbose: false,
+            ..ClientConfig::default()
         };
 
         let solve_config = SolveConfig::new(&config, true);
         assert!(solve_config.thread_count >= 1);
         assert!(solve_config.use_multithreaded);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn test_estimate_solve_memory_scales_with_threads() {
+        let challenge = crate::client::challenge::generate_challenge(8, "https://example.com");
+        let single = SolveConfig { thread_count: 4, use_multithreaded: false, difficulty_override: None, stack_size: None };
+        let multi = SolveConfig { thread_count: 4, use_multithreaded: true, difficulty_override: None, stack_size: None };
+
+        assert_eq!(
+            estimate_solve_memory(&multi, &challenge) - estimate_solve_memory(&single, &challenge),
+            3 * SOLVE_THREAD_MEMORY_BYTES
+        );
+        assert!(estimate_solve_memory(&single, &challenge) > SOLVE_THREAD_MEMORY_BYTES + SOLVE_BASE_MEMORY_BYTES);
+    }
+
+    #[test]
+    fn test_hashrate_deadline_aborts_when_unreachable() {
+        let output: Arc<Mutex<Vec<u8>>> = Arc::new(Mutex::new(Vec::new()));
+        let mut config = ClientConfig {
+            min_hashrate_deadline: Some((1_000_000_000, Duration::ZERO)),
+            ..ClientConfig::default()
+        };
+        config.set_log_writer(output.clone());
+        let mut state = SolveState::new(Instant::now() - Duration::from_millis(500), 1, None);
+        state.deadline = Some(state.start_time + Duration::from_secs(1));
+
+        check_hashrate_deadline(&state, &config, 10);
+
+        assert!(state.finished.load(Ordering::Relaxed));
+        assert!(matches!(state.take_abort_reason(), ErrorHandler::Solve(SolveError::TimedOut { .. })));
+
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert!(output.starts_with("Aborting solve: projected "));
+        assert!(output.contains("999999990 still needed"));
+    }
+
+    #[test]
+    fn test_hashrate_deadline_waits_for_warmup() {
+        let config = ClientConfig {
+            min_hashrate_deadline: Some((1_000_000_000, Duration::from_secs(60))),
+            ..ClientConfig::default()
+        };
+        let mut state = SolveState::new(Instant::now(), 1, None);
+        state.deadline = Some(state.start_time + Duration::from_secs(1));
+
+        check_hashrate_deadline(&state, &config, 10);
+
+        assert!(!state.finished.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_hashrate_deadline_uses_solve_deadline() {
+        // The HTTP timeout is far shorter, but the solve's own deadline is an hour away.
+        let config = ClientConfig {
+            timeout: Duration::from_secs(1),
+            min_hashrate_deadline: Some((1_000_000, Duration::ZERO)),
+            ..ClientConfig::default()
+        };
+        let mut state = SolveState::new(Instant::now() - Duration::from_millis(500), 1, None);
+        check_hashrate_deadline(&state, &config, 10_000);
+        assert!(!state.finished.load(Ordering::Relaxed));
+
+        state.deadline = Some(state.start_time + Duration::from_secs(3_600));
+        check_hashrate_deadline(&state, &config, 10_000);
+        assert!(!state.finished.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn test_calibrate_measures_both_strategies() {
+        let config = ClientConfig {
+            num_threads:            Some(2),
+            allow_oversubscription: true,
+            ..ClientConfig::default()
+        };
+
+        let report = calibrate(&config).await.unwrap();
+        assert_eq!(report.thread_count, 2);
+        assert!(report.single_hashrate > 0);
+        assert!(report.multi_hashrate > 0);
+    }
+
+    #[test]
+    fn test_panic_message_downcasts_strings() {
+        assert_eq!(panic_message(Box::new("static")), "static");
+        assert_eq!(panic_message(Box::new("owned".to_string())), "owned");
+        assert_eq!(panic_message(Box::new(42)), "unknown panic payload");
+    }
+
+    /// Records every stop reported to it.
+    #[derive(Default)]
+    struct RecordingObserver {
+        stops: Mutex<Vec<(usize, StopReason)>>,
+    }
+
+    impl ThreadLifecycleObserver for RecordingObserver {
+        fn on_start(&self, _thread_id: usize, _offset: u64, _stride: u64) {}
+
+        fn on_stop(&self, thread_id: usize, reason: StopReason) {
+            self.stops.lock().unwrap().push((thread_id, reason));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_solution_reports_worker_panics() {
+        let config = ClientConfig::default();
+        let state = Arc::new(SolveState::new(Instant::now(), 2, None));
+        let handles: Vec<(usize, JoinHandle<ResultHandler<IronShieldChallengeResponse>>)> = (0..2)
+            .map(|thread_id| (thread_id, tokio::task::spawn_blocking(|| -> ResultHandler<IronShieldChallengeResponse> {
+                panic!("core exploded")
+            })))
+            .collect();
+        let observer = Arc::new(RecordingObserver::default());
+        let workers = WorkerHandles { handles, state, observer: Some(observer.clone()) };
+
+        let error = wait_for_solution(workers, &config).await.unwrap_err();
+        assert!(error.to_string().contains("core exploded"));
+
+        let stops = observer.stops.lock().unwrap();
+        assert_eq!(stops.len(), 2);
+        assert!(stops.iter().all(|(_, reason)| matches!(reason, StopReason::Error(message) if message.contains("core exploded"))));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_solution_reports_most_common_failure() {
+        let config = ClientConfig::default();
+        let state = Arc::new(SolveState::new(Instant::now(), 3, None));
+        let handles: Vec<(usize, JoinHandle<ResultHandler<IronShieldChallengeResponse>>)> = ["bad target", "other", "bad target"]
+            .into_iter()
+            .enumerate()
+            .map(|(thread_id, message)| (thread_id, tokio::task::spawn_blocking(move || {
+                Err(ErrorHandler::ProcessingError(message.to_string()))
+            })))
+            .collect();
+        let workers = WorkerHandles { handles, state, observer: None };
+
+        let error = wait_for_solution(workers, &config).await.unwrap_err();
+        assert!(error.to_string().ends_with("2 of 3 failed with: bad target"));
+    }
+
+    #[tokio::test]
+    async fn test_run_bounded_limits_tasks_in_flight() {
+        let in_flight = AtomicU64::new(0);
+        let peak = AtomicU64::new(0);
+
+        let outputs = run_bounded((0..10_000).collect::<Vec<u64>>(), 8, |item| {
+            let in_flight = &in_flight;
+            let peak = &peak;
+            async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(current, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                item * 2
+            }
+        }).await;
+
+        assert_eq!(outputs.len(), 10_000);
+        assert!(outputs.iter().enumerate().all(|(index, output)| *output == index as u64 * 2));
+        assert!(peak.load(Ordering::SeqCst) <= 8);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn test_solve_challenges_in_order() {
+        let challenges: Vec<IronShieldChallenge> = (0..10_000)
+            .map(|seed| crate::client::challenge::generate_challenge_with_seed(0, "https://example.com", seed))
+            .collect();
+
+        let batch = Arc::new(BatchProgressTracker::new());
+        let results = solve_challenges(challenges, &ClientConfig::default(), false, Some(16), Some(batch.clone())).await;
+
+        assert_eq!(results.len(), 10_000);
+        assert!(results.iter().all(Result::is_ok));
+
+        let progress = batch.progress();
+        assert_eq!(progress.challenges, 10_000);
+        assert_eq!(progress.completed, 10_000);
+        assert_eq!(progress.hash_rate, 0);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn test_auto_deadline_from_expiry() {
+        let mut challenge = crate::client::challenge::generate_challenge(0, "https://example.com");
+        challenge.expiration_time = ironshield_types::chrono::Utc::now().timestamp_millis() + 1_000;
+
+        let mut config = ClientConfig::default();
+        let result = solve_challenge(challenge.clone(), &config, false, None).await;
+        assert!(matches!(result, Err(ErrorHandler::Solve(SolveError::TimedOut { .. }))));
+
+        config.expiry_safety_margin = Duration::ZERO;
+        assert!(solve_challenge(challenge.clone(), &config, false, None).await.is_ok());
+
+        config.expiry_safety_margin = Duration::from_secs(60);
+        config.auto_deadline_from_expiry = false;
+        assert!(solve_challenge(challenge, &config, false, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn test_solve_timeout_keeps_single_threaded_solves() {
+        let challenge = crate::client::challenge::generate_challenge(4, "https://example.com");
+        let config = ClientConfig {
+            solve_timeout: Some(Duration::from_secs(60)),
+            ..ClientConfig::default()
+        };
+
+        // Only the multithreaded solver reports per-thread stats.
+        let (_, stats) = solve_challenge_with_stats(challenge, &config, false, None).await.unwrap();
+        assert!(stats.threads.is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn test_solve_pipeline_yields_every_result() {
+        let challenges = (0..64)
+            .map(|seed| crate::client::challenge::generate_challenge_with_seed(0, "https://example.com", seed));
+        let config = ClientConfig::default();
+
+        let results: Vec<_> = solve_pipeline(futures::stream::iter(challenges), &config, 4, None).collect().await;
+
+        assert_eq!(results.len(), 64);
+        assert!(results.iter().all(Result::is_ok));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn test_solve_pipeline_keeps_input_order() {
+        // The first challenge is far harder, so the rest finish before it.
+        let challenges: Vec<IronShieldChallenge> = std::iter::once(18)
+            .chain(std::iter::repeat_n(0, 7))
+            .enumerate()
+            .map(|(seed, bits)| crate::client::challenge::generate_challenge_with_seed(bits, "https://example.com", seed as u64))
+            .collect();
+        let config = ClientConfig::default();
+
+        let results: Vec<_> = solve_pipeline(futures::stream::iter(challenges.clone()), &config, 4, None).collect().await;
+
+        assert_eq!(results.len(), challenges.len());
+        for (challenge, result) in challenges.iter().zip(results) {
+            assert_eq!(result.unwrap().solved_challenge.random_nonce, challenge.random_nonce);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn test_solve_multithreaded_falls_back_without_runtime() {
+        let challenge = crate::client::challenge::generate_challenge(4, "https://example.com");
+        let solve_config = SolveConfig { thread_count: 2, use_multithreaded: true, difficulty_override: None, stack_size: None };
+        let config = ClientConfig::default();
+
+        let result = futures::executor::block_on(solve_multithreaded(challenge, &solve_config, &config, None, None, None, None));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn test_solve_without_runtime_keeps_limit() {
+        let challenge = crate::client::challenge::generate_challenge(22, "https://example.com");
+        let solve_config = SolveConfig { thread_count: 2, use_multithreaded: true, difficulty_override: None, stack_size: None };
+        let config = ClientConfig::default();
+
+        let limit = Some(SolveLimit::Deadline(Instant::now()));
+        let result = futures::executor::block_on(solve_multithreaded(challenge.clone(), &solve_config, &config, None, limit, None, None));
+        assert!(matches!(result, Err(ErrorHandler::Solve(SolveError::TimedOut { .. }))));
+
+        let handle = SolveHandle::new();
+        handle.cancel();
+        let limit = Some(SolveLimit::Cancel(handle));
+        let result = futures::executor::block_on(solve_multithreaded(challenge, &solve_config, &config, None, limit, None, None));
+        assert!(matches!(result, Err(ErrorHandler::Solve(SolveError::Cancelled))));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn test_fixed_solver_skips_proof_of_work() {
+        let mut config = ClientConfig::default();
+        let response = solve_blocking(
+            crate::client::challenge::generate_challenge(0, "https://example.com"),
+            SolveConfig::new(&config, false)
+        ).unwrap();
+        config.set_solver(Arc::new(FixedSolver::new(response.clone())));
+
+        // Far too hard to solve within the test's lifetime.
+        let challenge = crate::client::challenge::generate_challenge(64, "https://example.com");
+        let solution = solve_challenge(challenge, &config, true, None).await.unwrap();
+
+        assert_eq!(serde_json::to_value(&solution).unwrap(), serde_json::to_value(&response).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn test_solve_challenge_local_uses_attached_solver() {
+        let mut config = ClientConfig::default();
+        let response = solve_blocking(
+            crate::client::challenge::generate_challenge(0, "https://example.com"),
+            SolveConfig::new(&config, false)
+        ).unwrap();
+        config.set_solver(Arc::new(FixedSolver::new(response.clone())));
+
+        let challenge = crate::client::challenge::generate_challenge(64, "https://example.com");
+        let solution = futures::executor::block_on(solve_challenge_local(challenge, &config)).unwrap();
+
+        assert_eq!(serde_json::to_value(&solution).unwrap(), serde_json::to_value(&response).unwrap());
+    }
+
+    #[test]
+    fn test_charge_cpu_budget_records_search_time() {
+        let budget = Arc::new(CpuBudget::unlimited());
+
+        let result = charge_cpu_budget(Some(Arc::clone(&budget)), || {
+            std::thread::sleep(Duration::from_millis(20));
+            7
+        });
+
+        assert_eq!(result, 7);
+        assert!(budget.cpu_seconds_used() >= 0.02);
+    }
+
+    #[test]
+    fn test_most_common_prefers_earliest_on_tie() {
+        let failures = vec!["a".to_string(), "b".to_string(), "b".to_string(), "a".to_string()];
+        assert_eq!(most_common(&failures), Some(("a", 2)));
+        assert_eq!(most_common(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn test_dropping_workers_aborts_pending_tasks() {
+        let state = Arc::new(SolveState::new(Instant::now(), 1, None));
+        let handle: JoinHandle<ResultHandler<IronShieldChallengeResponse>> = tokio::spawn(future::pending());
+        let abort_handle = handle.abort_handle();
+
+        let observer = Arc::new(RecordingObserver::default());
+        drop(WorkerHandles { handles: vec![(3, handle)], state: Arc::clone(&state), observer: Some(observer.clone()) });
+        assert_eq!(*observer.stops.lock().unwrap(), vec![(3, StopReason::Cancelled)]);
+        let aborted = tokio::time::timeout(Duration::from_secs(1), async {
+            while !abort_handle.is_finished() {
+                tokio::task::yield_now().await;
+            }
+        }).await;
+
+        assert!(aborted.is_ok());
+        assert!(state.finished.load(Ordering::Relaxed));
+    }
+
+    /// Records the request ID attached to each start and stop.
+    #[derive(Default)]
+    struct ContextObserver {
+        start_ids:   Mutex<Vec<Option<String>>>,
+        request_ids: Mutex<Vec<Option<String>>>,
+    }
+
+    impl ThreadLifecycleObserver for ContextObserver {
+        fn on_start(&self, _thread_id: usize, _offset: u64, _stride: u64) {}
+
+        fn on_start_with_context(&self, context: Option<&SolveContext>, _thread_id: usize, _offset: u64, _stride: u64) {
+            let request_id = context.and_then(|context| context.downcast_ref::<String>()).cloned();
+            self.start_ids.lock().unwrap().push(request_id);
+        }
+
+        fn on_stop(&self, _thread_id: usize, _reason: StopReason) {}
+
+        fn on_stop_with_context(&self, context: Option<&SolveContext>, _thread_id: usize, _reason: StopReason) {
+            let request_id = context.and_then(|context| context.downcast_ref::<String>()).cloned();
+            self.request_ids.lock().unwrap().push(request_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stop_callbacks_receive_context() {
+        let mut state = SolveState::new(Instant::now(), 1, None);
+        state.context = Some(Arc::new("request-42".to_string()));
+        let handle: JoinHandle<ResultHandler<IronShieldChallengeResponse>> = tokio::spawn(future::pending());
+
+        let observer = Arc::new(ContextObserver::default());
+        drop(WorkerHandles { handles: vec![(0, handle)], state: Arc::new(state), observer: Some(observer.clone()) });
+
+        assert_eq!(*observer.request_ids.lock().unwrap(), vec![Some("request-42".to_string())]);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn test_start_callbacks_receive_context() {
+        let observer = Arc::new(ContextObserver::default());
+        let mut config = ClientConfig::default();
+        config.num_threads = Some(2);
+        config.set_thread_observer(observer.clone());
+
+        let challenge = crate::client::challenge::generate_challenge(4, "https://example.com");
+        let context: SolveContext = Arc::new("request-7".to_string());
+        assert!(solve_challenge_with_context(challenge, &config, true, None, context).await.is_ok());
+
+        // Workers aborted before they start never call `on_start`.
+        let start_ids = observer.start_ids.lock().unwrap();
+        assert!(!start_ids.is_empty());
+        assert!(start_ids.iter().all(|id| id.as_deref() == Some("request-7")));
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn test_check_difficulty() {
+        let challenge = crate::client::challenge::generate_challenge(20, "https://example.com");
+        let mut config = ClientConfig::default();
+        assert!(check_difficulty(&challenge, &config).is_ok());
+
+        config.max_difficulty_bits = Some(20);
+        assert!(check_difficulty(&challenge, &config).is_ok());
+
+        config.max_difficulty_bits = Some(19);
+        assert!(matches!(check_difficulty(&challenge, &config), Err(ErrorHandler::ChallengeSolvingError(_))));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn test_solve_with_custom_stack_size() {
+        let mut config = ClientConfig::default();
+        config.solver_stack_size = Some(4 * 1024 * 1024);
+        config.num_threads = Some(2);
+
+        let challenge = crate::client::challenge::generate_challenge(8, "https://example.com");
+        assert!(solve_challenge(challenge.clone(), &config, true, None).await.is_ok());
+        assert!(solve_challenge(challenge, &config, false, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn test_solve_resumable_suspends_and_resumes() {
+        let mut config = ClientConfig::default();
+        config.num_threads = Some(2);
+
+        // A deadline already passed suspends at once; the workers finish shortly after.
+        let challenge = crate::client::challenge::generate_challenge(20, "https://example.com");
+        let suspended = solve_resumable(challenge, &config, true, 0, Instant::now()).await.unwrap();
+        assert!(matches!(suspended, ResumableSolve::Suspended { .. }));
+
+        let easy = crate::client::challenge::generate_challenge(4, "https://example.com");
+        let deadline = Instant::now() + Duration::from_secs(30);
+        match solve_resumable(easy, &config, true, 1_000_000, deadline).await.unwrap() {
+            ResumableSolve::Solved(solution, _) => assert!(solution.solution >= 1_000_000),
+            ResumableSolve::Suspended { .. }    => panic!("an easy challenge should be solved"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spawn_with_stack_propagates_panics() {
+        let handle = spawn_with_stack(1024 * 1024, || -> ResultHandler<()> { panic!("core exploded") });
+        assert!(handle.await.unwrap_err().is_panic());
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn test_solve_blocking_multithreaded() {
+        let challenge = crate::client::challenge::generate_challenge(8, "https://example.com");
+        let solve_config = SolveConfig {
+            thread_count:        4,
+            use_multithreaded:   true,
+            difficulty_override: None,
+            stack_size:          None,
+        };
+
+        assert!(solve_blocking(challenge, solve_config).is_ok());
+    }
+
+    #[tokio::test]
+    #[cfg(all(feature = "rayon", feature = "test-util"))]
+    async fn test_solve_on_pool() {
+        let challenge = crate::client::challenge::generate_challenge(8, "https://example.com");
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+
+        assert!(solve_on_pool(challenge, &ClientConfig::default(), &pool).await.is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn test_leading_zero_target() {
+        assert_eq!(leading_zero_target(0), [0xFF; 32]);
+        assert_eq!(leading_zero_target(256), [0x00; 32]);
+
+        let target = leading_zero_target(12);
+        assert_eq!(&target[..3], &[0x00, 0x0F, 0xFF]);
+    }
+}
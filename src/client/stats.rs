@@ -0,0 +1,197 @@
+//! # Solve statistics and formatting helpers.
+
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use std::fmt;
+
+/// Statistics for a single worker thread of a solve.
+///
+/// * `thread_id`: Index of the worker thread.
+/// * `attempts`:  Attempts made by this thread.
+/// * `hash_rate`: Attempts per second for this thread.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadStats {
+    pub thread_id: usize,
+    pub attempts:  u64,
+    pub hash_rate: u64,
+}
+
+/// Statistics for a completed solve.
+///
+/// `total_attempts` is an estimate rather than an exact count.
+/// Single-threaded solves derive it from the winning nonce,
+/// which assumes the core searches nonces in order from 0.
+/// Multithreaded solves sum the attempts reported in progress
+/// batches, so work done since each thread's last report is
+/// not counted and the total is a lower bound.
+///
+/// * `thread_count`:   Number of worker threads used.
+/// * `total_attempts`: Attempts made across all threads.
+/// * `elapsed_ms`:     Wall-clock time spent solving in
+///                     milliseconds.
+/// * `hash_rate`:      Aggregate attempts per second.
+/// * `threads`:        Per-thread breakdown, empty for
+///                     single-threaded solves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolveStats {
+    pub thread_count:   usize,
+    pub total_attempts: u64,
+    pub elapsed_ms:     u64,
+    pub hash_rate:      u64,
+    pub threads:        Vec<ThreadStats>,
+}
+
+impl SolveStats {
+    /// # Arguments
+    /// * `thread_count`:   Number of worker threads used.
+    /// * `total_attempts`: Attempts made across all threads.
+    /// * `elapsed_ms`:     Time spent solving in milliseconds.
+    /// * `threads`:        Per-thread breakdown.
+    ///
+    /// # Returns
+    /// * `Self`: Statistics with the aggregate hash rate
+    ///           derived from the attempts and elapsed time.
+    pub fn new(
+        thread_count:   usize,
+        total_attempts: u64,
+        elapsed_ms:     u64,
+        threads:        Vec<ThreadStats>,
+    ) -> Self {
+        Self {
+            thread_count,
+            total_attempts,
+            elapsed_ms,
+            hash_rate: hash_rate(total_attempts, elapsed_ms),
+            threads,
+        }
+    }
+
+    /// # Returns
+    /// * `ClientMetrics`: The subset of these statistics
+    ///                    reported to the API.
+    pub fn client_metrics(&self) -> ClientMetrics {
+        ClientMetrics {
+            thread_count:     self.thread_count,
+            hash_rate:        self.hash_rate,
+            solve_elapsed_ms: self.elapsed_ms,
+        }
+    }
+}
+
+/// Solve metadata sent as `client_metrics` with a solution
+/// when `ClientConfig::report_client_metrics` is set, so the
+/// API can calibrate difficulty per client.
+///
+/// * `thread_count`:     Number of worker threads used.
+/// * `hash_rate`:        Aggregate attempts per second.
+/// * `solve_elapsed_ms`: Time spent solving in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientMetrics {
+    pub thread_count:     usize,
+    pub hash_rate:        u64,
+    pub solve_elapsed_ms: u64,
+}
+
+impl fmt::Display for SolveStats {
+    /// Formats a one-line summary, e.g.
+    /// `1,234,567 attempts in 1.50s across 4 threads (823,044 h/s)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} attempts in {:.2}s across {} thread{} ({} h/s)",
+            format_number_with_commas(self.total_attempts),
+            self.elapsed_ms as f64 / 1000.0,
+            self.thread_count,
+            if self.thread_count == 1 { "" } else { "s" },
+            format_number_with_commas(self.hash_rate),
+        )
+    }
+}
+
+/// # Arguments
+/// * `attempts`:   Number of attempts made.
+/// * `elapsed_ms`: Time taken in milliseconds.
+///
+/// # Returns
+/// * `u64`: Attempts per second. If solved instantly,
+///          assumes 1ms.
+pub fn hash_rate(attempts: u64, elapsed_ms: u64) -> u64 {
+    ((attempts as u128 * 1000) / std::cmp::max(1, elapsed_ms) as u128) as u64
+}
+
+/// Formats a number with thousands separators.
+///
+/// # Arguments
+/// * `number`: The number to format.
+///
+/// # Returns
+/// * `String`: The formatted number, e.g. `1,234,567`.
+///
+/// # Example
+/// ```
+/// use ironshield::client::stats::format_number_with_commas;
+///
+/// assert_eq!(format_number_with_commas(1234567), "1,234,567");
+/// ```
+pub fn format_number_with_commas(number: u64) -> String {
+    let digits: String = number.to_string();
+    let mut formatted: String = String::with_capacity(digits.len() + digits.len() / 3);
+
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+
+    formatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_number_with_commas() {
+        assert_eq!(format_number_with_commas(0), "0");
+        assert_eq!(format_number_with_commas(999), "999");
+        assert_eq!(format_number_with_commas(1000), "1,000");
+        assert_eq!(format_number_with_commas(123456789), "123,456,789");
+    }
+
+    #[test]
+    fn test_solve_stats_display() {
+        let stats = SolveStats::new(4, 1_500_000, 1500, Vec::new());
+        assert_eq!(stats.to_string(), "1,500,000 attempts in 1.50s across 4 threads (1,000,000 h/s)");
+    }
+
+    #[test]
+    fn test_solve_stats_serde_round_trip() {
+        let stats = SolveStats::new(2, 10, 5, vec![
+            ThreadStats { thread_id: 0, attempts: 6, hash_rate: 1200 },
+            ThreadStats { thread_id: 1, attempts: 4, hash_rate: 800 },
+        ]);
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["total_attempts"], 10);
+        assert_eq!(json["threads"][1]["thread_id"], 1);
+
+        let parsed: SolveStats = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, stats);
+    }
+
+    #[test]
+    fn test_client_metrics() {
+        let stats = SolveStats::new(4, 1_500_000, 1500, Vec::new());
+
+        let json = serde_json::to_value(stats.client_metrics()).unwrap();
+        assert_eq!(json, serde_json::json!({
+            "thread_count":     4,
+            "hash_rate":        1_000_000,
+            "solve_elapsed_ms": 1500,
+        }));
+    }
+}
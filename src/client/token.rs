@@ -0,0 +1,335 @@
+//! # Token lifetime helpers.
+
+use ironshield_types::{
+    chrono::{
+        DateTime,
+        Utc
+    },
+    IronShieldToken
+};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use crate::client::clock::{
+    Clock,
+    SystemClock
+};
+use crate::client::config::ClientConfig;
+use crate::client::request::IronShieldClient;
+use crate::client::validate::validate_challenge;
+use crate::handler::result::ResultHandler;
+
+use std::sync::{
+    Arc,
+    atomic::{
+        AtomicU32,
+        Ordering
+    }
+};
+use std::time::Duration;
+
+/// How long before expiry a token is proactively refreshed.
+const REFRESH_MARGIN: Duration = Duration::from_secs(30);
+/// Shortest wait before refreshing a token that is still valid.
+const MIN_REFRESH_DELAY: Duration = Duration::from_secs(1);
+/// Delay before retrying the first failed refresh.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Upper bound on the delay between failed refreshes.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A token for one endpoint that is kept fresh in the
+/// background for long-lived consumers.
+///
+/// A background task re-validates `REFRESH_MARGIN` before the
+/// current token expires, or halfway through the remaining
+/// lifetime of shorter-lived tokens, backing off exponentially while
+/// re-validation fails. The task stops when this value is
+/// dropped. Must be created inside a tokio runtime.
+///
+/// # Example
+/// ```no_run
+/// use std::sync::Arc;
+/// use ironshield::client::config::ClientConfig;
+/// use ironshield::client::request::IronShieldClient;
+/// use ironshield::client::token::ManagedToken;
+///
+/// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = ClientConfig::default();
+/// let client = Arc::new(IronShieldClient::new(config.clone())?);
+/// let managed = ManagedToken::new(client, config, "https://example.com/stream", true);
+///
+/// let token = managed.current().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ManagedToken {
+    state:     Arc<TokenState>,
+    refresher: JoinHandle<()>,
+}
+
+/// State shared between a `ManagedToken` and its background
+/// refresh task.
+struct TokenState {
+    client:            Arc<IronShieldClient>,
+    config:            ClientConfig,
+    endpoint:          String,
+    use_multithreaded: bool,
+    token:             Mutex<Option<IronShieldToken>>,
+    failures:          AtomicU32,
+}
+
+impl TokenState {
+    /// Runs a full validation, tracking consecutive failures.
+    ///
+    /// # Returns
+    /// * `ResultHandler<IronShieldToken>`: A fresh token or the
+    ///                                     validation error.
+    async fn validate(&self) -> ResultHandler<IronShieldToken> {
+        let result = validate_challenge(
+            &self.client,
+            &self.config,
+            &self.endpoint,
+            self.use_multithreaded,
+        ).await;
+
+        match &result {
+            Ok(_)  => self.failures.store(0, Ordering::Relaxed),
+            Err(_) => { self.failures.fetch_add(1, Ordering::Relaxed); },
+        }
+
+        result
+    }
+}
+
+impl ManagedToken {
+    /// Creates a managed token and starts refreshing it in
+    /// the background.
+    ///
+    /// # Arguments
+    /// * `client`:            Client used to fetch and submit.
+    /// * `config`:            The client configuration.
+    /// * `endpoint`:          The protected endpoint URL.
+    /// * `use_multithreaded`: Whether to use multithreaded solving.
+    ///
+    /// # Returns
+    /// * `Self`: The managed token.
+    pub fn new(
+        client:            Arc<IronShieldClient>,
+        config:            ClientConfig,
+        endpoint:          &str,
+        use_multithreaded: bool,
+    ) -> Self {
+        let state = Arc::new(TokenState {
+            client,
+            config,
+            endpoint: endpoint.to_string(),
+            use_multithreaded,
+            token:    Mutex::new(None),
+            failures: AtomicU32::new(0),
+        });
+        let refresher = tokio::spawn(refresh_loop(Arc::clone(&state)));
+
+        Self { state, refresher }
+    }
+
+    /// Returns an unexpired token, validating inline only if
+    /// no valid token is available yet. Waits if a refresh
+    /// of an expired token is in flight.
+    ///
+    /// # Returns
+    /// * `ResultHandler<IronShieldToken>`: A fresh token or the
+    ///                                     validation error.
+    pub async fn current(&self) -> ResultHandler<IronShieldToken> {
+        let mut token = self.state.token.lock().await;
+
+        let clock: &dyn Clock = self.state.config.clock();
+
+        if let Some(current) = token.as_ref().filter(|token| !token_is_expired_at(token, clock)) {
+            return Ok(current.clone());
+        }
+
+        let fresh: IronShieldToken = self.state.validate().await?;
+        *token = Some(fresh.clone());
+
+        Ok(fresh)
+    }
+
+    /// # Returns
+    /// * `u32`: Number of validations that failed in a row.
+    pub fn consecutive_failures(&self) -> u32 {
+        self.state.failures.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for ManagedToken {
+    fn drop(&mut self) {
+        self.refresher.abort();
+    }
+}
+
+/// Keeps the token fresh until aborted.
+///
+/// # Arguments
+/// * `state`: State shared with the `ManagedToken`.
+async fn refresh_loop(state: Arc<TokenState>) {
+    let mut backoff: Duration = INITIAL_BACKOFF;
+
+    loop {
+        let wait: Duration = state.token
+            .lock()
+            .await
+            .as_ref()
+            .map(|token| time_until_refresh(token, state.config.clock()))
+            .unwrap_or(Duration::ZERO);
+
+        tokio::time::sleep(wait).await;
+
+        let mut token = state.token.lock().await;
+        let result = if token.as_ref().is_none_or(|token| token_is_expired_at(token, state.config.clock())) {
+            // Callers have to wait for a fresh token anyway.
+            state.validate().await
+        } else {
+            // Keep serving the current token while refreshing.
+            drop(token);
+            let result = state.validate().await;
+            token = state.token.lock().await;
+            result
+        };
+
+        match result {
+            Ok(fresh) => {
+                *token = Some(fresh);
+                backoff = INITIAL_BACKOFF;
+            },
+            Err(e) => {
+                drop(token);
+
+                if state.config.verbose {
+                    eprintln!("Token refresh for '{}' failed: {}", state.endpoint, e);
+                }
+
+                tokio::time::sleep(backoff).await;
+                backoff = std::cmp::min(backoff * 2, MAX_BACKOFF);
+            }
+        }
+    }
+}
+
+/// When a token stops being valid.
+///
+/// # Arguments
+/// * `token`: The token to inspect.
+///
+/// # Returns
+/// * `DateTime<Utc>`: The end of the token's validity,
+///                    clamped to the representable range.
+pub fn token_expiry(token: &IronShieldToken) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(token.valid_for).unwrap_or(
+        if token.valid_for > 0 { DateTime::<Utc>::MAX_UTC } else { DateTime::<Utc>::MIN_UTC }
+    )
+}
+
+/// Whether a token can no longer be presented.
+///
+/// # Arguments
+/// * `token`: The token to check.
+///
+/// # Returns
+/// * `bool`: `true` if the token's validity has ended.
+pub fn token_is_expired(token: &IronShieldToken) -> bool {
+    token_is_expired_at(token, &SystemClock)
+}
+
+/// Like `token_is_expired`, reading the time from `clock`.
+///
+/// # Arguments
+/// * `token`: The token to check.
+/// * `clock`: Source of the current time.
+///
+/// # Returns
+/// * `bool`: `true` if the token's validity has ended
+///           according to `clock`.
+pub fn token_is_expired_at(token: &IronShieldToken, clock: &dyn Clock) -> bool {
+    expired_at(token.valid_for, clock.now_millis())
+}
+
+/// # Arguments
+/// * `valid_for`: End of validity as Unix epoch milliseconds.
+/// * `now`:       Current time as Unix epoch milliseconds.
+///
+/// # Returns
+/// * `bool`: `true` once `now` has reached `valid_for`. A
+///           token is not valid at the instant it expires.
+fn expired_at(valid_for: i64, now: i64) -> bool {
+    valid_for <= now
+}
+
+/// # Arguments
+/// * `token`: The current token.
+/// * `clock`: Source of the current time.
+///
+/// # Returns
+/// * `Duration`: Time until the token should be refreshed:
+///               `REFRESH_MARGIN` before it expires, but no
+///               sooner than halfway through its remaining
+///               lifetime or `MIN_REFRESH_DELAY`, so tokens
+///               shorter-lived than the margin are not
+///               refreshed back to back. Zero once expired.
+fn time_until_refresh(token: &IronShieldToken, clock: &dyn Clock) -> Duration {
+    let remaining: i64 = token.valid_for - clock.now_millis();
+
+    if remaining <= 0 {
+        return Duration::ZERO;
+    }
+
+    let remaining: Duration = Duration::from_millis(remaining as u64);
+
+    remaining
+        .saturating_sub(REFRESH_MARGIN)
+        .max(remaining / 2)
+        .max(MIN_REFRESH_DELAY.min(remaining))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::clock::FixedClock;
+
+    #[test]
+    fn test_expired_at_boundary() {
+        assert!(!expired_at(1_000, 999));
+        assert!(expired_at(1_000, 1_000));
+        assert!(expired_at(1_000, 1_001));
+    }
+
+    #[test]
+    fn test_token_is_expired_at_edges() {
+        let token = IronShieldToken::new([0; 64], 10_000, [0; 32], [0; 64]);
+        let clock = FixedClock::new(9_999);
+
+        // About to expire: still valid one millisecond before.
+        assert!(!token_is_expired_at(&token, &clock));
+
+        // Just expired: invalid at the instant of expiry and after.
+        clock.advance(Duration::from_millis(1));
+        assert!(token_is_expired_at(&token, &clock));
+        clock.set(10_001);
+        assert!(token_is_expired_at(&token, &clock));
+    }
+
+    #[test]
+    fn test_time_until_refresh() {
+        let clock = FixedClock::new(1_000_000);
+        let token = |lifetime_ms: i64| IronShieldToken::new([0; 64], 1_000_000 + lifetime_ms, [0; 32], [0; 64]);
+
+        // Long-lived tokens are refreshed `REFRESH_MARGIN` before expiry.
+        assert_eq!(time_until_refresh(&token(120_000), &clock), Duration::from_secs(90));
+        // Shorter-lived tokens are refreshed halfway through.
+        assert_eq!(time_until_refresh(&token(10_000), &clock), Duration::from_secs(5));
+        // But never sooner than the minimum delay while valid.
+        assert_eq!(time_until_refresh(&token(1_500), &clock), MIN_REFRESH_DELAY);
+        assert_eq!(time_until_refresh(&token(500), &clock), Duration::from_millis(500));
+        assert_eq!(time_until_refresh(&token(0), &clock), Duration::ZERO);
+        assert_eq!(time_until_refresh(&token(-1_000), &clock), Duration::ZERO);
+    }
+}
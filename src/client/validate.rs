@@ -1,18 +1,149 @@
-use ironshield_types::IronShieldToken;
+use ironshield_types::{
+    IronShieldChallenge,
+    IronShieldChallengeResponse,
+    IronShieldToken
+};
 
-use crate::client::solve::solve_challenge;
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::client::challenge::{
+    challenge_is_fresh_at,
+    difficulty_bits
+};
+use crate::client::solve::{
+    solve_challenge_with_stats,
+    solve_resumable,
+    solve_with_deadline_and_stats,
+    ResumableSolve,
+    SolveStrategy
+};
 use crate::client::config::ClientConfig;
 use crate::client::request::IronShieldClient;
+use crate::client::stats::SolveStats;
+use crate::client::retry::{
+    RetryBudget,
+    SubmitAction,
+    SubmitRetryPolicy
+};
 
+use crate::handler::error::{
+    ErrorHandler,
+    SolveError
+};
 use crate::handler::result::ResultHandler;
 
+use std::time::{
+    Duration,
+    Instant
+};
+
+/// Time spent in each leg of a validation.
+///
+/// * `fetch`:  Fetching the challenge.
+/// * `solve`:  Solving the challenge.
+/// * `submit`: Submitting the solution.
+/// * `total`:  The whole validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimingBreakdown {
+    pub fetch:  Duration,
+    pub solve:  Duration,
+    pub submit: Duration,
+    pub total:  Duration,
+}
+
+/// A validation suspended mid-solve, to be persisted and
+/// continued later with `resume_validation`, e.g. in the
+/// next invocation of a function with a hard time limit.
+///
+/// * `endpoint`:   The protected endpoint.
+/// * `challenge`:  The challenge being solved.
+/// * `next_nonce`: First nonce not yet searched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationState {
+    pub endpoint:   String,
+    pub challenge:  IronShieldChallenge,
+    pub next_nonce: u64,
+}
+
+/// Result of a validation that may be suspended.
+#[derive(Debug, Clone)]
+pub enum ValidationOutcome {
+    /// The validation finished with a token.
+    Completed(IronShieldToken),
+    /// The deadline passed mid-solve.
+    Suspended(ValidationState),
+}
+
+/// How a validation ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlowOutcome {
+    Success,
+    Failure,
+}
+
+/// Machine-readable record of one validation, produced by
+/// `validate_challenge_report`, e.g. for CI to assert on.
+///
+/// Legs that were not reached are `None`. The solve
+/// statistics are those of the first solve; a re-solve
+/// after an expired submission is counted in `retries`.
+///
+/// * `endpoint`:        The protected endpoint.
+/// * `difficulty_bits`: Leading zero bits the challenge
+///                      required.
+/// * `strategy`:        How the challenge was solved.
+/// * `thread_count`:    Threads used to solve.
+/// * `fetch_ms`:        Time spent fetching the challenge.
+/// * `solve_ms`:        Time spent solving it.
+/// * `submit_ms`:       Time spent submitting, including
+///                      retries.
+/// * `total_ms`:        Time spent on the whole validation.
+/// * `attempts`:        Hashes computed by the solve.
+/// * `hash_rate`:       Attempts per second of the solve.
+/// * `retries`:         Submissions after the first.
+/// * `outcome`:         Whether a token was obtained.
+/// * `token`:           The token, on success.
+/// * `error`:           The error, on failure.
+#[derive(Debug, Clone, Serialize)]
+pub struct FlowReport {
+    pub endpoint:        String,
+    pub difficulty_bits: Option<u32>,
+    pub strategy:        SolveStrategy,
+    pub thread_count:    Option<usize>,
+    pub fetch_ms:        Option<u64>,
+    pub solve_ms:        Option<u64>,
+    pub submit_ms:       Option<u64>,
+    pub total_ms:        u64,
+    pub attempts:        Option<u64>,
+    pub hash_rate:       Option<u64>,
+    pub retries:         u32,
+    pub outcome:         FlowOutcome,
+    pub token:           Option<IronShieldToken>,
+    pub error:           Option<String>,
+}
+
 /// Fetches a challenge, solves it, and submits the solution for validation.
 ///
+/// Failed submissions are retried per
+/// `ClientConfig::submit_retry_policy`, with retries of the
+/// fetch and submission together bounded by
+/// `RetryPolicy::per_validation_budget`. With a
+/// `ClientConfig::token_cache` attached, a cached token for
+/// `endpoint` that is not within
+/// `ClientConfig::token_refresh_margin` of expiry is
+/// returned without fetching or solving anything.
+///
 /// # Arguments
 /// * `client`:          An instance of `IronShieldClient` to communicate with the API.
 /// * `config`:          The client configuration.
 /// * `endpoint`:        The protected endpoint URL to get a challenge for.
-/// * `use_multithread`: A boolean indicating whether to use multithreaded solving.
+/// * `use_multithread`: A boolean indicating whether to use multithreaded solving,
+///                      unless `ClientConfig::endpoint_overrides` has an
+///                      entry for `endpoint`.
 ///
 /// # Returns
 /// * `ResultHandler<IronShieldToken>`: An `IronShieldToken` if successful,
@@ -23,9 +154,589 @@ pub async fn validate_challenge(
     endpoint:        &str,
     use_multithread: bool,
 ) -> ResultHandler<IronShieldToken> {
+    if let Some(token) = cached_token(config, endpoint) {
+        return Ok(token);
+    }
+
+    let use_multithread: bool = multithread_for(config, endpoint, use_multithread);
+    let budget: RetryBudget = RetryBudget::new(&config.retry_policy);
+
+    let challenge = client.fetch_challenge_within(endpoint, &budget).await?;
+    let  solution = solve_challenge_with_stats(challenge, config, use_multithread, None).await?;
+    let     token = submit_with_retry(client, config, endpoint, solution, use_multithread, &budget, &mut 0).await?;
+
+    cache_token(config, endpoint, &token);
+
+    Ok(token)
+}
+
+/// # Arguments
+/// * `config`:   The client configuration.
+/// * `endpoint`: The protected endpoint URL.
+///
+/// # Returns
+/// * `Option<IronShieldToken>`: A token from `ClientConfig::token_cache`
+///                              that is not within
+///                              `ClientConfig::token_refresh_margin`
+///                              of expiry, if any.
+fn cached_token(config: &ClientConfig, endpoint: &str) -> Option<IronShieldToken> {
+    config
+        .token_cache
+        .as_ref()
+        .and_then(|cache| cache.get(endpoint, config.clock(), config.token_refresh_margin))
+}
+
+/// Stores a token in `ClientConfig::token_cache`, if one is
+/// attached.
+///
+/// # Arguments
+/// * `config`:   The client configuration.
+/// * `endpoint`: The protected endpoint URL.
+/// * `token`:    The token obtained for `endpoint`.
+fn cache_token(config: &ClientConfig, endpoint: &str, token: &IronShieldToken) {
+    if let Some(cache) = &config.token_cache {
+        cache.insert(endpoint, token.clone(), config.clock());
+    }
+}
+
+/// Solves and submits a challenge obtained earlier, e.g.
+/// from a prefetch buffer, fetching a new one instead if it
+/// is older than `ClientConfig::max_challenge_age`.
+/// Failed submissions are retried per
+/// `ClientConfig::submit_retry_policy`.
+///
+/// # Arguments
+/// * `client`:          An instance of `IronShieldClient` to communicate with the API.
+/// * `config`:          The client configuration.
+/// * `endpoint`:        The protected endpoint URL the challenge is for.
+/// * `challenge`:       The possibly stale challenge.
+/// * `use_multithread`: A boolean indicating whether to use multithreaded solving,
+///                      unless `ClientConfig::endpoint_overrides` has an
+///                      entry for `endpoint`.
+///
+/// # Returns
+/// * `ResultHandler<IronShieldToken>`: An `IronShieldToken` if successful,
+///                                     or an error.
+pub async fn validate_prefetched_challenge(
+    client:          &IronShieldClient,
+    config:          &ClientConfig,
+    endpoint:        &str,
+    challenge:       IronShieldChallenge,
+    use_multithread: bool,
+) -> ResultHandler<IronShieldToken> {
+    let use_multithread: bool = multithread_for(config, endpoint, use_multithread);
+    let budget: RetryBudget = RetryBudget::new(&config.retry_policy);
+
+    let challenge = if challenge_is_fresh_at(&challenge, config.max_challenge_age, config.clock()) {
+        challenge
+    } else {
+        if config.verbose {
+            eprintln!("Discarding stale challenge for '{}', fetching a fresh one.", endpoint);
+        }
+
+        client.fetch_challenge_within(endpoint, &budget).await?
+    };
+
+    let solution = solve_challenge_with_stats(challenge, config, use_multithread, None).await?;
+    let    token = submit_with_retry(client, config, endpoint, solution, use_multithread, &budget, &mut 0).await?;
+
+    Ok(token)
+}
+
+/// Submits a solution, retrying per
+/// `ClientConfig::submit_retry_policy`: the same solution
+/// after a transient failure, and a freshly fetched and
+/// solved challenge after an expiry.
+///
+/// # Arguments
+/// * `client`:          The client to submit with.
+/// * `config`:          The client configuration.
+/// * `endpoint`:        The protected endpoint the solution is for.
+/// * `solution`:        The solution to submit first, with
+///                      the statistics of its solve.
+/// * `use_multithread`: Whether re-solves are multithreaded.
+/// * `budget`:          Retries left for the validation.
+/// * `retries`:         Incremented for each submission
+///                      after the first.
+///
+/// # Returns
+/// * `ResultHandler<IronShieldToken>`: The token, or the error
+///                                     of the last submission.
+async fn submit_with_retry(
+    client:          &IronShieldClient,
+    config:          &ClientConfig,
+    endpoint:        &str,
+    mut solution:    (IronShieldChallengeResponse, SolveStats),
+    use_multithread: bool,
+    budget:          &RetryBudget,
+    retries:         &mut u32,
+) -> ResultHandler<IronShieldToken> {
+    let policy: SubmitRetryPolicy = config.submit_retry_policy;
+    let mut attempt: u32 = 1;
+
+    loop {
+        let (response, stats) = &solution;
+        let error: ErrorHandler = match client.submit_solution_with_stats(response, stats).await {
+            Ok(token) => return Ok(token),
+            Err(e)    => e,
+        };
+
+        let action: SubmitAction = match policy.action_for(&error, attempt) {
+            SubmitAction::Fail => SubmitAction::Fail,
+            _ if !budget.try_spend() => {
+                if config.verbose {
+                    eprintln!("Submission failed ({}) and the validation's retry budget is spent.", error);
+                }
+
+                SubmitAction::Fail
+            },
+            action => action,
+        };
+
+        match action {
+            SubmitAction::Resubmit => {
+                if config.verbose {
+                    eprintln!("Submission failed ({}), resubmitting ({} of {}).", error, attempt + 1, policy.max_attempts);
+                }
+
+                tokio::time::sleep(policy.backoff).await;
+            },
+            SubmitAction::Resolve => {
+                if config.verbose {
+                    eprintln!("Challenge expired before submission, solving a new one ({} of {}).", attempt + 1, policy.max_attempts);
+                }
+
+                let challenge = client.fetch_challenge_within(endpoint, budget).await?;
+                solution = solve_challenge_with_stats(challenge, config, use_multithread, None).await?;
+            },
+            SubmitAction::Fail => return Err(error),
+        }
+
+        attempt += 1;
+        *retries += 1;
+    }
+}
+
+/// Validates like `validate_challenge`, also recording
+/// everything that happened in a `FlowReport`, which is
+/// produced whether or not validation succeeds. Does not
+/// consult `ClientConfig::token_cache`.
+///
+/// # Arguments
+/// * `client`:          An instance of `IronShieldClient` to communicate with the API.
+/// * `config`:          The client configuration.
+/// * `endpoint`:        The protected endpoint URL to get a challenge for.
+/// * `use_multithread`: A boolean indicating whether to use multithreaded solving,
+///                      unless `ClientConfig::endpoint_overrides` has an
+///                      entry for `endpoint`.
+///
+/// # Returns
+/// * `(ResultHandler<IronShieldToken>, FlowReport)`: The token or
+///                                                   error, and the
+///                                                   report.
+///
+/// # Example
+/// ```no_run
+/// use ironshield::client::config::ClientConfig;
+/// use ironshield::client::request::IronShieldClient;
+/// use ironshield::client::validate::validate_challenge_report;
+///
+/// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = ClientConfig::default();
+/// let client = IronShieldClient::new(config.clone())?;
+///
+/// let (_token, report) = validate_challenge_report(&client, &config, "https://example.com/protected", true).await;
+/// println!("{}", serde_json::to_string_pretty(&report)?);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn validate_challenge_report(
+    client:          &IronShieldClient,
+    config:          &ClientConfig,
+    endpoint:        &str,
+    use_multithread: bool,
+) -> (ResultHandler<IronShieldToken>, FlowReport) {
+    let use_multithread: bool = multithread_for(config, endpoint, use_multithread);
+    let budget: RetryBudget = RetryBudget::new(&config.retry_policy);
+    let start_time: Instant = Instant::now();
+
+    let mut report = FlowReport {
+        endpoint:        endpoint.to_string(),
+        difficulty_bits: None,
+        strategy:        if use_multithread { SolveStrategy::MultiThreaded } else { SolveStrategy::SingleThreaded },
+        thread_count:    None,
+        fetch_ms:        None,
+        solve_ms:        None,
+        submit_ms:       None,
+        total_ms:        0,
+        attempts:        None,
+        hash_rate:       None,
+        retries:         0,
+        outcome:         FlowOutcome::Failure,
+        token:           None,
+        error:           None,
+    };
+
+    let result: ResultHandler<IronShieldToken> = async {
+        let leg_start: Instant = Instant::now();
+        let challenge = client.fetch_challenge_within(endpoint, &budget).await?;
+        report.fetch_ms = Some(elapsed_ms(leg_start));
+        report.difficulty_bits = Some(difficulty_bits(&challenge));
+
+        let leg_start: Instant = Instant::now();
+        let solution = solve_challenge_with_stats(challenge, config, use_multithread, None).await?;
+        report.solve_ms = Some(elapsed_ms(leg_start));
+        report.thread_count = Some(solution.1.thread_count);
+        report.attempts = Some(solution.1.total_attempts);
+        report.hash_rate = Some(solution.1.hash_rate);
+
+        let leg_start: Instant = Instant::now();
+        let result = submit_with_retry(client, config, endpoint, solution, use_multithread, &budget, &mut report.retries).await;
+        report.submit_ms = Some(elapsed_ms(leg_start));
+
+        result
+    }.await;
+
+    report.total_ms = elapsed_ms(start_time);
+    match &result {
+        Ok(token) => {
+            report.outcome = FlowOutcome::Success;
+            report.token = Some(token.clone());
+        },
+        Err(e) => report.error = Some(e.to_string()),
+    }
+
+    (result, report)
+}
+
+/// # Arguments
+/// * `since`: When the measured span started.
+///
+/// # Returns
+/// * `u64`: Milliseconds elapsed since `since`.
+fn elapsed_ms(since: Instant) -> u64 {
+    u64::try_from(since.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Validates like `validate_challenge`, suspending instead
+/// of failing if the solve has not finished by `deadline`.
+///
+/// Suspending returns at `deadline`, but the solver threads
+/// already searching run on in the background until they
+/// find a solution or the process exits, so persist the
+/// state and exit rather than keep the process alive.
+///
+/// # Arguments
+/// * `client`:          An instance of `IronShieldClient` to communicate with the API.
+/// * `config`:          The client configuration.
+/// * `endpoint`:        The protected endpoint URL to get a challenge for.
+/// * `use_multithread`: A boolean indicating whether to use multithreaded solving,
+///                      unless `ClientConfig::endpoint_overrides` has an
+///                      entry for `endpoint`.
+/// * `deadline`:        When to stop solving and suspend.
+///
+/// # Returns
+/// * `ResultHandler<ValidationOutcome>`: The token, the state to
+///                                       resume from, or an error.
+///
+/// # Example
+/// ```no_run
+/// use std::time::{Duration, Instant};
+/// use ironshield::client::config::ClientConfig;
+/// use ironshield::client::request::IronShieldClient;
+/// use ironshield::client::validate::{validate_challenge_resumable, ValidationOutcome};
+///
+/// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = ClientConfig::default();
+/// let client = IronShieldClient::new(config.clone())?;
+/// let deadline = Instant::now() + Duration::from_secs(10);
+///
+/// match validate_challenge_resumable(&client, &config, "https://example.com/protected", true, deadline).await? {
+///     ValidationOutcome::Completed(token) => println!("Validated until {}", token.valid_for),
+///     ValidationOutcome::Suspended(state) => println!("Persist: {}", serde_json::to_string(&state)?),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn validate_challenge_resumable(
+    client:          &IronShieldClient,
+    config:          &ClientConfig,
+    endpoint:        &str,
+    use_multithread: bool,
+    deadline:        Instant,
+) -> ResultHandler<ValidationOutcome> {
     let challenge = client.fetch_challenge(endpoint).await?;
-    let  solution = solve_challenge(challenge, config, use_multithread, None).await?;
-    let     token = client.submit_solution(&solution).await?;
+
+    resume_validation(client, config, ValidationState {
+        endpoint:   endpoint.to_string(),
+        challenge,
+        next_nonce: 0,
+    }, use_multithread, deadline).await
+}
+
+/// Continues a validation suspended by
+/// `validate_challenge_resumable` or an earlier call of this
+/// function, searching from the saved nonce and submitting
+/// once solved. A challenge that expired in the meantime is
+/// replaced with a fresh one, solved from the start.
+///
+/// # Arguments
+/// * `client`:          An instance of `IronShieldClient` to communicate with the API.
+/// * `config`:          The client configuration.
+/// * `state`:           The suspended validation.
+/// * `use_multithread`: A boolean indicating whether to use multithreaded solving,
+///                      unless `ClientConfig::endpoint_overrides` has an
+///                      entry for the endpoint.
+/// * `deadline`:        When to stop solving and suspend again.
+///
+/// # Returns
+/// * `ResultHandler<ValidationOutcome>`: The token, the state to
+///                                       resume from, or an error.
+pub async fn resume_validation(
+    client:          &IronShieldClient,
+    config:          &ClientConfig,
+    state:           ValidationState,
+    use_multithread: bool,
+    deadline:        Instant,
+) -> ResultHandler<ValidationOutcome> {
+    let ValidationState { endpoint, mut challenge, mut next_nonce } = state;
+    let use_multithread: bool = multithread_for(config, &endpoint, use_multithread);
+
+    if challenge.expiration_time <= config.clock().now_millis() {
+        if config.verbose {
+            eprintln!("Suspended challenge for '{}' expired, fetching a fresh one.", endpoint);
+        }
+
+        challenge = client.fetch_challenge(&endpoint).await?;
+        next_nonce = 0;
+    }
+
+    match solve_resumable(challenge.clone(), config, use_multithread, next_nonce, deadline).await? {
+        ResumableSolve::Solved(response, stats) => {
+            let budget: RetryBudget = RetryBudget::new(&config.retry_policy);
+            let token = submit_with_retry(client, config, &endpoint, (response, stats), use_multithread, &budget, &mut 0).await?;
+
+            Ok(ValidationOutcome::Completed(token))
+        },
+        ResumableSolve::Suspended { next_nonce } => Ok(ValidationOutcome::Suspended(ValidationState {
+            endpoint,
+            challenge,
+            next_nonce,
+        })),
+    }
+}
+
+/// Validates like `validate_challenge`, also reporting how
+/// long each leg took.
+///
+/// The submit leg includes any resubmissions and re-solves
+/// made per `ClientConfig::submit_retry_policy`. A token
+/// served from `ClientConfig::token_cache` is reported with
+/// zero-length fetch, solve and submit legs.
+///
+/// # Arguments
+/// * `client`:          An instance of `IronShieldClient` to communicate with the API.
+/// * `config`:          The client configuration.
+/// * `endpoint`:        The protected endpoint URL to get a challenge for.
+/// * `use_multithread`: A boolean indicating whether to use multithreaded solving,
+///                      unless `ClientConfig::endpoint_overrides` has an
+///                      entry for `endpoint`.
+///
+/// # Returns
+/// * `ResultHandler<(IronShieldToken, TimingBreakdown)>`: The token and
+///                                                        per-leg timings
+///                                                        if successful,
+///                                                        or an error.
+pub async fn validate_challenge_timed(
+    client:          &IronShieldClient,
+    config:          &ClientConfig,
+    endpoint:        &str,
+    use_multithread: bool,
+) -> ResultHandler<(IronShieldToken, TimingBreakdown)> {
+    let start_time: Instant = Instant::now();
+
+    if let Some(token) = cached_token(config, endpoint) {
+        let total = start_time.elapsed();
+
+        return Ok((token, TimingBreakdown {
+            fetch:  Duration::ZERO,
+            solve:  Duration::ZERO,
+            submit: Duration::ZERO,
+            total,
+        }));
+    }
+
+    let use_multithread: bool = multithread_for(config, endpoint, use_multithread);
+    let budget: RetryBudget = RetryBudget::new(&config.retry_policy);
+
+    let challenge = client.fetch_challenge_within(endpoint, &budget).await?;
+    let     fetch = start_time.elapsed();
+
+    let  solution = solve_challenge_with_stats(challenge, config, use_multithread, None).await?;
+    let     solve = start_time.elapsed() - fetch;
+
+    let     token = submit_with_retry(client, config, endpoint, solution, use_multithread, &budget, &mut 0).await?;
+    let     total = start_time.elapsed();
+
+    cache_token(config, endpoint, &token);
+
+    Ok((token, TimingBreakdown {
+        fetch,
+        solve,
+        submit: total - fetch - solve,
+        total,
+    }))
+}
+
+/// Validates like `validate_challenge`, with fetch, solve, and
+/// submit together bounded by a single deadline.
+///
+/// The fetch and submit are cut off when the deadline passes,
+/// and the solve is aborted at the deadline. Resubmissions
+/// and re-solves made per `ClientConfig::submit_retry_policy`
+/// count against the same deadline.
+///
+/// # Arguments
+/// * `client`:          An instance of `IronShieldClient` to communicate with the API.
+/// * `config`:          The client configuration.
+/// * `endpoint`:        The protected endpoint URL to get a challenge for.
+/// * `use_multithread`: A boolean indicating whether to use multithreaded solving,
+///                      unless `ClientConfig::endpoint_overrides` has an
+///                      entry for `endpoint`.
+/// * `deadline`:        When the whole validation must be done.
+///
+/// # Returns
+/// * `ResultHandler<IronShieldToken>`: An `IronShieldToken` if successful,
+///                                     `ErrorHandler::TimeoutError` with the
+///                                     total time allowed if the deadline
+///                                     passes, or another error.
+pub async fn validate_challenge_with_deadline(
+    client:          &IronShieldClient,
+    config:          &ClientConfig,
+    endpoint:        &str,
+    use_multithread: bool,
+    deadline:        Instant,
+) -> ResultHandler<IronShieldToken> {
+    if let Some(token) = cached_token(config, endpoint) {
+        return Ok(token);
+    }
+
+    let use_multithread: bool = multithread_for(config, endpoint, use_multithread);
+    let budget: Duration = deadline.saturating_duration_since(Instant::now());
+    let retry_budget: RetryBudget = RetryBudget::new(&config.retry_policy);
+
+    let challenge = within_deadline(deadline, budget, client.fetch_challenge_within(endpoint, &retry_budget)).await?;
+    let  solution = solve_with_deadline_and_stats(challenge, config, use_multithread, deadline)
+        .await
+        .map_err(|e| match e {
+            ErrorHandler::Solve(SolveError::TimedOut { .. }) => ErrorHandler::timeout(budget),
+            e                                                => e,
+        })?;
+    let     token = within_deadline(
+        deadline,
+        budget,
+        submit_with_retry(client, config, endpoint, solution, use_multithread, &retry_budget, &mut 0),
+    ).await?;
+
+    cache_token(config, endpoint, &token);
 
     Ok(token)
-} 
\ No newline at end of file
+}
+
+/// Runs one leg of a validation, failing once `deadline`
+/// passes.
+///
+/// # Arguments
+/// * `deadline`: When the validation must be done.
+/// * `budget`:   Total time the validation was given,
+///               reported in the timeout error.
+/// * `leg`:      The fetch or submit to run.
+///
+/// # Returns
+/// * `ResultHandler<T>`: The leg's result, or
+///                       `ErrorHandler::TimeoutError`.
+async fn within_deadline<T>(
+    deadline: Instant,
+    budget:   Duration,
+    leg:      impl Future<Output = ResultHandler<T>>,
+) -> ResultHandler<T> {
+    tokio::time::timeout_at(deadline.into(), leg)
+        .await
+        .unwrap_or_else(|_| Err(ErrorHandler::timeout(budget)))
+}
+
+/// # Arguments
+/// * `config`:          The client configuration.
+/// * `endpoint`:        The protected endpoint URL.
+/// * `use_multithread`: The caller's choice.
+///
+/// # Returns
+/// * `bool`: Whether to solve multithreaded, honouring
+///           `ClientConfig::endpoint_overrides`.
+fn multithread_for(config: &ClientConfig, endpoint: &str, use_multithread: bool) -> bool {
+    config
+        .strategy_for(endpoint)
+        .map_or(use_multithread, SolveStrategy::is_multithreaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_within_deadline_times_out() {
+        let budget = Duration::from_millis(20);
+        let deadline = Instant::now() + budget;
+
+        let result: ResultHandler<()> = within_deadline(deadline, budget, async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        }).await;
+        assert!(matches!(result, Err(ErrorHandler::TimeoutError { duration }) if duration == budget));
+
+        let result = within_deadline(Instant::now() + budget, budget, async { Ok(7) }).await;
+        assert_eq!(result.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn test_validate_challenge_with_fixed_solver() {
+        use axum::{
+            routing::post,
+            Json,
+            Router
+        };
+        use std::sync::Arc;
+        use crate::client::challenge::generate_challenge;
+        use crate::client::solve::{
+            solve_blocking,
+            FixedSolver,
+            SolveConfig
+        };
+
+        // Far too hard to solve within the test's lifetime.
+        let challenge = generate_challenge(64, "https://example.com/protected");
+        let token = IronShieldToken::new([0; 64], i64::MAX, [0; 32], [0; 64]);
+        let challenge_body = serde_json::json!({ "status": 200, "message": "ok", "challenge": challenge });
+        let token_body = serde_json::json!({ "status": 200, "message": "ok", "token": token });
+
+        let app = Router::new()
+            .route("/request", post(move || async move { Json(challenge_body) }))
+            .route("/response", post(move || async move { Json(token_body) }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let mut config = ClientConfig {
+            api_base_url:   format!("http://{}", addr),
+            allow_insecure: true,
+            ..ClientConfig::default()
+        };
+        let response = solve_blocking(generate_challenge(0, "https://example.com/protected"), SolveConfig::new(&config, false)).unwrap();
+        config.set_solver(Arc::new(FixedSolver::new(response)));
+        let client = IronShieldClient::new_insecure(config.clone()).unwrap();
+
+        let issued = validate_challenge(&client, &config, "https://example.com/protected", true).await.unwrap();
+        assert_eq!(issued.valid_for, i64::MAX);
+    }
+}
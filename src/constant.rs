@@ -1 +1,27 @@
-pub const USER_AGENT: &str = "curl/8.4.0"; 
\ No newline at end of file
+pub const USER_AGENT: &str = "curl/8.4.0";
+
+/// Revision of the IronShield API protocol this client
+/// speaks.
+pub const PROTOCOL_VERSION: &str = "1.0";
+
+/// Header carrying `client_version_string` on every API
+/// request.
+pub const CLIENT_VERSION_HEADER: &str = "X-IronShield-Client";
+
+/// # Returns
+/// * `String`: The crate version, `PROTOCOL_VERSION`, and
+///             `USER_AGENT`, e.g.
+///             `ironshield-rs/0.2.23 (protocol 1.0; curl/8.4.0)`.
+///
+/// # Example
+/// ```
+/// use ironshield::{client_version_string, PROTOCOL_VERSION};
+///
+/// assert!(client_version_string().contains(PROTOCOL_VERSION));
+/// ```
+pub fn client_version_string() -> String {
+    format!(
+        "ironshield-rs/{} (protocol {}; {})",
+        env!("CARGO_PKG_VERSION"), PROTOCOL_VERSION, USER_AGENT
+    )
+}
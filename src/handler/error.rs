@@ -8,6 +8,7 @@ use axum::{
         Response
     },
 };
+use serde::Serialize;
 use thiserror::Error;
 
 use std::time::Duration;
@@ -136,10 +137,24 @@ pub enum ErrorHandler {
     Config(String),
     #[error("Configuration error: {0}")]
     ConfigurationError(String),
+    #[error("Failed to deserialize {context}: {source} (fields present: [{fields}])")]
+    Deserialization {
+        /// What was being parsed and from where.
+        context: String,
+        /// Comma-separated keys of the JSON object that
+        /// was parsed.
+        fields:  String,
+        /// The underlying `serde_json` failure.
+        source:  serde_json::Error
+    },
     #[error("Internal server error")]
     InternalError,
+    #[error("Invalid configuration: {}", join_validation_errors(.0))]
+    InvalidConfig(Vec<ConfigValidationError>),
     #[error("Invalid request format: {0}")]
     InvalidRequest(String),
+    #[error("Invalid solution: {0}")]
+    InvalidSolution(String),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     #[error("Network request failed: {0}")]
@@ -154,6 +169,10 @@ pub enum ErrorHandler {
     RateLimitError(String),
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
+    #[error("Signature verification failed: {0}")]
+    SignatureVerificationError(String),
+    #[error("Solve stopped: {0}")]
+    Solve(#[from] SolveError),
     #[error("Operation timed out after {duration:?}")]
     TimeoutError { duration: Duration },
     #[cfg(feature = "toml")]
@@ -161,6 +180,95 @@ pub enum ErrorHandler {
     Toml(#[from] toml::de::Error),
 }
 
+/// Why a solve ended without a solution.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// Stopped through a `SolveHandle`.
+    #[error("cancelled")]
+    Cancelled,
+    /// A deadline, solve timeout, or hash-rate deadline was
+    /// reached.
+    #[error("timed out after {elapsed:?}")]
+    TimedOut { elapsed: Duration },
+    /// An attempt budget ran out. Not returned by the built-in
+    /// solvers, which cannot bound the work of a search; for
+    /// `Solver` implementations that can.
+    #[error("budget exhausted after {attempts} attempts")]
+    BudgetExhausted { attempts: u64 },
+    /// The solve was dropped, or every worker stopped without
+    /// reporting a failure.
+    #[error("aborted")]
+    Aborted,
+    /// `ironshield_core` failed or a worker thread panicked.
+    #[error("{0}")]
+    CoreFailure(String),
+}
+
+/// Machine-readable reason a configuration field is
+/// invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigErrorCode {
+    /// A required value is empty.
+    Empty,
+    /// A URL does not use HTTPS.
+    InsecureUrl,
+    /// A duration that must be positive is zero.
+    ZeroDuration,
+    /// A thread count is zero.
+    ZeroThreads,
+    /// A number is outside its permitted range.
+    OutOfRange,
+}
+
+/// A configuration field that failed validation, e.g. for
+/// a frontend to highlight the matching form field.
+///
+/// * `field`:   The `ClientConfig` field name.
+/// * `code`:    Why the value is invalid.
+/// * `message`: A human-readable description.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize)]
+#[error("{field}: {message}")]
+pub struct ConfigValidationError {
+    pub field:   &'static str,
+    pub code:    ConfigErrorCode,
+    pub message: String,
+}
+
+impl ConfigValidationError {
+    /// # Arguments
+    /// * `field`:   The invalid field.
+    /// * `code`:    Why it is invalid.
+    /// * `message`: A human-readable description.
+    ///
+    /// # Returns
+    /// * `Self`: The validation error.
+    pub fn new(
+        field:   &'static str,
+        code:    ConfigErrorCode,
+        message: impl Into<String>
+    ) -> Self {
+        Self {
+            field,
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// # Arguments
+/// * `errors`: Validation errors to display.
+///
+/// # Returns
+/// * `String`: The errors separated by `; `.
+fn join_validation_errors(errors: &[ConfigValidationError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
 /// Converts `ErrorHandler` into an `axum::response::Response`.
 ///
 /// This implementation allows `ErrorHandler` to be used
@@ -174,7 +282,7 @@ impl IntoResponse for ErrorHandler {
             ErrorHandler::ProcessingError(message) => {
                 (StatusCode::UNPROCESSABLE_ENTITY, message)
             },
-            ErrorHandler::SerializationError(_) => {
+            ErrorHandler::SerializationError(_) | ErrorHandler::Deserialization { .. } => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Data processing error".to_string())
             },
             ErrorHandler::InternalError => {
@@ -275,6 +383,28 @@ impl ErrorHandler {
         Self::ConfigurationError(message.into())
     }
 
+    /// # Arguments
+    /// * `context`: What was being parsed, e.g.
+    ///              `"challenge from API response"`.
+    /// * `value`:   The JSON that failed to parse.
+    /// * `error`:   The `serde_json` failure.
+    ///
+    /// # Returns
+    /// * `Self`: An `ErrorHandler::Deserialization` listing the
+    ///           keys present in `value`.
+    pub fn deserialization_error(
+        context: impl Into<String>,
+        value:   &serde_json::Value,
+        error:   serde_json::Error
+    ) -> Self {
+        let fields: String = value
+            .as_object()
+            .map(|object| object.keys().map(String::as_str).collect::<Vec<&str>>().join(", "))
+            .unwrap_or_default();
+
+        Self::Deserialization { context: context.into(), fields, source: error }
+    }
+
     /// # Arguments
     /// * `error`: A `reqwest` network error.
     ///
@@ -337,4 +467,157 @@ impl ErrorHandler {
     ) -> Self {
         Self::TimeoutError { duration }
     }
+
+    /// # Returns
+    /// * `&'static str`: The name of this error's variant, e.g.
+    ///                   for labelling error metrics.
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            Self::Api { .. }                    => "Api",
+            Self::AuthenticationError(_)        => "AuthenticationError",
+            Self::Challenge(_)                  => "Challenge",
+            Self::ChallengeSolvingError(_)      => "ChallengeSolvingError",
+            Self::ChallengeVerificationError(_) => "ChallengeVerificationError",
+            Self::Config(_)                     => "Config",
+            Self::ConfigurationError(_)         => "ConfigurationError",
+            Self::Deserialization { .. }        => "Deserialization",
+            Self::InternalError                 => "InternalError",
+            Self::InvalidConfig(_)              => "InvalidConfig",
+            Self::InvalidRequest(_)             => "InvalidRequest",
+            Self::InvalidSolution(_)            => "InvalidSolution",
+            Self::Io(_)                         => "Io",
+            Self::NetworkError(_)               => "NetworkError",
+            Self::NotFoundError(_)              => "NotFoundError",
+            Self::PermissionError(_)            => "PermissionError",
+            Self::ProcessingError(_)            => "ProcessingError",
+            Self::RateLimitError(_)             => "RateLimitError",
+            Self::SerializationError(_)         => "SerializationError",
+            Self::SignatureVerificationError(_) => "SignatureVerificationError",
+            Self::Solve(_)                      => "Solve",
+            Self::TimeoutError { .. }           => "TimeoutError",
+            #[cfg(feature = "toml")]
+            Self::Toml(_)                       => "Toml",
+        }
+    }
+
+    /// Maps this error to a process exit code for command-line
+    /// callers. The mapping is stable across releases; new
+    /// variants are assigned to an existing category.
+    ///
+    /// * `1`: Internal, processing, I/O and (de)serialization errors.
+    /// * `2`: Network errors and timeouts, including
+    ///        `SolveError::TimedOut`.
+    /// * `3`: Invalid solutions, challenge failures and other
+    ///        solve errors, including `SolveError::Cancelled`.
+    /// * `4`: Configuration errors.
+    /// * `5`: Authentication and permission errors.
+    /// * `6`: Rate limiting.
+    /// * `7`: Invalid requests, unexpected API and not-found responses.
+    ///
+    /// # Returns
+    /// * `i32`: The exit code for this error's category.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::InternalError
+            | Self::ProcessingError(_)
+            | Self::Io(_)
+            | Self::SerializationError(_)
+            | Self::Deserialization { .. }      => 1,
+            Self::NetworkError(_)
+            | Self::TimeoutError { .. }
+            | Self::Solve(SolveError::TimedOut { .. }) => 2,
+            Self::Challenge(_)
+            | Self::ChallengeSolvingError(_)
+            | Self::ChallengeVerificationError(_)
+            | Self::InvalidSolution(_)
+            | Self::SignatureVerificationError(_)
+            | Self::Solve(_)                    => 3,
+            Self::Config(_)
+            | Self::ConfigurationError(_)
+            | Self::InvalidConfig(_)            => 4,
+            #[cfg(feature = "toml")]
+            Self::Toml(_)                       => 4,
+            Self::AuthenticationError(_)
+            | Self::PermissionError(_)          => 5,
+            Self::RateLimitError(_)             => 6,
+            Self::InvalidRequest(_)
+            | Self::Api { .. }
+            | Self::NotFoundError(_)            => 7,
+        }
+    }
+
+    /// Classifies an API rejection by the error message in its
+    /// body. Only `410 Gone` is specific enough to classify
+    /// when the message is not recognized.
+    ///
+    /// * `CLOCK_SKEW`:        `ErrorHandler::InvalidRequest`
+    /// * `CHALLENGE_EXPIRED`: `ErrorHandler::Challenge`
+    /// * `INVALID_SOLUTION`:  `ErrorHandler::InvalidSolution`
+    /// * `SIGNATURE_FAIL`:    `ErrorHandler::SignatureVerificationError`
+    ///
+    /// # Arguments
+    /// * `status`:  The HTTP status code of the rejection.
+    /// * `message`: The error message from the response body.
+    ///
+    /// # Returns
+    /// * `Option<Self>`: The matching error, carrying the
+    ///                   constant's message, or `None` if the
+    ///                   rejection is not a known one.
+    pub fn from_rejection(
+        status:  u16,
+        message: &str
+    ) -> Option<Self> {
+        let known: [ErrorInfo; 4] = [CLOCK_SKEW, CHALLENGE_EXPIRED, INVALID_SOLUTION, SIGNATURE_FAIL];
+
+        let info: ErrorInfo = known
+            .iter()
+            .find(|info| info.message == message.trim())
+            .cloned()
+            .or_else(|| (status == CHALLENGE_EXPIRED.status_code).then_some(CHALLENGE_EXPIRED))?;
+
+        Some(match info.message {
+            CLOCK_SKEW_MSG        => Self::InvalidRequest(info.message.to_string()),
+            CHALLENGE_EXPIRED_MSG => Self::Challenge(info.message.to_string()),
+            INVALID_SOLUTION_MSG  => Self::InvalidSolution(info.message.to_string()),
+            _                     => Self::SignatureVerificationError(info.message.to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_rejection() {
+        assert!(matches!(
+            ErrorHandler::from_rejection(STATUS_BAD_REQUEST, CLOCK_SKEW_MSG),
+            Some(ErrorHandler::InvalidRequest(_))
+        ));
+        assert!(matches!(
+            ErrorHandler::from_rejection(STATUS_GONE, "gone"),
+            Some(ErrorHandler::Challenge(message)) if message == CHALLENGE_EXPIRED_MSG
+        ));
+        assert!(matches!(
+            ErrorHandler::from_rejection(STATUS_UNPROCESSABLE_ENTITY, SIGNATURE_FAIL_MSG),
+            Some(ErrorHandler::SignatureVerificationError(message)) if message == SIGNATURE_FAIL_MSG
+        ));
+        assert!(matches!(
+            ErrorHandler::from_rejection(STATUS_UNPROCESSABLE_ENTITY, INVALID_SOLUTION_MSG),
+            Some(ErrorHandler::InvalidSolution(message)) if message == INVALID_SOLUTION_MSG
+        ));
+        assert!(ErrorHandler::from_rejection(STATUS_UNPROCESSABLE_ENTITY, "Something else").is_none());
+    }
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(ErrorHandler::InternalError.exit_code(), 1);
+        assert_eq!(ErrorHandler::timeout(Duration::from_secs(1)).exit_code(), 2);
+        assert_eq!(ErrorHandler::Solve(SolveError::TimedOut { elapsed: Duration::from_secs(1) }).exit_code(), 2);
+        assert_eq!(ErrorHandler::Solve(SolveError::Cancelled).exit_code(), 3);
+        assert_eq!(ErrorHandler::ChallengeVerificationError("bad".to_string()).exit_code(), 3);
+        assert_eq!(ErrorHandler::InvalidSolution("bad".to_string()).exit_code(), 3);
+        assert_eq!(ErrorHandler::InvalidConfig(Vec::new()).exit_code(), 4);
+        assert_eq!(ErrorHandler::RateLimitError("slow".to_string()).exit_code(), 6);
+    }
 }
\ No newline at end of file
@@ -5,24 +5,93 @@ pub mod handler {
 }
 
 pub mod client {
+    pub mod cache;
+    pub mod challenge;
+    pub mod circuit;
+    pub mod clock;
+    #[cfg(feature = "gzip")]
+    pub mod compression;
     pub mod config;
     pub mod http;
+    pub mod metrics;
+    pub mod progress;
     pub mod request;
     pub mod response;
+    pub mod retry;
+    #[cfg(feature = "hmac")]
+    pub mod signing;
     pub mod solve;
+    pub mod stats;
+    pub mod token;
     pub mod validate;
 }
 
-pub use constant::USER_AGENT;
+pub use constant::{
+    client_version_string,
+    CLIENT_VERSION_HEADER,
+    PROTOCOL_VERSION,
+    USER_AGENT
+};
 pub use client::config::ClientConfig;
+pub use client::metrics::SolverMetrics;
 pub use client::request::IronShieldClient;
 pub use client::solve::{
+    calibrate,
+    challenge_to_pow_config,
+    estimate_solve_memory,
+    recommended_thread_count,
     solve_challenge,
+    solve_challenge_cancellable,
+    solve_challenge_into,
+    solve_challenge_json,
+    solve_challenge_local,
+    solve_challenge_with_context,
+    solve_challenge_with_stats,
+    solve_challenges,
+    solve_pipeline,
+    solve_prepared,
+    solve_resumable,
+    solve_with_config,
+    solve_with_deadline,
+    CalibrationReport,
+    PowConfigMapper,
+    ResumableSolve,
     SolveConfig,
-    ProgressTracker
+    SolveContext,
+    SolveHandle,
+    Solver,
+    SolveStrategy,
+    StopReason,
+    ProgressTracker,
+    ThreadLifecycleObserver
+};
+#[cfg(feature = "test-util")]
+pub use client::solve::{
+    solve_blocking,
+    FixedSolver
+};
+#[cfg(feature = "rayon")]
+pub use client::solve::solve_on_pool;
+pub use client::stats::{
+    SolveStats,
+    ThreadStats
+};
+pub use client::validate::{
+    resume_validation,
+    validate_challenge,
+    validate_challenge_report,
+    validate_challenge_resumable,
+    validate_challenge_timed,
+    validate_challenge_with_deadline,
+    validate_prefetched_challenge,
+    FlowOutcome,
+    FlowReport,
+    TimingBreakdown,
+    ValidationOutcome,
+    ValidationState
 };
-pub use client::validate::validate_challenge;
 
+pub use ironshield_core::PoWConfig;
 pub use ironshield_types::{
     IronShieldChallenge,
     IronShieldChallengeResponse,